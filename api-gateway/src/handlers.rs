@@ -27,6 +27,8 @@ use tokio_util::io::ReaderStream;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use genetics_processor::parsers::PgsNormalization as ProcessorPgsNormalization;
+
 use crate::{
     // PUBLIC PLATFORM: No authentication middleware needed
     models::*,
@@ -114,11 +116,13 @@ pub async fn submit_job(
         .await
         .map_err(|e| AppError::Internal(format!("Failed to create upload directory: {}", e)))?;
 
-    let mut genome_file: Option<PathBuf> = None;
+    let mut genome_files: Vec<PathBuf> = Vec::new(); // One raw data file, or a PLINK .bed/.bim/.fam fileset
     let mut vcf_files: Vec<PathBuf> = Vec::new();
     let mut pgs_file: Option<PathBuf> = None;
     let mut output_formats = vec![OutputFormat::Parquet, OutputFormat::Vcf]; // Default formats (Parquet for analytics, VCF for bioinformatics)
     let mut quality_threshold = QualityThreshold::default(); // Default R² ≥ 0.9
+    let mut pgs_normalization = PgsNormalization::default(); // Default reference-only z-score
+    let mut haploid_convention = HaploidConvention::default(); // Default 0/1 haploid dosages
     let mut user_email: Option<String> = None; // REQUIRED: Email for job ownership and notifications
     let mut vcf_format = "merged".to_string(); // Default to merged VCF
 
@@ -152,7 +156,7 @@ pub async fn submit_job(
                     .await
                     .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
 
-                genome_file = Some(file_path);
+                genome_files.push(file_path);
                 info!("Saved genome file: {}", validated.safe_name);
             }
 
@@ -206,6 +210,30 @@ pub async fn submit_job(
                 info!("Saved PGS file: {}", validated.safe_name);
             }
 
+            "info_file" => {
+                let filename = field.file_name().unwrap_or("chr.info.gz").to_string();
+                let data = field.bytes().await
+                    .map_err(|e| AppError::BadRequest(format!("Failed to read info file: {}", e)))?;
+
+                // SECURITY: Validate file before writing to disk
+                let validated = validator.validate_upload(&filename, &data, "vcf")
+                    .map_err(|e| AppError::BadRequest(format!("Invalid info file: {}", e)))?;
+
+                info!("Info file validated: {} ({} bytes, SHA256: {})",
+                    validated.safe_name, validated.size, &validated.hash_sha256[..16]);
+
+                // Save file using sanitized filename (the worker finds .info files by extension)
+                let file_path = job_upload_dir.join(&validated.safe_name);
+                let mut file = tokio::fs::File::create(&file_path)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Failed to create file: {}", e)))?;
+                file.write_all(&data)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+
+                info!("Saved info file: {}", validated.safe_name);
+            }
+
             "output_formats" => {
                 let data = field.text().await
                     .map_err(|e| AppError::BadRequest(format!("Failed to read output formats: {}", e)))?;
@@ -241,6 +269,35 @@ pub async fn submit_job(
                 };
             }
 
+            "pgs_normalization" => {
+                let data = field.text().await
+                    .map_err(|e| AppError::BadRequest(format!("Failed to read PGS normalization: {}", e)))?;
+
+                // Parse PGS normalization method
+                pgs_normalization = match ProcessorPgsNormalization::from_name(&data) {
+                    Some(method) => method.into(),
+                    None => {
+                        warn!("Unknown PGS normalization '{}', using default (reference_zscore)", data);
+                        PgsNormalization::default()
+                    }
+                };
+            }
+
+            "haploid_convention" => {
+                let data = field.text().await
+                    .map_err(|e| AppError::BadRequest(format!("Failed to read haploid convention: {}", e)))?;
+
+                // Parse haploid dosage coding
+                haploid_convention = match data.trim().to_lowercase().as_str() {
+                    "allele_count" | "0/1" => HaploidConvention::AlleleCount,
+                    "homozygous" | "0/2" => HaploidConvention::Homozygous,
+                    _ => {
+                        warn!("Unknown haploid convention '{}', using default (allele_count)", data);
+                        HaploidConvention::AlleleCount
+                    }
+                };
+            }
+
             "user_email" => {
                 let email = field.text().await
                     .map_err(|e| AppError::BadRequest(format!("Failed to read user email: {}", e)))?;
@@ -288,17 +345,41 @@ pub async fn submit_job(
 
     info!("Job output formats: {:?}", output_formats);
     info!("Job quality threshold: {:?}", quality_threshold);
+    info!("Job PGS normalization: {:?}", pgs_normalization);
+    info!("Job haploid convention: {:?}", haploid_convention);
 
     // Validate required files
-    let genome_file = genome_file
-        .ok_or_else(|| AppError::BadRequest("Missing genome_file".to_string()))?;
+    if genome_files.is_empty() {
+        return Err(AppError::BadRequest("Missing genome_file".to_string()));
+    }
+
+    let genome_names: Vec<String> = genome_files
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    let plink_prefix = validator.group_plink_files(&genome_names)
+        .map_err(|e| AppError::BadRequest(format!("Invalid genome file: {}", e)))?;
+
+    match plink_prefix {
+        Some(prefix) => info!("Job {} genome data is PLINK fileset {}", job_id, prefix),
+        None if genome_files.len() > 1 => {
+            return Err(AppError::BadRequest(
+                "Expected one genome_file or a PLINK .bed/.bim/.fam fileset".to_string(),
+            ));
+        }
+        None => {}
+    }
 
     if vcf_files.is_empty() {
         return Err(AppError::BadRequest("Missing vcf_file(s)".to_string()));
     }
 
-    let pgs_file = pgs_file
-        .ok_or_else(|| AppError::BadRequest("Missing pgs_file".to_string()))?;
+    // PGS scores are optional: without an uploaded scores file the worker
+    // computes them from its PGS Catalog scoring files
+    if pgs_file.is_none() {
+        info!("Job {} has no pgs_file, scores will be computed by the worker", job_id);
+    }
 
     // PUBLIC PLATFORM: Require email for job ownership and notifications
     let user_email = user_email
@@ -307,7 +388,9 @@ pub async fn submit_job(
     // Create job in database with VCF format metadata
     let created_at = Utc::now();
     let metadata = serde_json::json!({
-        "vcf_format": vcf_format
+        "vcf_format": vcf_format,
+        "pgs_normalization": pgs_normalization,
+        "haploid_convention": haploid_convention
     });
 
     // PUBLIC PLATFORM: Use email as user_id (no RLS/authentication needed)
@@ -339,6 +422,8 @@ pub async fn submit_job(
         output_dir: job_results_dir.to_string_lossy().to_string(),
         output_formats: output_formats.clone(),
         quality_threshold,
+        pgs_normalization,
+        haploid_convention,
         chunked_upload: false,  // Phase 7.1: Standard upload, no reassembly needed
         upload_session_id: None,  // Phase 7.1: Only for chunked uploads
     };
@@ -363,8 +448,8 @@ pub async fn get_job_status(
 ) -> Result<Json<JobStatusResponse>, AppError> {
     // PUBLIC PLATFORM: Anyone with job_id can check status (no authentication required)
     // Query job from database
-    let job = sqlx::query_as::<_, (uuid::Uuid, String, String, chrono::DateTime<Utc>, Option<chrono::DateTime<Utc>>, Option<chrono::DateTime<Utc>>, Option<String>)>(
-        "SELECT id, user_id, status, created_at, started_at, completed_at, error_message FROM genetics_jobs WHERE id = $1"
+    let job = sqlx::query_as::<_, (uuid::Uuid, String, String, chrono::DateTime<Utc>, Option<chrono::DateTime<Utc>>, Option<chrono::DateTime<Utc>>, Option<String>, Option<serde_json::Value>)>(
+        "SELECT id, user_id, status, created_at, started_at, completed_at, error_message, metadata->'parse_reports' FROM genetics_jobs WHERE id = $1"
     )
     .bind(job_id)
     .fetch_optional(state.db_pool())
@@ -372,7 +457,7 @@ pub async fn get_job_status(
     .map_err(|e| AppError::Internal(format!("Database error: {}", e)))?
     .ok_or(AppError::NotFound)?;
 
-    let (job_id_db, user_id_db, status_str, created_at_db, started_at_db, completed_at_db, error_message_db) = job;
+    let (job_id_db, user_id_db, status_str, created_at_db, started_at_db, completed_at_db, error_message_db, parse_reports_db) = job;
 
     let status = match status_str.as_str() {
         "queued" => JobStatus::Queued,
@@ -414,6 +499,7 @@ pub async fn get_job_status(
             vcf_files: vec!["chr1-22.vcf.gz".to_string()],
             pgs_file: "scores.txt".to_string(),
         },
+        parse_reports: parse_reports_db,
     }))
 }
 
@@ -767,6 +853,8 @@ pub async fn finalize_upload(
     let mut upload_id: Option<String> = None;
     let mut output_formats = Vec::new();
     let mut quality_threshold = QualityThreshold::default(); // Default R² ≥ 0.9
+    let mut pgs_normalization = PgsNormalization::default(); // Default reference-only z-score
+    let mut haploid_convention = HaploidConvention::default(); // Default 0/1 haploid dosages
     let mut user_email: Option<String> = None; // REQUIRED: Email for job ownership and notifications
     let mut vcf_format = "merged".to_string(); // Default to merged VCF
 
@@ -816,6 +904,33 @@ pub async fn finalize_upload(
                     }
                 };
             }
+            "pgs_normalization" => {
+                let data = field.text().await
+                    .map_err(|e| AppError::BadRequest(format!("Failed to read PGS normalization: {}", e)))?;
+
+                // Parse PGS normalization method
+                pgs_normalization = match ProcessorPgsNormalization::from_name(&data) {
+                    Some(method) => method.into(),
+                    None => {
+                        warn!("Unknown PGS normalization '{}', using default (reference_zscore)", data);
+                        PgsNormalization::default()
+                    }
+                };
+            }
+            "haploid_convention" => {
+                let data = field.text().await
+                    .map_err(|e| AppError::BadRequest(format!("Failed to read haploid convention: {}", e)))?;
+
+                // Parse haploid dosage coding
+                haploid_convention = match data.trim().to_lowercase().as_str() {
+                    "allele_count" | "0/1" => HaploidConvention::AlleleCount,
+                    "homozygous" | "0/2" => HaploidConvention::Homozygous,
+                    _ => {
+                        warn!("Unknown haploid convention '{}', using default (allele_count)", data);
+                        HaploidConvention::AlleleCount
+                    }
+                };
+            }
             "user_email" => {
                 let email = field.text().await
                     .map_err(|e| AppError::BadRequest(format!("Failed to read user email: {}", e)))?;
@@ -868,6 +983,8 @@ pub async fn finalize_upload(
     info!("Finalizing chunked upload: {}", upload_id);
     info!("Job output formats: {:?}", output_formats);
     info!("Job quality threshold: {:?}", quality_threshold);
+    info!("Job PGS normalization: {:?}", pgs_normalization);
+    info!("Job haploid convention: {:?}", haploid_convention);
 
     // Generate job ID
     let job_id = Uuid::new_v4();
@@ -890,7 +1007,9 @@ pub async fn finalize_upload(
     // Create job in database with VCF format metadata
     let created_at = Utc::now();
     let metadata = serde_json::json!({
-        "vcf_format": vcf_format
+        "vcf_format": vcf_format,
+        "pgs_normalization": pgs_normalization,
+        "haploid_convention": haploid_convention
     });
 
     // PUBLIC PLATFORM: Use email as user_id (no RLS/authentication needed)
@@ -922,6 +1041,8 @@ pub async fn finalize_upload(
         output_dir: job_results_dir.to_string_lossy().to_string(),
         output_formats: output_formats.clone(),
         quality_threshold,
+        pgs_normalization,
+        haploid_convention,
         chunked_upload: true,  // Phase 7.1: Worker will reassemble chunks
         upload_session_id: Some(upload_id.clone()),  // Phase 7.1: For chunk reassembly
     };
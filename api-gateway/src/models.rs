@@ -12,6 +12,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use genetics_processor::parsers::PgsNormalization as ProcessorPgsNormalization;
+
 /// Job submission response
 #[derive(Debug, Serialize)]
 pub struct JobSubmitResponse {
@@ -34,6 +36,9 @@ pub struct JobStatusResponse {
     pub error_message: Option<String>,
     pub output_formats: Vec<String>,
     pub files: JobFiles,
+    /// Per-file parse diagnostics recorded by the worker (rejected lines, filtering counts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_reports: Option<serde_json::Value>,
 }
 
 /// Job files information
@@ -90,6 +95,39 @@ impl Default for QualityThreshold {
     }
 }
 
+/// Normalization applied to scaled polygenic scores (must match worker queue.rs)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PgsNormalization {
+    #[default]
+    ReferenceZscore,    // z-score against the 50 reference samples (default)
+    Zscore,             // z-score against all 51 samples, including the user
+    RankInverseNormal,  // Rank-based inverse normal transform
+    MinMax,             // Rescaled to 0-1
+    None,               // Raw scores
+}
+
+impl From<ProcessorPgsNormalization> for PgsNormalization {
+    fn from(method: ProcessorPgsNormalization) -> Self {
+        match method {
+            ProcessorPgsNormalization::ReferenceZScore => PgsNormalization::ReferenceZscore,
+            ProcessorPgsNormalization::ZScore => PgsNormalization::Zscore,
+            ProcessorPgsNormalization::RankInverseNormal => PgsNormalization::RankInverseNormal,
+            ProcessorPgsNormalization::MinMax => PgsNormalization::MinMax,
+            ProcessorPgsNormalization::None => PgsNormalization::None,
+        }
+    }
+}
+
+/// Dosage coding of haploid genotypes in the output (must match worker queue.rs)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HaploidConvention {
+    #[default]
+    AlleleCount,        // 0/1 ALT allele count (default)
+    Homozygous,         // 0/2, hemizygous calls coded as homozygotes (PLINK-style)
+}
+
 impl OutputFormat {
     pub fn extension(&self) -> &'static str {
         match self {
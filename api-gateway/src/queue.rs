@@ -13,7 +13,7 @@ use redis::{Client, Commands};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::models::{OutputFormat, QualityThreshold};
+use crate::models::{HaploidConvention, OutputFormat, PgsNormalization, QualityThreshold};
 
 const QUEUE_KEY: &str = "genetics:job_queue";
 const JOB_PREFIX: &str = "genetics:job:";
@@ -34,6 +34,12 @@ pub struct JobPayload {
     pub chunked_upload: bool,
     /// Phase 7.1: Upload session ID for chunk reassembly (if chunked_upload=true)
     pub upload_session_id: Option<String>,
+    /// Normalization for scaled polygenic scores (defaults to reference_zscore)
+    #[serde(default)]
+    pub pgs_normalization: PgsNormalization,
+    /// Haploid genotype dosage coding (defaults to allele_count, 0/1)
+    #[serde(default)]
+    pub haploid_convention: HaploidConvention,
 }
 
 /// Job queue manager
@@ -16,6 +16,8 @@ use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read};
 use tracing::{debug, info, warn};
 
+use genetics_processor::parsers::{is_genotype_csv_header, ImputationInfoParser, ParseReport, PgsParser};
+
 // Maximum file sizes (enforced at validation layer)
 const MAX_GENOME_FILE_SIZE: usize = 100 * 1024 * 1024; // 100 MB
 const MAX_VCF_FILE_SIZE: usize = 200 * 1024 * 1024;    // 200 MB
@@ -40,18 +42,30 @@ impl FileValidator {
     pub fn new() -> Self {
         let mut allowed_types = HashMap::new();
 
-        // 23andMe raw text file (plain text, no specific magic number)
+        // 23andMe / AncestryDNA raw text file (plain text, no specific magic number)
         allowed_types.insert("txt".to_string(), vec![]);
 
+        // MyHeritage / FamilyTreeDNA raw CSV file (plain text)
+        allowed_types.insert("csv".to_string(), vec![]);
+
         // Gzip compressed files (VCF.gz)
         allowed_types.insert("vcf.gz".to_string(), vec![0x1f, 0x8b, 0x08]);
 
         // BGZF tabix index
         allowed_types.insert("vcf.gz.tbi".to_string(), vec![0x1f, 0x8b, 0x08]);
 
+        // Imputation server per-variant quality (chrN.info.gz, or uncompressed)
+        allowed_types.insert("info.gz".to_string(), vec![0x1f, 0x8b, 0x08]);
+        allowed_types.insert("info".to_string(), vec![]);
+
         // PGS score files
         allowed_types.insert("pgs".to_string(), vec![]);
 
+        // PLINK binary fileset (variant-major .bed plus text .bim/.fam)
+        allowed_types.insert("bed".to_string(), vec![0x6c, 0x1b, 0x01]);
+        allowed_types.insert("bim".to_string(), vec![]);
+        allowed_types.insert("fam".to_string(), vec![]);
+
         Self { allowed_types }
     }
 
@@ -110,7 +124,7 @@ impl FileValidator {
         }
 
         // 5. Content validation (basic format check)
-        self.validate_content(file_data, &ext)?;
+        self.validate_content(file_data, &ext, file_type)?;
         debug!("Content validation passed");
 
         // 6. Compute SHA-256 hash
@@ -157,6 +171,8 @@ impl FileValidator {
             return Ok("vcf.gz".to_string());
         } else if filename.ends_with(".vcf.gz.tbi") {
             return Ok("vcf.gz.tbi".to_string());
+        } else if filename.ends_with(".info.gz") {
+            return Ok("info.gz".to_string());
         } else if filename.ends_with(".pgs") || filename.ends_with(".txt.gz") {
             // PGS files can be .pgs or .txt.gz
             return Ok("pgs".to_string());
@@ -175,25 +191,41 @@ impl FileValidator {
             && expected.iter().zip(actual.iter()).all(|(e, a)| e == a)
     }
 
-    fn validate_content(&self, data: &Bytes, ext: &str) -> Result<()> {
+    fn validate_content(&self, data: &Bytes, ext: &str, file_type: &str) -> Result<()> {
+        // PGS scores may arrive as .txt/.csv, so check them by upload type
+        if file_type == "pgs" {
+            return self.validate_pgs_scores(data);
+        }
+
         match ext {
-            "txt" => self.validate_23andme_format(data),
+            "txt" | "csv" => self.validate_23andme_format(data),
             "vcf.gz" => self.validate_vcf_format(data),
             "vcf.gz.tbi" => Ok(()), // Tabix index, no content validation needed
-            "pgs" => self.validate_pgs_format(data),
+            "pgs" => self.validate_pgs_scores(data),
+            "info.gz" | "info" => self.validate_imputation_info(data, ext),
+            "bim" | "fam" => self.validate_plink_text(data, ext),
             _ => Ok(()),
         }
     }
 
+    /// Validate a consumer raw genotype file
+    ///
+    /// Accepts 23andMe (4 tab-delimited columns), AncestryDNA (5 columns with
+    /// allele1/allele2) and MyHeritage/FTDNA quoted CSV with a
+    /// `RSID,CHROMOSOME,POSITION,RESULT` header.
     fn validate_23andme_format(&self, data: &Bytes) -> Result<()> {
         let reader = BufReader::new(&data[..]);
         let mut lines = reader.lines();
 
-        // Check for 23andMe header
+        // Check for 23andMe header (or the FTDNA CSV header, which has no comments)
         let first_line = lines
             .next()
             .ok_or_else(|| anyhow::anyhow!("File is empty"))??;
 
+        if is_genotype_csv_header(&first_line) {
+            return Ok(());
+        }
+
         if !first_line.contains("23andMe") && !first_line.starts_with('#') {
             anyhow::bail!("Not a valid 23andMe format file");
         }
@@ -203,11 +235,17 @@ impl FileValidator {
             // Only check first 100 lines
             let line = line?;
             if !line.starts_with('#') && !line.trim().is_empty() {
+                // MyHeritage: comment block followed by the CSV header
+                if is_genotype_csv_header(&line) {
+                    break;
+                }
+
                 // First data line should have 4 columns: rsid, chromosome, position, genotype
+                // (AncestryDNA: 5 columns with separate allele1/allele2)
                 let columns: Vec<&str> = line.split_whitespace().collect();
-                if columns.len() != 4 {
+                if columns.len() != 4 && columns.len() != 5 {
                     anyhow::bail!(
-                        "Invalid 23andMe format: expected 4 columns, found {}",
+                        "Invalid 23andMe format: expected 4 or 5 columns, found {}",
                         columns.len()
                     );
                 }
@@ -236,31 +274,92 @@ impl FileValidator {
         Ok(())
     }
 
-    fn validate_pgs_format(&self, data: &Bytes) -> Result<()> {
-        // PGS files are tab-separated or space-separated text files
-        // Should have at least a header line with rsid and effect columns
+    /// Validate a PGS scores file with the parser the worker uses
+    ///
+    /// Accepts long-form (ID,PGS_label,score_value) or wide-form scores,
+    /// comma-, tab- or semicolon-separated, optionally gzip-compressed.
+    fn validate_pgs_scores(&self, data: &Bytes) -> Result<()> {
+        let dataset = PgsParser::parse_reader(&data[..], ParseReport::new("upload"))
+            .context("Invalid PGS format")?;
+        debug!("PGS scores parsed: {} records", dataset.unscaled.len());
+
+        Ok(())
+    }
+
+    /// Validate an imputation server .info file with the parser the worker uses
+    fn validate_imputation_info(&self, data: &Bytes, ext: &str) -> Result<()> {
+        let report = ParseReport::new("upload");
+        let (records, report) = if ext == "info.gz" {
+            let decoder = flate2::read::MultiGzDecoder::new(&data[..]);
+            ImputationInfoParser::parse_reader(BufReader::new(decoder), report)
+        } else {
+            ImputationInfoParser::parse_reader(BufReader::new(&data[..]), report)
+        }
+        .context("Invalid imputation .info format")?;
+        debug!(
+            "Imputation info parsed: {} variants on {} chromosome(s)",
+            report.records_accepted,
+            records.len()
+        );
+
+        Ok(())
+    }
+
+    /// Validate a PLINK .bim or .fam file (6 whitespace-separated columns)
+    fn validate_plink_text(&self, data: &Bytes, ext: &str) -> Result<()> {
         let reader = BufReader::new(&data[..]);
-        let mut lines = reader.lines();
 
-        // Check first non-comment line
-        for line in lines.take(100) {
-            let line = line?;
-            if !line.starts_with('#') && !line.trim().is_empty() {
-                // Should have at least 2 columns
-                let columns: Vec<&str> = line.split_whitespace().collect();
-                if columns.len() < 2 {
-                    anyhow::bail!(
-                        "Invalid PGS format: expected at least 2 columns, found {}",
-                        columns.len()
-                    );
-                }
-                break;
-            }
+        let first_line = reader
+            .lines()
+            .find(|line| line.as_ref().map(|l| !l.trim().is_empty()).unwrap_or(true))
+            .ok_or_else(|| anyhow::anyhow!(".{} file is empty", ext))??;
+
+        let columns = first_line.split_whitespace().count();
+        if columns != 6 {
+            anyhow::bail!("Invalid PLINK .{} format: expected 6 columns, found {}", ext, columns);
         }
 
         Ok(())
     }
 
+    /// Group uploaded genome files into a PLINK fileset
+    ///
+    /// # Returns
+    /// * `Ok(Some(prefix))` - The files are exactly one .bed, .bim and .fam
+    ///   sharing a prefix
+    /// * `Ok(None)` - No PLINK files were uploaded
+    /// * `Err` - PLINK files are incomplete, mismatched or mixed with other files
+    pub fn group_plink_files(&self, filenames: &[String]) -> Result<Option<String>> {
+        let plink: Vec<(&str, &str)> = filenames
+            .iter()
+            .filter_map(|name| {
+                let (prefix, ext) = name.rsplit_once('.')?;
+                matches!(ext, "bed" | "bim" | "fam").then_some((prefix, ext))
+            })
+            .collect();
+
+        if plink.is_empty() {
+            return Ok(None);
+        }
+        if plink.len() != filenames.len() {
+            anyhow::bail!("PLINK files cannot be combined with other genome files");
+        }
+
+        let prefix = plink[0].0;
+        if plink.iter().any(|(p, _)| *p != prefix) {
+            anyhow::bail!("PLINK .bed/.bim/.fam files must share a file name prefix");
+        }
+        for ext in ["bed", "bim", "fam"] {
+            match plink.iter().filter(|(_, e)| *e == ext).count() {
+                1 => {}
+                0 => anyhow::bail!("PLINK fileset is missing its .{} file", ext),
+                _ => anyhow::bail!("PLINK fileset has more than one .{} file", ext),
+            }
+        }
+
+        Ok(Some(prefix.to_string()))
+    }
+
     fn compute_sha256(&self, data: &Bytes) -> String {
         let mut hasher = Sha256::new();
         hasher.update(data);
@@ -350,6 +449,8 @@ mod tests {
             validator.get_extension("chr1.dose.vcf.gz.tbi").unwrap(),
             "vcf.gz.tbi"
         );
+
+        assert_eq!(validator.get_extension("chr1.info.gz").unwrap(), "info.gz");
     }
 
     #[test]
@@ -362,4 +463,64 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("too large"));
     }
+
+    #[test]
+    fn test_validate_genotype_csv_format() {
+        let validator = FileValidator::new();
+
+        // MyHeritage: comment block then CSV header
+        let myheritage = Bytes::from(
+            "# MyHeritage DNA raw data.\nRSID,CHROMOSOME,POSITION,RESULT\n\"rs1\",\"1\",\"100\",\"AA\"\n",
+        );
+        assert!(validator.validate_upload("MyHeritage_raw.csv", &myheritage, "genome").is_ok());
+
+        // FTDNA: quoted CSV header on the first line
+        let ftdna = Bytes::from(
+            "\"RSID\",\"CHROMOSOME\",\"POSITION\",\"RESULT\"\n\"rs1\",\"1\",\"100\",\"AA\"\n",
+        );
+        assert!(validator.validate_upload("ftdna_raw.csv", &ftdna, "genome").is_ok());
+
+        // Unrelated CSV is still rejected
+        let other = Bytes::from("name,value\nfoo,1\n");
+        assert!(validator.validate_upload("other.csv", &other, "genome").is_err());
+    }
+
+    #[test]
+    fn test_plink_fileset() {
+        let validator = FileValidator::new();
+
+        let bed = Bytes::from(vec![0x6c, 0x1b, 0x01, 0b00_10_11]);
+        assert!(validator.validate_upload("array.bed", &bed, "genome").is_ok());
+        let bim = Bytes::from("1\trs1\t0\t1000\tA\tG\n");
+        assert!(validator.validate_upload("array.bim", &bim, "genome").is_ok());
+        let fam = Bytes::from("F1 I1 0 0 1 -9\n");
+        assert!(validator.validate_upload("array.fam", &fam, "genome").is_ok());
+        assert!(validator.validate_upload("array.fam", &Bytes::from("F1 I1\n"), "genome").is_err());
+
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            validator.group_plink_files(&names(&["array.bim", "array.bed", "array.fam"])).unwrap(),
+            Some("array".to_string())
+        );
+        assert_eq!(validator.group_plink_files(&names(&["genome.txt"])).unwrap(), None);
+        assert!(validator.group_plink_files(&names(&["array.bed", "array.bim"])).is_err());
+        assert!(validator.group_plink_files(&names(&["array.bed", "array.bim", "other.fam"])).is_err());
+    }
+
+    #[test]
+    fn test_validate_pgs_scores() {
+        let validator = FileValidator::new();
+
+        // Wide-form Michigan Imputation Server scores, tab-separated
+        let wide = Bytes::from("sample\tPGS000001\tPGS000002\nsamp1\t0.5\t-1.2\n");
+        assert!(validator.validate_upload("scores.txt", &wide, "pgs").is_ok());
+
+        // Long-form, semicolon-separated
+        let long = Bytes::from("ID;PGS_label;score_value\nsamp1;Height;1.5\n");
+        assert!(validator.validate_upload("scores.csv", &long, "pgs").is_ok());
+
+        // Non-numeric scores are rejected
+        let invalid = Bytes::from("sample,PGS000001\nsamp1,high\n");
+        assert!(validator.validate_upload("scores.txt", &invalid, "pgs").is_err());
+    }
 }
@@ -0,0 +1,269 @@
+// ==============================================================================
+// chromosomes.rs - Chromosome Encoding and Ploidy
+// ==============================================================================
+// Description: Numeric chromosome codes for autosomes, sex chromosomes and
+//              mitochondria, pseudoautosomal regions, and sex inference
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Encoding (PLINK convention, used in the reference panel and all outputs):
+//   1-22 = autosomes, 23 = X, 24 = Y, 26 = MT
+//   Pseudoautosomal (PAR) variants stay on X (23) and are diploid in males.
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+
+use crate::parsers::GenomeByChromosome;
+
+/// Numeric code for chromosome X
+pub const CHR_X: u8 = 23;
+
+/// Numeric code for chromosome Y
+pub const CHR_Y: u8 = 24;
+
+/// Numeric code for the mitochondrial genome
+pub const CHR_MT: u8 = 26;
+
+/// Every chromosome processed by the pipeline, in output order
+pub const ALL_CHROMOSOMES: [u8; 25] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22,
+    CHR_X, CHR_Y, CHR_MT,
+];
+
+/// Description of the chromosome encoding (recorded in output metadata)
+pub const CHROMOSOME_ENCODING: &str = "1-22=autosomes,23=X,24=Y,26=MT";
+
+/// GRCh37 pseudoautosomal regions on chromosome X (1-based, inclusive)
+const PAR_REGIONS_GRCH37: [(u64, u64); 2] = [
+    (60_001, 2_699_520),       // PAR1
+    (154_931_044, 155_260_560), // PAR2
+];
+
+/// Fraction of heterozygous non-PAR X calls below which a sample is male
+const MALE_MAX_X_HET_RATE: f64 = 0.03;
+
+/// Fraction of heterozygous non-PAR X calls above which a sample is female
+const FEMALE_MIN_X_HET_RATE: f64 = 0.10;
+
+/// Minimum number of called non-PAR X markers needed to infer sex
+const MIN_X_CALLS_FOR_SEX: usize = 100;
+
+/// Genetic sex, used to determine ploidy on X and Y
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sex {
+    Male,
+    Female,
+    Unknown,
+}
+
+impl Sex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "Male",
+            Sex::Female => "Female",
+            Sex::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Check whether a chromosome code is an autosome (1-22)
+pub fn is_autosome(chromosome: u8) -> bool {
+    (1..=22).contains(&chromosome)
+}
+
+/// Display name for a chromosome code ("1"-"22", "X", "Y", "MT")
+///
+/// Used for VCF CHROM values (prefixed with "chr"), output filenames and
+/// matching against raw genotype files, which use 23andMe naming.
+pub fn chromosome_name(chromosome: u8) -> String {
+    match chromosome {
+        CHR_X => "X".to_string(),
+        CHR_Y => "Y".to_string(),
+        CHR_MT => "MT".to_string(),
+        n => n.to_string(),
+    }
+}
+
+/// Parse a chromosome name into its numeric code
+///
+/// Accepts an optional "chr" prefix, numeric codes (including 23/24/25/26)
+/// and X, Y, XY (PAR, reported as X), M and MT.
+///
+/// # Returns
+/// * `Some(code)` - Recognized chromosome
+/// * `None` - Unrecognized chromosome (e.g., unplaced contigs)
+pub fn parse_chromosome(name: &str) -> Option<u8> {
+    let name = name.trim();
+    let name = name
+        .strip_prefix("chr")
+        .or_else(|| name.strip_prefix("CHR"))
+        .unwrap_or(name);
+
+    match name.to_ascii_uppercase().as_str() {
+        "X" | "XY" | "23" | "25" => Some(CHR_X),
+        "Y" | "24" => Some(CHR_Y),
+        "M" | "MT" | "26" => Some(CHR_MT),
+        other => match other.parse::<u8>() {
+            Ok(n) if is_autosome(n) => Some(n),
+            _ => None,
+        },
+    }
+}
+
+/// Check whether a position on X lies in a pseudoautosomal region (GRCh37)
+pub fn in_par(chromosome: u8, position: u64) -> bool {
+    chromosome == CHR_X
+        && PAR_REGIONS_GRCH37
+            .iter()
+            .any(|(start, end)| position >= *start && position <= *end)
+}
+
+/// Expected number of allele copies at a site for a sample of the given sex
+///
+/// Autosomes and PAR are diploid; non-PAR X is haploid in males; Y is
+/// haploid in males and absent (0) in females; MT is always haploid.
+/// Unknown sex is treated as diploid on X and haploid on Y.
+pub fn ploidy(chromosome: u8, position: u64, sex: Sex) -> u8 {
+    match chromosome {
+        CHR_X if in_par(chromosome, position) => 2,
+        CHR_X => match sex {
+            Sex::Male => 1,
+            Sex::Female | Sex::Unknown => 2,
+        },
+        CHR_Y => match sex {
+            Sex::Female => 0,
+            Sex::Male | Sex::Unknown => 1,
+        },
+        CHR_MT => 1,
+        _ => 2,
+    }
+}
+
+/// Infer genetic sex from non-PAR chromosome X genotype calls
+///
+/// Genotypes are raw two-letter calls ("AG") or single-letter hemizygous
+/// calls ("A"); no-calls ("--") are ignored. A low heterozygosity rate
+/// indicates one X chromosome.
+///
+/// # Arguments
+/// * `genotypes` - Raw genotype strings at non-PAR X positions
+pub fn infer_sex<'a, I>(genotypes: I) -> Sex
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut called = 0usize;
+    let mut heterozygous = 0usize;
+
+    for genotype in genotypes {
+        let alleles: Vec<char> = genotype.trim().chars().collect();
+        if alleles.is_empty() || alleles.iter().any(|c| *c == '-' || *c == '0') {
+            continue;
+        }
+
+        called += 1;
+        if alleles.len() == 2 && alleles[0] != alleles[1] {
+            heterozygous += 1;
+        }
+    }
+
+    if called < MIN_X_CALLS_FOR_SEX {
+        return Sex::Unknown;
+    }
+
+    let het_rate = heterozygous as f64 / called as f64;
+    if het_rate < MALE_MAX_X_HET_RATE {
+        Sex::Male
+    } else if het_rate > FEMALE_MIN_X_HET_RATE {
+        Sex::Female
+    } else {
+        Sex::Unknown
+    }
+}
+
+/// Infer genetic sex from a parsed raw genotype file
+///
+/// Uses the non-PAR chromosome X calls (see `infer_sex`).
+pub fn infer_sex_from_genome(genome: &GenomeByChromosome) -> Sex {
+    match genome.get("X") {
+        Some(x_records) => infer_sex(
+            x_records
+                .values()
+                .filter(|r| !in_par(CHR_X, r.position))
+                .map(|r| r.genotype.as_str()),
+        ),
+        None => Sex::Unknown,
+    }
+}
+
+/// Convert a diploid-coded dosage (0/1/2) at a haploid site to 0/1
+///
+/// Providers often report hemizygous calls as homozygous ("AA"); a
+/// heterozygous call at a haploid site is inconsistent and returns None.
+pub fn haploid_dosage(diploid_dosage: f64) -> Option<f64> {
+    if diploid_dosage < 0.5 {
+        Some(0.0)
+    } else if diploid_dosage > 1.5 {
+        Some(1.0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chromosome() {
+        assert_eq!(parse_chromosome("1"), Some(1));
+        assert_eq!(parse_chromosome("chr22"), Some(22));
+        assert_eq!(parse_chromosome("chrX"), Some(CHR_X));
+        assert_eq!(parse_chromosome("23"), Some(CHR_X));
+        assert_eq!(parse_chromosome("XY"), Some(CHR_X));
+        assert_eq!(parse_chromosome("Y"), Some(CHR_Y));
+        assert_eq!(parse_chromosome("chrM"), Some(CHR_MT));
+        assert_eq!(parse_chromosome("MT"), Some(CHR_MT));
+        assert_eq!(parse_chromosome("0"), None);
+        assert_eq!(parse_chromosome("chrUn_gl000220"), None);
+    }
+
+    #[test]
+    fn test_chromosome_name_round_trip() {
+        for chr in ALL_CHROMOSOMES {
+            assert_eq!(parse_chromosome(&chromosome_name(chr)), Some(chr));
+        }
+        assert_eq!(chromosome_name(CHR_MT), "MT");
+    }
+
+    #[test]
+    fn test_ploidy() {
+        assert_eq!(ploidy(1, 1000, Sex::Male), 2);
+        assert_eq!(ploidy(CHR_X, 100_000, Sex::Female), 2);
+        assert_eq!(ploidy(CHR_X, 5_000_000, Sex::Male), 1);
+        // PAR1 is diploid in males
+        assert_eq!(ploidy(CHR_X, 2_000_000, Sex::Male), 2);
+        assert_eq!(ploidy(CHR_Y, 10_000_000, Sex::Male), 1);
+        assert_eq!(ploidy(CHR_Y, 10_000_000, Sex::Female), 0);
+        assert_eq!(ploidy(CHR_MT, 16_000, Sex::Female), 1);
+    }
+
+    #[test]
+    fn test_infer_sex() {
+        let male: Vec<&str> = std::iter::repeat("A").take(150).chain(["AG"]).collect();
+        assert_eq!(infer_sex(male), Sex::Male);
+
+        let female: Vec<&str> = ["AG", "CC", "TT"].iter().cycle().take(150).copied().collect();
+        assert_eq!(infer_sex(female), Sex::Female);
+
+        assert_eq!(infer_sex(["AA", "--"]), Sex::Unknown);
+    }
+
+    #[test]
+    fn test_haploid_dosage() {
+        assert_eq!(haploid_dosage(0.0), Some(0.0));
+        assert_eq!(haploid_dosage(2.0), Some(1.0));
+        assert_eq!(haploid_dosage(1.0), None);
+    }
+}
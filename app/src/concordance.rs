@@ -0,0 +1,365 @@
+// ==============================================================================
+// concordance.rs - Genotyped vs Imputed Concordance
+// ==============================================================================
+// Description: Compares the user's genotyped calls with their imputed dosages
+//              per chromosome and MAF bin, and flags likely sample swaps or
+//              build mismatches
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Metrics (over variants with both a genotyped call and an imputed dosage):
+//   - Hard-call concordance: rounded imputed dosage equals the genotyped dosage
+//   - Dosage r²: squared Pearson correlation of genotyped and imputed dosages
+//   - Overlap: fraction of genotyped calls with an imputed dosage at the same
+//     position and alleles (imputation servers return typed sites too)
+//
+// Flags:
+//   - Build mismatch: overlap below BUILD_MISMATCH_OVERLAP
+//   - Sample swap: concordance below SAMPLE_SWAP_CONCORDANCE (unrelated
+//     individuals agree at roughly half of common sites)
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::chromosomes::chromosome_name;
+
+/// Minimum number of sites before a summary is used to raise a flag
+pub const MIN_FLAG_SITES: usize = 50;
+
+/// Hard-call concordance below which the files likely come from different people
+pub const SAMPLE_SWAP_CONCORDANCE: f64 = 0.9;
+
+/// Genotyped/imputed overlap below which positions likely use different builds
+pub const BUILD_MISMATCH_OVERLAP: f64 = 0.5;
+
+/// MAF bins by exclusive upper bound
+const MAF_BINS: [(f64, &str); 5] = [
+    (0.01, "0-0.01"),
+    (0.05, "0.01-0.05"),
+    (0.1, "0.05-0.1"),
+    (0.2, "0.1-0.2"),
+    (f64::INFINITY, "0.2-0.5"),
+];
+
+/// MAF bin label for a minor allele frequency ("unknown" without one)
+pub fn maf_bin(maf: Option<f64>) -> &'static str {
+    let Some(maf) = maf else {
+        return "unknown";
+    };
+    MAF_BINS
+        .iter()
+        .find(|(upper, _)| maf < *upper)
+        .map(|(_, label)| *label)
+        .unwrap_or("0.2-0.5")
+}
+
+/// Running sums for one group of sites
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    genotyped: usize,
+    compared: usize,
+    concordant: usize,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xx: f64,
+    sum_yy: f64,
+    sum_xy: f64,
+}
+
+impl Accumulator {
+    fn record(&mut self, genotyped: f64, imputed: Option<f64>) {
+        self.genotyped += 1;
+        let Some(imputed) = imputed else {
+            return;
+        };
+
+        self.compared += 1;
+        if imputed.round() == genotyped.round() {
+            self.concordant += 1;
+        }
+        self.sum_x += genotyped;
+        self.sum_y += imputed;
+        self.sum_xx += genotyped * genotyped;
+        self.sum_yy += imputed * imputed;
+        self.sum_xy += genotyped * imputed;
+    }
+
+    fn summary(&self) -> ConcordanceSummary {
+        let n = self.compared as f64;
+        let concordance = (self.compared > 0).then(|| self.concordant as f64 / n);
+
+        let cov = self.sum_xy - self.sum_x * self.sum_y / n;
+        let var_x = self.sum_xx - self.sum_x * self.sum_x / n;
+        let var_y = self.sum_yy - self.sum_y * self.sum_y / n;
+        let dosage_r2 = (self.compared > 1 && var_x > 0.0 && var_y > 0.0)
+            .then(|| (cov * cov) / (var_x * var_y));
+
+        ConcordanceSummary {
+            genotyped: self.genotyped,
+            compared: self.compared,
+            concordant: self.concordant,
+            concordance,
+            dosage_r2,
+        }
+    }
+}
+
+/// Concordance of genotyped calls with imputed dosages for a group of sites
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConcordanceSummary {
+    /// Genotyped calls on chromosomes with imputed data
+    pub genotyped: usize,
+    /// Genotyped calls with an imputed dosage for the same variant
+    pub compared: usize,
+    /// Compared calls whose imputed hard call agrees
+    pub concordant: usize,
+    /// Hard-call concordance (concordant / compared)
+    pub concordance: Option<f64>,
+    /// Squared correlation of genotyped and imputed dosages
+    pub dosage_r2: Option<f64>,
+}
+
+impl ConcordanceSummary {
+    /// Fraction of genotyped calls with an imputed dosage
+    pub fn overlap(&self) -> Option<f64> {
+        (self.genotyped > 0).then(|| self.compared as f64 / self.genotyped as f64)
+    }
+
+    fn has_low_concordance(&self) -> bool {
+        self.compared >= MIN_FLAG_SITES
+            && self.concordance.is_some_and(|c| c < SAMPLE_SWAP_CONCORDANCE)
+    }
+}
+
+/// Likely cause of poor genotyped/imputed agreement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConcordanceFlag {
+    /// Few genotyped calls line up with imputed variants
+    PossibleBuildMismatch,
+    /// Genotyped and imputed calls line up but disagree
+    PossibleSampleSwap,
+}
+
+impl ConcordanceFlag {
+    pub fn description(self) -> &'static str {
+        match self {
+            ConcordanceFlag::PossibleBuildMismatch => {
+                "few genotyped variants match imputed positions; the files may use different genome builds"
+            }
+            ConcordanceFlag::PossibleSampleSwap => {
+                "genotyped and imputed calls disagree; the files may come from different people"
+            }
+        }
+    }
+}
+
+/// Genotyped vs imputed concordance for one job
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConcordanceReport {
+    pub overall: ConcordanceSummary,
+    pub by_chromosome: BTreeMap<String, ConcordanceSummary>,
+    pub by_maf_bin: BTreeMap<String, ConcordanceSummary>,
+    /// Chromosomes with enough sites and concordance below `SAMPLE_SWAP_CONCORDANCE`
+    pub low_concordance_chromosomes: Vec<String>,
+    pub flags: Vec<ConcordanceFlag>,
+}
+
+impl ConcordanceReport {
+    /// One-line summary for logging
+    pub fn summary(&self) -> String {
+        let percent = |value: Option<f64>| {
+            value.map_or_else(|| "n/a".to_string(), |v| format!("{:.1}%", v * 100.0))
+        };
+        let mut summary = format!(
+            "{} of {} genotyped calls compared, {} concordant, dosage r² {}",
+            self.overall.compared,
+            self.overall.genotyped,
+            percent(self.overall.concordance),
+            self.overall.dosage_r2.map_or_else(|| "n/a".to_string(), |r2| format!("{:.3}", r2))
+        );
+        for flag in &self.flags {
+            summary.push_str(&format!("; {}", flag.description()));
+        }
+        summary
+    }
+}
+
+/// Accumulates genotyped vs imputed comparisons during the merge
+#[derive(Debug, Clone, Default)]
+pub struct ConcordanceAnalysis {
+    overall: Accumulator,
+    by_chromosome: BTreeMap<u8, Accumulator>,
+    by_maf_bin: BTreeMap<&'static str, Accumulator>,
+}
+
+impl ConcordanceAnalysis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one genotyped call
+    ///
+    /// Only record calls on chromosomes with imputed data, so missing
+    /// chromosomes do not read as a build mismatch.
+    ///
+    /// # Arguments
+    /// * `chromosome` - Chromosome code (1-22, 23=X, 24=Y, 26=MT)
+    /// * `maf` - Reference panel minor allele frequency
+    /// * `genotyped_dosage` - Dosage of the genotyped call
+    /// * `imputed_dosage` - Imputed dosage for the same variant, if any
+    pub fn record(&mut self, chromosome: u8, maf: Option<f64>, genotyped_dosage: f64, imputed_dosage: Option<f64>) {
+        self.overall.record(genotyped_dosage, imputed_dosage);
+        self.by_chromosome
+            .entry(chromosome)
+            .or_default()
+            .record(genotyped_dosage, imputed_dosage);
+        self.by_maf_bin
+            .entry(maf_bin(maf))
+            .or_default()
+            .record(genotyped_dosage, imputed_dosage);
+    }
+
+    /// Summaries and flags for everything recorded so far
+    pub fn report(&self) -> ConcordanceReport {
+        let overall = self.overall.summary();
+        let by_chromosome: BTreeMap<String, ConcordanceSummary> = self
+            .by_chromosome
+            .iter()
+            .map(|(chr, acc)| (chromosome_name(*chr), acc.summary()))
+            .collect();
+        let low_concordance_chromosomes = self
+            .by_chromosome
+            .iter()
+            .filter(|(_, acc)| acc.summary().has_low_concordance())
+            .map(|(chr, _)| chromosome_name(*chr))
+            .collect();
+
+        let mut flags = Vec::new();
+        if overall.genotyped >= MIN_FLAG_SITES
+            && overall.overlap().is_some_and(|o| o < BUILD_MISMATCH_OVERLAP)
+        {
+            flags.push(ConcordanceFlag::PossibleBuildMismatch);
+        } else if overall.has_low_concordance() {
+            flags.push(ConcordanceFlag::PossibleSampleSwap);
+        }
+
+        ConcordanceReport {
+            overall,
+            by_chromosome,
+            by_maf_bin: self
+                .by_maf_bin
+                .iter()
+                .map(|(bin, acc)| (bin.to_string(), acc.summary()))
+                .collect(),
+            low_concordance_chromosomes,
+            flags,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Record `concordant` agreeing and `discordant` disagreeing calls, plus
+    /// `genotyped_only` calls without an imputed dosage
+    fn record_calls(
+        analysis: &mut ConcordanceAnalysis,
+        chromosome: u8,
+        concordant: usize,
+        discordant: usize,
+        genotyped_only: usize,
+    ) {
+        for i in 0..concordant {
+            let genotyped = (i % 3) as f64;
+            analysis.record(chromosome, Some(0.3), genotyped, Some(genotyped));
+        }
+        for i in 0..discordant {
+            analysis.record(chromosome, Some(0.3), (i % 3) as f64, Some(((i + 1) % 3) as f64));
+        }
+        for _ in 0..genotyped_only {
+            analysis.record(chromosome, Some(0.3), 0.0, None);
+        }
+    }
+
+    fn flags(concordant: usize, discordant: usize, genotyped_only: usize) -> Vec<ConcordanceFlag> {
+        let mut analysis = ConcordanceAnalysis::new();
+        record_calls(&mut analysis, 1, concordant, discordant, genotyped_only);
+        analysis.report().flags
+    }
+
+    #[test]
+    fn test_maf_bins() {
+        assert_eq!(maf_bin(Some(0.003)), "0-0.01");
+        assert_eq!(maf_bin(Some(0.01)), "0.01-0.05");
+        assert_eq!(maf_bin(Some(0.19)), "0.1-0.2");
+        assert_eq!(maf_bin(Some(0.2)), "0.2-0.5");
+        assert_eq!(maf_bin(Some(0.5)), "0.2-0.5");
+        assert_eq!(maf_bin(None), "unknown");
+    }
+
+    #[test]
+    fn test_same_person_concordance() {
+        // Imputed dosages track the genotyped calls
+        let mut analysis = ConcordanceAnalysis::new();
+        for i in 0..100 {
+            let genotyped = (i % 3) as f64;
+            analysis.record(1, Some(0.3), genotyped, Some(genotyped * 0.95 + 0.02));
+        }
+        analysis.record(1, None, 1.0, Some(1.0));
+
+        let report = analysis.report();
+        assert_eq!(report.overall.compared, 101);
+        assert_eq!(report.overall.concordance, Some(1.0));
+        assert!(report.overall.dosage_r2.unwrap() > 0.99);
+        assert_eq!(report.by_chromosome["1"], report.overall);
+        assert_eq!(report.by_maf_bin["0.2-0.5"].compared, 100);
+        assert_eq!(report.by_maf_bin["unknown"].compared, 1);
+        assert!(report.low_concordance_chromosomes.is_empty());
+        assert!(report.flags.is_empty());
+    }
+
+    #[test]
+    fn test_sample_swap_threshold() {
+        // Concordance 0.88 and 0.9 at exactly MIN_FLAG_SITES
+        assert_eq!(flags(44, 6, 0), vec![ConcordanceFlag::PossibleSampleSwap]);
+        assert!(flags(45, 5, 0).is_empty());
+
+        // No concordance at all, one site short of and at MIN_FLAG_SITES
+        assert!(flags(0, 49, 0).is_empty());
+        assert_eq!(flags(0, 50, 0), vec![ConcordanceFlag::PossibleSampleSwap]);
+    }
+
+    #[test]
+    fn test_build_mismatch_threshold() {
+        // Overlap 0.49 and 0.5 of 100 genotyped calls
+        assert_eq!(flags(49, 0, 51), vec![ConcordanceFlag::PossibleBuildMismatch]);
+        assert!(flags(50, 0, 50).is_empty());
+
+        // No overlap, one call short of and at MIN_FLAG_SITES
+        assert!(flags(0, 0, 49).is_empty());
+        assert_eq!(flags(0, 0, 50), vec![ConcordanceFlag::PossibleBuildMismatch]);
+
+        // Low overlap explains discordance, so only the build is flagged
+        assert_eq!(flags(0, 60, 140), vec![ConcordanceFlag::PossibleBuildMismatch]);
+    }
+
+    #[test]
+    fn test_low_concordance_chromosomes() {
+        let mut analysis = ConcordanceAnalysis::new();
+        record_calls(&mut analysis, 1, 100, 0, 0);
+        record_calls(&mut analysis, 23, 10, 50, 0);
+        // Too few sites on MT to judge
+        record_calls(&mut analysis, 26, 0, 10, 0);
+
+        let report = analysis.report();
+        assert_eq!(report.low_concordance_chromosomes, vec!["X".to_string()]);
+        assert_eq!(report.by_chromosome["X"].concordance, Some(10.0 / 60.0));
+        // 110 of 170 overall is below the swap threshold as well
+        assert_eq!(report.flags, vec![ConcordanceFlag::PossibleSampleSwap]);
+    }
+}
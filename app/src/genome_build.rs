@@ -0,0 +1,140 @@
+// ==============================================================================
+// genome_build.rs - Reference Genome Builds
+// ==============================================================================
+// Description: Reference genome assembly identifiers shared by parsers and
+//              outputs (GRCh37/hg19, GRCh38/hg38, NCBI36/hg18)
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+
+/// Build of the reference panel (and of merged output coordinates)
+pub const REFERENCE_PANEL_BUILD: GenomeBuild = GenomeBuild::GRCh37;
+
+/// Chromosome lengths (1-22, X, Y) in GRCh37
+const GRCH37_LENGTHS: [u64; 24] = [
+    249_250_621, 243_199_373, 198_022_430, 191_154_276, 180_915_260, 171_115_067,
+    159_138_663, 146_364_022, 141_213_431, 135_534_747, 135_006_516, 133_851_895,
+    115_169_878, 107_349_540, 102_531_392, 90_354_753, 81_195_210, 78_077_248,
+    59_128_983, 63_025_520, 48_129_895, 51_304_566, 155_270_560, 59_373_566,
+];
+
+/// Chromosome lengths (1-22, X, Y) in GRCh38
+const GRCH38_LENGTHS: [u64; 24] = [
+    248_956_422, 242_193_529, 198_295_559, 190_214_555, 181_538_259, 170_805_979,
+    159_345_973, 145_138_636, 138_394_717, 133_797_422, 135_086_622, 133_275_309,
+    114_364_328, 107_043_718, 101_991_189, 90_338_345, 83_257_441, 80_373_285,
+    58_617_616, 64_444_167, 46_709_983, 50_818_468, 156_040_895, 57_227_415,
+];
+
+/// Reference genome assembly
+///
+/// The reference panel and `MultiSampleVariant.position` use GRCh37.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GenomeBuild {
+    /// NCBI Build 36 (hg18)
+    NCBI36,
+    /// GRCh37 (hg19)
+    GRCh37,
+    /// GRCh38 (hg38)
+    GRCh38,
+    /// Build not stated or not recognized
+    #[default]
+    Unknown,
+}
+
+impl GenomeBuild {
+    /// Parse a build name
+    ///
+    /// Accepts assembly names (GRCh37), UCSC names (hg19), short forms
+    /// (b37, 37) and is case-insensitive. "NR" (not reported) and anything
+    /// else map to `Unknown`.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "grch37" | "hg19" | "b37" | "37" | "grch37.p13" => GenomeBuild::GRCh37,
+            "grch38" | "hg38" | "b38" | "38" => GenomeBuild::GRCh38,
+            "ncbi36" | "hg18" | "b36" | "36" => GenomeBuild::NCBI36,
+            _ => GenomeBuild::Unknown,
+        }
+    }
+
+    /// Identify the build from a chromosome's length (e.g. a VCF `##contig` line)
+    ///
+    /// Only GRCh37 and GRCh38 are recognized; MT has the same length in both.
+    ///
+    /// # Arguments
+    /// * `chromosome` - Chromosome code (see `chromosomes`)
+    /// * `length` - Contig length in base pairs
+    pub fn from_contig_length(chromosome: u8, length: u64) -> Self {
+        let index = match chromosome {
+            1..=24 => usize::from(chromosome - 1),
+            _ => return GenomeBuild::Unknown,
+        };
+
+        if GRCH37_LENGTHS[index] == length {
+            GenomeBuild::GRCh37
+        } else if GRCH38_LENGTHS[index] == length {
+            GenomeBuild::GRCh38
+        } else {
+            GenomeBuild::Unknown
+        }
+    }
+
+    /// Identify the build from a reference FASTA name or URL
+    /// (e.g. "file:///ref/hs37d5.fa.gz" or "GRCh38")
+    pub fn from_reference(reference: &str) -> Self {
+        let reference = reference.to_ascii_lowercase();
+        if ["grch38", "hg38", "b38"].iter().any(|name| reference.contains(name)) {
+            GenomeBuild::GRCh38
+        } else if ["grch37", "hg19", "b37", "hs37d5", "g1k_v37"].iter().any(|name| reference.contains(name)) {
+            GenomeBuild::GRCh37
+        } else if ["ncbi36", "hg18", "b36"].iter().any(|name| reference.contains(name)) {
+            GenomeBuild::NCBI36
+        } else {
+            GenomeBuild::Unknown
+        }
+    }
+
+    /// Whether the build was identified
+    pub fn is_known(&self) -> bool {
+        *self != GenomeBuild::Unknown
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GenomeBuild::NCBI36 => "NCBI36",
+            GenomeBuild::GRCh37 => "GRCh37",
+            GenomeBuild::GRCh38 => "GRCh38",
+            GenomeBuild::Unknown => "Unknown",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(GenomeBuild::from_name("GRCh37"), GenomeBuild::GRCh37);
+        assert_eq!(GenomeBuild::from_name("hg19"), GenomeBuild::GRCh37);
+        assert_eq!(GenomeBuild::from_name(" hg38 "), GenomeBuild::GRCh38);
+        assert_eq!(GenomeBuild::from_name("NCBI36"), GenomeBuild::NCBI36);
+        assert_eq!(GenomeBuild::from_name("NR"), GenomeBuild::Unknown);
+    }
+
+    #[test]
+    fn test_from_contig_and_reference() {
+        assert_eq!(GenomeBuild::from_contig_length(1, 249_250_621), GenomeBuild::GRCh37);
+        assert_eq!(GenomeBuild::from_contig_length(22, 50_818_468), GenomeBuild::GRCh38);
+        assert_eq!(GenomeBuild::from_contig_length(22, 1_000), GenomeBuild::Unknown);
+        assert_eq!(GenomeBuild::from_contig_length(26, 16_569), GenomeBuild::Unknown);
+
+        assert_eq!(GenomeBuild::from_reference("file:///data/hs37d5.fa.gz"), GenomeBuild::GRCh37);
+        assert_eq!(GenomeBuild::from_reference("GRCh38_full_analysis_set.fa"), GenomeBuild::GRCh38);
+        assert_eq!(GenomeBuild::from_reference("file:///ref/genome.fa"), GenomeBuild::Unknown);
+    }
+}
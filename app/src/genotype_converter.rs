@@ -13,10 +13,34 @@
 //   - REF/ALT or ALT/REF (e.g., AG where REF=A, ALT=G) → 1.0 (one ALT allele)
 //   - ALT/ALT (e.g., GG where ALT=G) → 2.0 (two ALT alleles)
 //   - --/-- (no call) → None (use imputed dosage from VCF)
+//
+//   Indels are reported as D (deletion) / I (insertion) calls:
+//   - D is the shorter of REF/ALT, I is the longer
+//   - DD/DI/II are counted against whichever of REF/ALT each maps to
+//
+//   Strand:
+//   - Mismatched alleles are retried on the opposite strand (A↔T, C↔G)
+//   - A/T and C/G SNPs read the same on both strands; homozygous calls are
+//     dropped when the file shows other-strand calls elsewhere
+//
+//   Haploid sites (male non-PAR X, Y, MT):
+//   - Single-letter calls ("A") and homozygous calls ("AA") are one allele
+//   - Dosage is the ALT allele count (0/1), or 0/2 when coded as a homozygote
 // ==============================================================================
 
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use thiserror::Error;
 
+use crate::models::MultiSampleVariant;
+
+/// Share of unambiguous SNPs matching REF/ALT only after complementing above
+/// which a genotype file is treated as not reported on the forward strand
+pub const STRAND_MISMATCH_FLIP_RATE: f64 = 0.05;
+
+/// Unambiguous SNPs needed before their flip rate counts as strand evidence
+pub const MIN_STRAND_EVIDENCE_SITES: usize = 50;
+
 /// Errors that can occur during genotype conversion
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum GenotypeConversionError {
@@ -36,6 +60,50 @@ pub enum GenotypeConversionError {
         ref_allele: String,
         alt_allele: String,
     },
+
+    #[error("Indel genotype '{genotype}' is ambiguous at REF '{ref_allele}', ALT '{alt_allele}'")]
+    AmbiguousIndel {
+        genotype: String,
+        ref_allele: String,
+        alt_allele: String,
+    },
+}
+
+/// Dosage coding for haploid (hemizygous) genotypes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaploidConvention {
+    /// ALT allele count: 0 or 1
+    #[default]
+    AlleleCount,
+    /// Coded as a homozygote: 0 or 2 (PLINK-style)
+    Homozygous,
+}
+
+impl HaploidConvention {
+    /// Scale a dosage at a site of the given ploidy to this convention
+    ///
+    /// Only haploid (0-1) dosages change; diploid dosages are returned as is.
+    pub fn scale(self, dosage: f64, ploidy: u8) -> f64 {
+        match (self, ploidy) {
+            (HaploidConvention::Homozygous, 1) => dosage * 2.0,
+            _ => dosage,
+        }
+    }
+
+    /// Recode the haploid dosages of merged variants to this convention
+    ///
+    /// Merging and scoring work on ALT allele counts (0/1 at haploid sites);
+    /// call this once the dosages are final, just before they are written.
+    /// Every sample is recoded alike, by the ploidy of its own genotype.
+    pub fn apply(self, variants: &mut [MultiSampleVariant]) {
+        if self == HaploidConvention::AlleleCount {
+            return;
+        }
+        for sample in variants.iter_mut().flat_map(|variant| variant.samples.iter_mut()) {
+            sample.dosage = self.scale(sample.dosage, sample.genotype.ploidy());
+        }
+    }
 }
 
 /// Convert 23andMe genotype to dosage given REF and ALT alleles
@@ -87,9 +155,13 @@ pub fn genotype_to_dosage(
         return Err(GenotypeConversionError::InvalidFormat(genotype.to_string()));
     }
 
-    // Validate that REF and ALT are single characters (SNPs only)
-    // 23andMe genotypes are 2 characters and cannot represent indels properly
-    // For indels, we should return an error to fall back to imputed dosage
+    // Indel calls (DD, DI, II) are resolved by allele length
+    if genotype.chars().all(|c| c == 'D' || c == 'I') {
+        return indel_genotype_to_dosage(genotype, ref_allele, alt_allele);
+    }
+
+    // Nucleotide genotypes can only be matched against SNPs; indels
+    // called with bases fall back to imputed dosage
     if ref_allele.len() != 1 || alt_allele.len() != 1 {
         return Err(GenotypeConversionError::AllelesMismatch {
             genotype: genotype.to_string(),
@@ -148,6 +220,70 @@ pub fn genotype_to_dosage(
     Ok(Some(alt_count as f64))
 }
 
+/// Expand a single-letter haploid call ("A", "D", "-") to the two-letter
+/// form `genotype_to_dosage` expects ("AA", "DD", "--")
+///
+/// Providers report hemizygous calls either as one letter ("A") or as a
+/// homozygote ("AA"); both are one allele. Convert the expanded call, then
+/// reduce it to an allele count with `chromosomes::haploid_dosage`.
+pub fn expand_haploid_call(genotype: &str) -> Cow<'_, str> {
+    let mut chars = genotype.chars();
+    match (chars.next(), chars.next()) {
+        (Some(allele), None) => Cow::Owned([allele, allele].iter().collect()),
+        _ => Cow::Borrowed(genotype),
+    }
+}
+
+/// Convert a 23andMe D/I indel genotype to dosage given REF and ALT alleles
+///
+/// D (deletion) is the shorter of REF and ALT, I (insertion) the longer, so
+/// the same call gives opposite dosages for insertions (REF shorter) and
+/// deletions (REF longer).
+///
+/// # Arguments
+/// * `genotype` - Two-character D/I genotype ("DD", "DI", "ID", "II")
+/// * `ref_allele` - Reference allele from VCF (e.g., "A")
+/// * `alt_allele` - Alternate allele from VCF (e.g., "AT")
+///
+/// # Returns
+/// * `Ok(Some(dosage))` - Successfully converted to dosage (0.0, 1.0, or 2.0)
+/// * `Err(GenotypeConversionError::AmbiguousIndel)` - REF and ALT have the same
+///   length, or ALT lists several alleles, so D/I cannot be assigned
+///
+/// # Examples
+/// ```
+/// use genetics_processor::genotype_converter::indel_genotype_to_dosage;
+///
+/// // Insertion: REF=A, ALT=AT (I is ALT)
+/// assert_eq!(indel_genotype_to_dosage("II", "A", "AT").unwrap(), Some(2.0));
+///
+/// // Deletion: REF=AT, ALT=A (D is ALT)
+/// assert_eq!(indel_genotype_to_dosage("II", "AT", "A").unwrap(), Some(0.0));
+/// ```
+pub fn indel_genotype_to_dosage(
+    genotype: &str,
+    ref_allele: &str,
+    alt_allele: &str,
+) -> Result<Option<f64>, GenotypeConversionError> {
+    if genotype.len() != 2 || !genotype.chars().all(|c| c == 'D' || c == 'I') {
+        return Err(GenotypeConversionError::InvalidFormat(genotype.to_string()));
+    }
+
+    if alt_allele.contains(',') || ref_allele.len() == alt_allele.len() {
+        return Err(GenotypeConversionError::AmbiguousIndel {
+            genotype: genotype.to_string(),
+            ref_allele: ref_allele.to_string(),
+            alt_allele: alt_allele.to_string(),
+        });
+    }
+
+    // ALT is the insertion when it is longer than REF, otherwise the deletion
+    let alt_code = if alt_allele.len() > ref_allele.len() { 'I' } else { 'D' };
+    let alt_count = genotype.chars().filter(|&c| c == alt_code).count();
+
+    Ok(Some(alt_count as f64))
+}
+
 /// Convert genotype to dosage with strand flipping support
 ///
 /// This function attempts to convert the genotype, and if it fails due to
@@ -179,6 +315,110 @@ pub fn genotype_to_dosage_with_flip(
     }
 }
 
+/// How a genotype's strand was resolved against REF/ALT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrandResolution {
+    /// Genotype matched REF/ALT as reported
+    Forward,
+    /// Genotype matched REF/ALT after complementing
+    Flipped,
+    /// Homozygous A/T or C/G call that could not be oriented
+    Dropped,
+}
+
+/// Per-chromosome counts of strand-resolved genotypes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrandStats {
+    /// Genotypes complemented to match REF/ALT
+    pub flipped: usize,
+    /// Homozygous A/T or C/G genotypes dropped on other-strand chromosomes
+    pub dropped: usize,
+}
+
+impl StrandStats {
+    /// Count one strand resolution
+    pub fn record(&mut self, resolution: StrandResolution) {
+        match resolution {
+            StrandResolution::Forward => {}
+            StrandResolution::Flipped => self.flipped += 1,
+            StrandResolution::Dropped => self.dropped += 1,
+        }
+    }
+}
+
+/// Whether a SNP reads the same on both strands (A/T or C/G)
+pub fn is_strand_ambiguous(ref_allele: &str, alt_allele: &str) -> bool {
+    ref_allele.len() == 1 && alt_allele.len() == 1 && flip_strand(ref_allele) == alt_allele
+}
+
+/// Whether genotype calls show evidence of being reported on the other strand
+///
+/// A/T and C/G SNPs cannot reveal a strand mismatch, so the evidence comes
+/// from unambiguous SNPs: a forward-strand file matches REF/ALT as reported
+/// almost everywhere, while TOP/BOT or minus-strand files need complementing
+/// at a large share of sites. Fewer than `MIN_STRAND_EVIDENCE_SITES`
+/// convertible calls are no evidence.
+///
+/// # Arguments
+/// * `calls` - (genotype, REF, ALT) for the file's genotyped panel sites
+pub fn has_strand_mismatch<'a>(calls: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>) -> bool {
+    let mut stats = StrandStats::default();
+    let mut resolved = 0;
+    for (genotype, ref_allele, alt_allele) in calls {
+        if is_strand_ambiguous(ref_allele, alt_allele) {
+            continue;
+        }
+        if let Ok((Some(_), resolution)) = genotype_to_dosage_with_strand(genotype, ref_allele, alt_allele, false) {
+            stats.record(resolution);
+            resolved += 1;
+        }
+    }
+
+    resolved >= MIN_STRAND_EVIDENCE_SITES && stats.flipped as f64 / resolved as f64 > STRAND_MISMATCH_FLIP_RATE
+}
+
+/// Convert genotype to dosage, resolving strand against REF/ALT
+///
+/// Unambiguous SNPs are complemented when the reported alleles do not match.
+/// At A/T and C/G SNPs complementing cannot detect a strand mismatch, so
+/// calls are used as reported unless `strand_mismatch` says the file is not
+/// on the forward strand (see `has_strand_mismatch`). Then homozygous calls
+/// are dropped: such a file can mix strands, so neither the reported allele
+/// nor its complement is known to be right. Heterozygous calls read the same
+/// on both strands and are kept.
+///
+/// # Arguments
+/// * `genotype` - Two-character genotype string
+/// * `ref_allele` - Reference allele from the reference panel
+/// * `alt_allele` - Alternate allele from the reference panel
+/// * `strand_mismatch` - Whether the file shows evidence of other-strand calls
+///
+/// # Returns
+/// * `Ok((dosage, resolution))` - Dosage is `None` for no-calls and dropped sites
+/// * `Err(GenotypeConversionError)` - Cannot convert on either strand
+pub fn genotype_to_dosage_with_strand(
+    genotype: &str,
+    ref_allele: &str,
+    alt_allele: &str,
+    strand_mismatch: bool,
+) -> Result<(Option<f64>, StrandResolution), GenotypeConversionError> {
+    if is_strand_ambiguous(ref_allele, alt_allele) {
+        return Ok(match genotype_to_dosage(genotype, ref_allele, alt_allele)? {
+            Some(dosage) if dosage != 1.0 && strand_mismatch => (None, StrandResolution::Dropped),
+            as_reported => (as_reported, StrandResolution::Forward),
+        });
+    }
+
+    match genotype_to_dosage(genotype, ref_allele, alt_allele) {
+        Ok(dosage) => Ok((dosage, StrandResolution::Forward)),
+        Err(GenotypeConversionError::AllelesMismatch { .. }) => {
+            genotype_to_dosage(&flip_strand(genotype), ref_allele, alt_allele)
+                .map(|dosage| (dosage, StrandResolution::Flipped))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 /// Flip genotype to reverse complement (strand flip)
 ///
 /// # Mapping
@@ -192,7 +432,7 @@ pub fn genotype_to_dosage_with_flip(
 ///
 /// # Returns
 /// * Reverse complement genotype string
-fn flip_strand(genotype: &str) -> String {
+pub fn flip_strand(genotype: &str) -> String {
     genotype
         .chars()
         .map(|c| match c {
@@ -227,6 +467,7 @@ pub fn batch_convert_genotypes(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chromosomes;
 
     #[test]
     fn test_homozygous_reference() {
@@ -323,6 +564,158 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_genotype_with_strand() {
+        // Unambiguous SNP reported on the opposite strand
+        assert_eq!(
+            genotype_to_dosage_with_strand("TC", "A", "G", false).unwrap(),
+            (Some(1.0), StrandResolution::Flipped)
+        );
+        assert_eq!(
+            genotype_to_dosage_with_strand("AG", "A", "G", false).unwrap(),
+            (Some(1.0), StrandResolution::Forward)
+        );
+
+        // A/T SNP: heterozygous calls need no orientation
+        assert!(is_strand_ambiguous("A", "T"));
+        assert_eq!(
+            genotype_to_dosage_with_strand("AT", "A", "T", true).unwrap(),
+            (Some(1.0), StrandResolution::Forward)
+        );
+
+        // With strand evidence, homozygous calls cannot be oriented: neither
+        // the reported genotype nor its complement is used
+        assert_eq!(
+            genotype_to_dosage_with_strand("TT", "A", "T", true).unwrap(),
+            (None, StrandResolution::Dropped)
+        );
+        assert_eq!(
+            genotype_to_dosage_with_strand("AA", "A", "T", true).unwrap(),
+            (None, StrandResolution::Dropped)
+        );
+        assert_eq!(
+            genotype_to_dosage_with_strand("CC", "C", "G", true).unwrap(),
+            (None, StrandResolution::Dropped)
+        );
+
+        let mut stats = StrandStats::default();
+        for resolution in [StrandResolution::Forward, StrandResolution::Flipped, StrandResolution::Dropped] {
+            stats.record(resolution);
+        }
+        assert_eq!(stats, StrandStats { flipped: 1, dropped: 1 });
+    }
+
+    #[test]
+    fn test_ambiguous_calls_kept_without_strand_evidence() {
+        // Homozygous-minor calls on a forward-strand file are real genotypes
+        assert_eq!(
+            genotype_to_dosage_with_strand("AA", "A", "T", false).unwrap(),
+            (Some(0.0), StrandResolution::Forward)
+        );
+        assert_eq!(
+            genotype_to_dosage_with_strand("TT", "A", "T", false).unwrap(),
+            (Some(2.0), StrandResolution::Forward)
+        );
+        // ...at C/G SNPs too
+        assert_eq!(
+            genotype_to_dosage_with_strand("CC", "C", "G", false).unwrap(),
+            (Some(0.0), StrandResolution::Forward)
+        );
+        assert_eq!(
+            genotype_to_dosage_with_strand("GG", "C", "G", false).unwrap(),
+            (Some(2.0), StrandResolution::Forward)
+        );
+    }
+
+    #[test]
+    fn test_strand_mismatch_evidence() {
+        let forward = vec![("AG", "A", "G"); 60];
+        let flipped = vec![("TC", "A", "G"); 10];
+        let ambiguous = vec![("TT", "A", "T"); 100];
+
+        assert!(!has_strand_mismatch(forward.iter().copied()));
+        // A/T and C/G calls are no evidence either way
+        assert!(!has_strand_mismatch(forward.iter().chain(&ambiguous).copied()));
+        assert!(has_strand_mismatch(forward.iter().chain(&flipped).copied()));
+        // Too few unambiguous calls to tell
+        assert!(!has_strand_mismatch(flipped.iter().chain(&ambiguous).copied()));
+
+        // Unconvertible calls do not count towards the minimum
+        let mut calls = vec![("AG", "A", "G"); 45];
+        calls.extend(vec![("TC", "A", "G"); 3]);
+        calls.extend(vec![("--", "A", "G"), ("CC", "A", "G")]);
+        assert!(!has_strand_mismatch(calls.iter().copied()));
+        calls.extend(vec![("AA", "A", "G"); 2]);
+        assert!(has_strand_mismatch(calls.iter().copied()));
+    }
+
+    #[test]
+    fn test_haploid_genotypes() {
+        let haploid = |genotype: &str, ref_allele: &str, alt_allele: &str| {
+            genotype_to_dosage(&expand_haploid_call(genotype), ref_allele, alt_allele)
+                .map(|dosage| dosage.map(chromosomes::haploid_dosage))
+        };
+
+        // Single-letter and homozygous calls are both one allele
+        assert_eq!(expand_haploid_call("C"), "CC");
+        assert_eq!(expand_haploid_call("CC"), "CC");
+        assert_eq!(haploid("T", "T", "C").unwrap(), Some(Some(0.0)));
+        assert_eq!(haploid("C", "T", "C").unwrap(), Some(Some(1.0)));
+        assert_eq!(haploid("CC", "T", "C").unwrap(), Some(Some(1.0)));
+        assert_eq!(haploid("I", "A", "AG").unwrap(), Some(Some(1.0)));
+
+        // No-calls, heterozygous calls and allele mismatches
+        assert_eq!(haploid("-", "T", "C").unwrap(), None);
+        assert_eq!(haploid("TC", "T", "C").unwrap(), Some(None));
+        assert!(matches!(
+            haploid("G", "T", "C"),
+            Err(GenotypeConversionError::AllelesMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_haploid_convention_applies_to_every_sample() {
+        use crate::models::{DataSource, Genotype, SampleData};
+
+        let sample = |sample_id: &str, genotype: Genotype| SampleData {
+            sample_id: sample_id.to_string(),
+            dosage: genotype.alt_dosage().unwrap_or(0.0),
+            genotype,
+            source: DataSource::Imputed,
+            imputation_quality: None,
+        };
+        let variant = |samples: Vec<SampleData>| MultiSampleVariant {
+            rsid: "rs1".to_string(),
+            chromosome: chromosomes::CHR_X,
+            position: 5_000_000,
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            allele_freq: None,
+            minor_allele_freq: None,
+            is_typed: false,
+            samples,
+        };
+        let samples = || {
+            vec![
+                sample("samp1", Genotype::haploid(Some(1))),
+                sample("samp2", Genotype::from_alt_count(1)),
+                sample("samp51", Genotype::haploid(Some(1))),
+                sample("mother", Genotype::haploid(Some(0))),
+            ]
+        };
+        let dosages = |variants: &[MultiSampleVariant]| -> Vec<f64> {
+            variants[0].samples.iter().map(|s| s.dosage).collect()
+        };
+
+        let mut variants = vec![variant(samples())];
+        HaploidConvention::AlleleCount.apply(&mut variants);
+        assert_eq!(dosages(&variants), vec![1.0, 1.0, 1.0, 0.0]);
+
+        // Reference, user and extra haploid samples alike; diploid ones unchanged
+        HaploidConvention::Homozygous.apply(&mut variants);
+        assert_eq!(dosages(&variants), vec![2.0, 1.0, 2.0, 0.0]);
+    }
+
     #[test]
     fn test_batch_conversion() {
         let genotypes = vec![
@@ -379,6 +772,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_indel_calls() {
+        // Insertion: REF=A, ALT=AG → I is ALT
+        assert_eq!(genotype_to_dosage("DD", "A", "AG").unwrap(), Some(0.0));
+        assert_eq!(genotype_to_dosage("DI", "A", "AG").unwrap(), Some(1.0));
+        assert_eq!(genotype_to_dosage("ID", "A", "AG").unwrap(), Some(1.0));
+        assert_eq!(genotype_to_dosage("II", "A", "AG").unwrap(), Some(2.0));
+
+        // Deletion: REF=AG, ALT=A → D is ALT
+        assert_eq!(genotype_to_dosage("DD", "AG", "A").unwrap(), Some(2.0));
+        assert_eq!(genotype_to_dosage("DI", "AG", "A").unwrap(), Some(1.0));
+        assert_eq!(genotype_to_dosage("II", "AG", "A").unwrap(), Some(0.0));
+
+        // Equal-length alleles or several ALT alleles cannot be assigned D/I
+        for (ref_allele, alt_allele) in [("A", "G"), ("AT", "GC"), ("A", "AT,ATT")] {
+            assert!(matches!(
+                genotype_to_dosage("DI", ref_allele, alt_allele),
+                Err(GenotypeConversionError::AmbiguousIndel { .. })
+            ));
+        }
+
+        // D/I calls pass through the strand flip unchanged
+        assert_eq!(genotype_to_dosage_with_flip("DI", "A", "AG").unwrap(), Some(1.0));
+    }
+
     #[test]
     fn test_indels() {
         // Indels should be rejected since 23andMe genotypes cannot represent them
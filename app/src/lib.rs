@@ -14,6 +14,13 @@ pub mod validator;
 pub mod secure_delete;
 pub mod genotype_converter;
 pub mod models;
+pub mod chromosomes;
+pub mod concordance;
+pub mod genome_build;
+pub mod liftover;
+pub mod pgs_engine;
+pub mod pgs_interpretation;
 pub mod reference_panel;
+pub mod rsid_merge;
 pub mod processor;
 pub mod output;
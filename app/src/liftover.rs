@@ -0,0 +1,838 @@
+// ==============================================================================
+// liftover.rs - Coordinate Liftover Between Genome Builds
+// ==============================================================================
+// Description: Converts positions between genome builds (GRCh37 <-> GRCh38)
+//              using UCSC chain files read from local disk
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Chain format (https://genome.ucsc.edu/goldenPath/help/chain.html):
+//   chain score tName tSize tStrand tStart tEnd qName qSize qStrand qStart qEnd id
+//   size dt dq
+//   ...
+//   size
+// Coordinates are 0-based, half-open. The target (t) is the source build and
+// the query (q) the destination build, so hg38ToHg19.over.chain.gz converts
+// GRCh38 positions to GRCh37. Query coordinates on the '-' strand count from
+// the end of the query chromosome.
+// ==============================================================================
+
+use flate2::read::MultiGzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::chromosomes::{chromosome_name, parse_chromosome};
+use crate::genome_build::GenomeBuild;
+use crate::genotype_converter::flip_strand;
+use crate::models::ReferencePanelVariant;
+use crate::parsers::{Genome23Record, ImputationInfoRecord, VCFRecord};
+
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Errors that can occur while reading a chain file
+#[derive(Error, Debug)]
+pub enum LiftOverError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid chain file at line {line}: {details}")]
+    InvalidFormat { line: usize, details: String },
+
+    #[error("Chain file contains no alignment blocks")]
+    EmptyFile,
+}
+
+/// Why a variant could not be lifted over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmappedReason {
+    /// Position is not covered by any alignment block
+    NoAlignment,
+    /// Position is covered by blocks that map to different places
+    MultipleAlignments,
+    /// REF allele spans a gap between alignment blocks
+    SpansGap,
+    /// Destination is an unplaced or alternate contig
+    UnplacedContig,
+    /// Indel on a reverse-strand block (its padding base would change)
+    ReverseStrandIndel,
+}
+
+impl UnmappedReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnmappedReason::NoAlignment => "no_alignment",
+            UnmappedReason::MultipleAlignments => "multiple_alignments",
+            UnmappedReason::SpansGap => "spans_gap",
+            UnmappedReason::UnplacedContig => "unplaced_contig",
+            UnmappedReason::ReverseStrandIndel => "reverse_strand_indel",
+        }
+    }
+}
+
+/// A position in the destination build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappedPosition {
+    /// Chromosome code (see `chromosomes`)
+    pub chromosome: u8,
+    /// 1-based position of the first base of the lifted span
+    pub position: u64,
+    /// Whether the span maps to the opposite strand (alleles are complemented)
+    pub reverse_strand: bool,
+}
+
+/// Counts of lifted and unmappable variants
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiftOverStats {
+    /// Source build
+    pub from: GenomeBuild,
+    /// Destination build
+    pub to: GenomeBuild,
+    /// Variants lifted to the destination build
+    pub lifted: usize,
+    /// Lifted variants whose alleles were reverse-complemented
+    pub strand_flipped: usize,
+    /// Unmappable variants by reason
+    pub unmapped: BTreeMap<UnmappedReason, usize>,
+    /// Lifted variants whose REF disagrees with the reference panel at the lifted position
+    pub ref_mismatch: usize,
+}
+
+impl LiftOverStats {
+    /// Total number of unmappable variants
+    pub fn unmapped_count(&self) -> usize {
+        self.unmapped.values().sum()
+    }
+
+    /// Count one liftover result
+    pub fn record<T>(&mut self, result: &Result<(T, bool), UnmappedReason>) {
+        match result {
+            Ok((_, flipped)) => {
+                self.lifted += 1;
+                if *flipped {
+                    self.strand_flipped += 1;
+                }
+            }
+            Err(reason) => *self.unmapped.entry(*reason).or_insert(0) += 1,
+        }
+    }
+
+    /// Count lifted records whose REF allele disagrees with the reference panel
+    ///
+    /// Both slices must hold one destination chromosome. Only positions the
+    /// panel contains are checked; a different REF there means the record
+    /// was placed on the wrong base and cannot join the panel.
+    pub fn count_ref_mismatches(&mut self, lifted: &[VCFRecord], panel: &[ReferencePanelVariant]) {
+        let mut panel_refs: HashMap<u64, Vec<&str>> = HashMap::new();
+        for variant in panel {
+            panel_refs.entry(variant.position).or_default().push(&variant.ref_allele);
+        }
+
+        self.ref_mismatch += lifted
+            .iter()
+            .filter(|r| {
+                panel_refs
+                    .get(&r.position)
+                    .is_some_and(|refs| !refs.contains(&r.ref_allele.as_str()))
+            })
+            .count();
+    }
+
+    /// One-line summary for logging
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} -> {}: {} lifted ({} strand-flipped, {} REF mismatches), {} unmapped",
+            self.from.as_str(),
+            self.to.as_str(),
+            self.lifted,
+            self.strand_flipped,
+            self.ref_mismatch,
+            self.unmapped_count()
+        );
+        let reasons: Vec<String> = self
+            .unmapped
+            .iter()
+            .map(|(reason, count)| format!("{} {}", count, reason.as_str()))
+            .collect();
+        if !reasons.is_empty() {
+            summary.push_str(&format!(" ({})", reasons.join(", ")));
+        }
+        summary
+    }
+}
+
+/// One ungapped alignment block
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    /// Source start (0-based)
+    t_start: u64,
+    /// Source end (exclusive)
+    t_end: u64,
+    /// Destination chromosome, `None` for unplaced or alternate contigs
+    q_chromosome: Option<u8>,
+    /// Destination start on the chain's query strand (0-based)
+    q_start: u64,
+    /// Destination chromosome length (to convert '-' strand coordinates)
+    q_size: u64,
+    /// Whether the query strand is '-'
+    reverse: bool,
+}
+
+impl Block {
+    /// Destination 0-based forward-strand coordinate of a source coordinate
+    fn map(&self, t: u64) -> u64 {
+        let q = self.q_start + (t - self.t_start);
+        if self.reverse {
+            self.q_size - 1 - q
+        } else {
+            q
+        }
+    }
+}
+
+/// Alignment blocks of one source chromosome, sorted by start
+#[derive(Debug, Default)]
+struct ChromosomeBlocks {
+    blocks: Vec<Block>,
+    /// Running maximum of `t_end`, so overlapping chains can be found
+    max_end: Vec<u64>,
+}
+
+impl ChromosomeBlocks {
+    fn finish(&mut self) {
+        self.blocks.sort_by_key(|b| (b.t_start, b.t_end));
+        let mut max_end = 0;
+        self.max_end = self
+            .blocks
+            .iter()
+            .map(|b| {
+                max_end = max_end.max(b.t_end);
+                max_end
+            })
+            .collect();
+    }
+
+    /// All blocks containing a 0-based source coordinate
+    fn containing(&self, t: u64) -> Vec<&Block> {
+        let end = self.blocks.partition_point(|b| b.t_start <= t);
+        (0..end)
+            .rev()
+            .take_while(|&i| self.max_end[i] > t)
+            .map(|i| &self.blocks[i])
+            .filter(|b| t < b.t_end)
+            .collect()
+    }
+}
+
+/// Liftover between two builds from a UCSC chain file
+#[derive(Debug)]
+pub struct LiftOver {
+    from: GenomeBuild,
+    to: GenomeBuild,
+    chromosomes: HashMap<u8, ChromosomeBlocks>,
+}
+
+impl LiftOver {
+    /// Load a chain file (plain or gzip-compressed)
+    ///
+    /// # Arguments
+    /// * `path` - Chain file (e.g., "hg38ToHg19.over.chain.gz")
+    /// * `from` - Build of the chain's target (source) coordinates
+    /// * `to` - Build of the chain's query (destination) coordinates
+    pub fn load(path: impl AsRef<Path>, from: GenomeBuild, to: GenomeBuild) -> Result<Self, LiftOverError> {
+        let mut reader = BufReader::new(File::open(path.as_ref())?);
+
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Self::parse_reader(BufReader::new(MultiGzDecoder::new(reader)), from, to)
+        } else {
+            Self::parse_reader(reader, from, to)
+        }
+    }
+
+    /// Source and destination builds named by a UCSC chain file name
+    ///
+    /// "hg38ToHg19.over.chain.gz" gives `(GRCh38, GRCh37)`.
+    pub fn builds_from_file_name(name: &str) -> Option<(GenomeBuild, GenomeBuild)> {
+        let stem = name.split('.').next()?;
+        let lower = stem.to_ascii_lowercase();
+        let split = lower.find("to")?;
+        let from = GenomeBuild::from_reference(&lower[..split]);
+        let to = GenomeBuild::from_reference(&lower[split + 2..]);
+        (from.is_known() && to.is_known() && from != to).then_some((from, to))
+    }
+
+    /// Parse an uncompressed chain file from a reader
+    pub fn parse_reader<R: Read>(
+        reader: BufReader<R>,
+        from: GenomeBuild,
+        to: GenomeBuild,
+    ) -> Result<Self, LiftOverError> {
+        let mut chromosomes: HashMap<u8, ChromosomeBlocks> = HashMap::new();
+
+        // Current chain: source chromosome, next source/destination offsets, template block
+        let mut current: Option<(Option<u8>, u64, u64, Block)> = None;
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = idx + 1;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = |details: &str| LiftOverError::InvalidFormat {
+                line: line_number,
+                details: details.to_string(),
+            };
+            let number = |value: &str| value.parse::<u64>().map_err(|_| invalid("expected an integer"));
+
+            if fields.is_empty() || fields[0].starts_with('#') {
+                continue;
+            }
+
+            if fields[0] == "chain" {
+                if fields.len() < 12 {
+                    return Err(invalid("chain header needs 12 fields"));
+                }
+                let t_chromosome = parse_chromosome(fields[2]);
+                let template = Block {
+                    t_start: 0,
+                    t_end: 0,
+                    q_chromosome: parse_chromosome(fields[7]),
+                    q_start: 0,
+                    q_size: number(fields[8])?,
+                    reverse: fields[9] == "-",
+                };
+                current = Some((t_chromosome, number(fields[5])?, number(fields[10])?, template));
+                continue;
+            }
+
+            let Some((t_chromosome, t_pos, q_pos, template)) = current.as_mut() else {
+                return Err(invalid("alignment data before chain header"));
+            };
+
+            let size = number(fields[0])?;
+            if let Some(chr) = t_chromosome {
+                chromosomes.entry(*chr).or_default().blocks.push(Block {
+                    t_start: *t_pos,
+                    t_end: *t_pos + size,
+                    q_start: *q_pos,
+                    ..*template
+                });
+            }
+
+            match fields.len() {
+                3 => {
+                    *t_pos += size + number(fields[1])?;
+                    *q_pos += size + number(fields[2])?;
+                }
+                1 => current = None,
+                _ => return Err(invalid("alignment line needs 1 or 3 fields")),
+            }
+        }
+
+        if chromosomes.values().all(|c| c.blocks.is_empty()) {
+            return Err(LiftOverError::EmptyFile);
+        }
+        for blocks in chromosomes.values_mut() {
+            blocks.finish();
+        }
+
+        Ok(Self { from, to, chromosomes })
+    }
+
+    /// Source build
+    pub fn source_build(&self) -> GenomeBuild {
+        self.from
+    }
+
+    /// Destination build
+    pub fn destination_build(&self) -> GenomeBuild {
+        self.to
+    }
+
+    /// Empty stats for this liftover's builds
+    pub fn stats(&self) -> LiftOverStats {
+        LiftOverStats {
+            from: self.from,
+            to: self.to,
+            ..Default::default()
+        }
+    }
+
+    /// Lift a span of `length` bases starting at a 1-based position
+    ///
+    /// The whole span must lie in one alignment block. On reverse-strand
+    /// blocks the returned position is the first base of the span on the
+    /// destination's forward strand.
+    pub fn map_span(&self, chromosome: u8, position: u64, length: u64) -> Result<MappedPosition, UnmappedReason> {
+        let start = position.checked_sub(1).ok_or(UnmappedReason::NoAlignment)?;
+        let end = start + length.max(1) - 1;
+
+        let blocks = self
+            .chromosomes
+            .get(&chromosome)
+            .map(|c| c.containing(start))
+            .unwrap_or_default();
+
+        let block = match blocks.as_slice() {
+            [] => return Err(UnmappedReason::NoAlignment),
+            [block] => *block,
+            [first, rest @ ..] => {
+                let target = (first.q_chromosome, first.map(start));
+                if rest.iter().any(|b| (b.q_chromosome, b.map(start)) != target) {
+                    return Err(UnmappedReason::MultipleAlignments);
+                }
+                *first
+            }
+        };
+
+        if end >= block.t_end {
+            return Err(UnmappedReason::SpansGap);
+        }
+        let q_chromosome = block.q_chromosome.ok_or(UnmappedReason::UnplacedContig)?;
+
+        Ok(MappedPosition {
+            chromosome: q_chromosome,
+            position: block.map(start).min(block.map(end)) + 1,
+            reverse_strand: block.reverse,
+        })
+    }
+
+    /// Lift a raw genotype record, complementing the genotype on reverse strands
+    ///
+    /// # Returns
+    /// * `Ok((record, flipped))` - Lifted record and whether it was complemented
+    /// * `Err(UnmappedReason)` - Record cannot be placed in the destination build
+    pub fn lift_genome23(&self, record: &Genome23Record) -> Result<(Genome23Record, bool), UnmappedReason> {
+        let chromosome = parse_chromosome(&record.chromosome).ok_or(UnmappedReason::UnplacedContig)?;
+        let mapped = self.map_span(chromosome, record.position, 1)?;
+
+        let genotype = if mapped.reverse_strand {
+            flip_strand(&record.genotype)
+        } else {
+            record.genotype.clone()
+        };
+
+        Ok((
+            Genome23Record {
+                rsid: record.rsid.clone(),
+                chromosome: chromosome_name(mapped.chromosome),
+                position: mapped.position,
+                genotype,
+            },
+            mapped.reverse_strand,
+        ))
+    }
+
+    /// Lift an imputed VCF record, reverse-complementing alleles on reverse strands
+    ///
+    /// # Returns
+    /// * `Ok((record, flipped))` - Lifted record (in the destination build)
+    /// * `Err(UnmappedReason)` - Record cannot be placed in the destination build
+    pub fn lift_vcf_record(&self, record: &VCFRecord) -> Result<(VCFRecord, bool), UnmappedReason> {
+        let (chromosome, position, ref_allele, alt_allele, flipped) = self.lift_alleles(
+            record.chromosome,
+            record.position,
+            &record.ref_allele,
+            &record.alt_allele,
+        )?;
+
+        Ok((
+            VCFRecord {
+                chromosome,
+                position,
+                genome_build: self.to,
+                ref_allele,
+                alt_allele,
+                ..record.clone()
+            },
+            flipped,
+        ))
+    }
+
+    /// Lift an imputation server .info record, reverse-complementing alleles on
+    /// reverse strands
+    ///
+    /// Lifted alongside the VCF so the (position, REF, ALT) keys still match.
+    pub fn lift_info_record(
+        &self,
+        record: &ImputationInfoRecord,
+    ) -> Result<(ImputationInfoRecord, bool), UnmappedReason> {
+        let (chromosome, position, ref_allele, alt_allele, flipped) = self.lift_alleles(
+            record.chromosome,
+            record.position,
+            &record.ref_allele,
+            &record.alt_allele,
+        )?;
+
+        Ok((
+            ImputationInfoRecord {
+                chromosome,
+                position,
+                ref_allele,
+                alt_allele,
+                ..record.clone()
+            },
+            flipped,
+        ))
+    }
+
+    /// Lift a reference panel variant, reverse-complementing alleles on reverse strands
+    ///
+    /// Sample genotypes are allele indices, so they are unchanged.
+    pub fn lift_panel_variant(
+        &self,
+        variant: &ReferencePanelVariant,
+    ) -> Result<(ReferencePanelVariant, bool), UnmappedReason> {
+        let (chromosome, position, ref_allele, alt_allele, flipped) = self.lift_alleles(
+            variant.chromosome,
+            variant.position,
+            &variant.ref_allele,
+            &variant.alt_allele,
+        )?;
+
+        Ok((
+            ReferencePanelVariant {
+                chromosome,
+                position,
+                ref_allele,
+                alt_allele,
+                ..variant.clone()
+            },
+            flipped,
+        ))
+    }
+
+    /// Lift a REF/ALT pair, returning (chromosome, position, REF, ALT, flipped)
+    fn lift_alleles(
+        &self,
+        chromosome: u8,
+        position: u64,
+        ref_allele: &str,
+        alt_allele: &str,
+    ) -> Result<(u8, u64, String, String, bool), UnmappedReason> {
+        let mapped = self.map_span(chromosome, position, ref_allele.len() as u64)?;
+
+        if !mapped.reverse_strand {
+            return Ok((mapped.chromosome, mapped.position, ref_allele.to_string(), alt_allele.to_string(), false));
+        }
+        if ref_allele.len() != alt_allele.len() {
+            return Err(UnmappedReason::ReverseStrandIndel);
+        }
+
+        Ok((
+            mapped.chromosome,
+            mapped.position,
+            reverse_complement(ref_allele),
+            reverse_complement(alt_allele),
+            true,
+        ))
+    }
+}
+
+/// Reverse complement of a DNA sequence (other characters are kept)
+fn reverse_complement(sequence: &str) -> String {
+    flip_strand(&sequence.chars().rev().collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Genotype;
+
+    // chr1 source 0-1000 maps forward to chr1 10000-11000 (with a 10 bp gap
+    // at 500); chr2 source 0-100 maps to the '-' strand of chr2 (size 1000);
+    // chr5 source 100-300 maps to the '-' strand of chr5 (size 2000) in two
+    // blocks with a 10 bp gap at 200
+    const CHAIN: &str = "\
+chain 1000 chr1 249250621 + 0 1000 chr1 248956422 + 10000 11010 1
+500 10 20
+490
+
+chain 500 chr2 243199373 + 0 100 chr2 1000 - 0 100 2
+100
+
+chain 10 chr3 198022430 + 0 50 chr6_apd_hap1 4622290 + 0 50 3
+50
+
+chain 400 chr5 180915260 + 100 300 chr5 2000 - 50 250 4
+100 10 10
+90
+";
+
+    fn liftover() -> LiftOver {
+        LiftOver::parse_reader(BufReader::new(CHAIN.as_bytes()), GenomeBuild::GRCh37, GenomeBuild::GRCh38).unwrap()
+    }
+
+    #[test]
+    fn test_map_span() {
+        let lift = liftover();
+
+        // First block: offset 10000
+        assert_eq!(lift.map_span(1, 1, 1).unwrap().position, 10_001);
+        assert_eq!(lift.map_span(1, 500, 1).unwrap().position, 10_500);
+        // Second block starts at source 510, destination 10520
+        assert_eq!(lift.map_span(1, 511, 1).unwrap().position, 10_521);
+        assert_eq!(lift.map_span(1, 505, 1), Err(UnmappedReason::NoAlignment));
+        assert_eq!(lift.map_span(1, 499, 3), Err(UnmappedReason::SpansGap));
+        assert_eq!(lift.map_span(3, 10, 1), Err(UnmappedReason::UnplacedContig));
+        assert_eq!(lift.map_span(4, 10, 1), Err(UnmappedReason::NoAlignment));
+
+        // Reverse strand: source base 0 is destination base 999
+        let mapped = lift.map_span(2, 1, 1).unwrap();
+        assert_eq!(mapped.position, 1000);
+        assert!(mapped.reverse_strand);
+        assert_eq!(lift.map_span(2, 1, 2).unwrap().position, 999);
+    }
+
+    #[test]
+    fn test_lift_records() {
+        let lift = liftover();
+        let mut stats = lift.stats();
+
+        let genome = Genome23Record {
+            rsid: "rs1".to_string(),
+            chromosome: "2".to_string(),
+            position: 10,
+            genotype: "AG".to_string(),
+        };
+        let result = lift.lift_genome23(&genome);
+        stats.record(&result);
+        let (lifted, flipped) = result.unwrap();
+        assert!(flipped);
+        assert_eq!(lifted.chromosome, "2");
+        assert_eq!(lifted.position, 991);
+        assert_eq!(lifted.genotype, "TC");
+
+        let vcf = VCFRecord {
+            rsid: "rs2".to_string(),
+            chromosome: 2,
+            position: 20,
+            genome_build: GenomeBuild::GRCh37,
+            ref_allele: "AC".to_string(),
+            alt_allele: "GT".to_string(),
+            dosage: 1.0,
+            imputation_quality: Some(0.9),
+            samples: Vec::new(),
+        };
+        let (lifted, _) = lift.lift_vcf_record(&vcf).unwrap();
+        assert_eq!(lifted.position, 980);
+        assert_eq!(lifted.ref_allele, "GT");
+        assert_eq!(lifted.alt_allele, "AC");
+        assert_eq!(lifted.genome_build, GenomeBuild::GRCh38);
+
+        let indel = VCFRecord { alt_allele: "A".to_string(), ..vcf };
+        let result = lift.lift_vcf_record(&indel);
+        assert_eq!(result.as_ref().err(), Some(&UnmappedReason::ReverseStrandIndel));
+        stats.record(&result);
+
+        assert_eq!(stats.lifted, 1);
+        assert_eq!(stats.strand_flipped, 1);
+        assert_eq!(stats.unmapped_count(), 1);
+    }
+
+    fn vcf(chromosome: u8, position: u64, ref_allele: &str, alt_allele: &str) -> VCFRecord {
+        VCFRecord {
+            rsid: format!("{}:{}", chromosome, position),
+            chromosome,
+            position,
+            genome_build: GenomeBuild::GRCh37,
+            ref_allele: ref_allele.to_string(),
+            alt_allele: alt_allele.to_string(),
+            dosage: 1.0,
+            imputation_quality: Some(0.9),
+            samples: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_reverse_strand_blocks() {
+        let lift = liftover();
+
+        // First block: source 100-200 is query 50-150 on '-', forward 1850-1950
+        let mapped = lift.map_span(5, 101, 1).unwrap();
+        assert_eq!(mapped, MappedPosition { chromosome: 5, position: 1950, reverse_strand: true });
+        assert_eq!(lift.map_span(5, 200, 1).unwrap().position, 1851);
+
+        // Second block follows a 10 bp gap on both sides
+        assert_eq!(lift.map_span(5, 211, 1).unwrap().position, 1840);
+        assert_eq!(lift.map_span(5, 300, 1).unwrap().position, 1751);
+
+        // A span starts at its lowest forward-strand base
+        assert_eq!(lift.map_span(5, 211, 2).unwrap().position, 1839);
+        assert_eq!(lift.map_span(5, 101, 3).unwrap().position, 1948);
+    }
+
+    #[test]
+    fn test_positions_between_blocks() {
+        let lift = liftover();
+
+        // Forward chain: gap covers source bases 501-510
+        assert_eq!(lift.map_span(1, 500, 1).unwrap().position, 10_500);
+        assert_eq!(lift.map_span(1, 501, 1), Err(UnmappedReason::NoAlignment));
+        assert_eq!(lift.map_span(1, 510, 1), Err(UnmappedReason::NoAlignment));
+        assert_eq!(lift.map_span(1, 500, 2), Err(UnmappedReason::SpansGap));
+
+        // Reverse chain: gap covers source bases 201-210, chain covers 101-300
+        for position in [201, 205, 210, 100, 301] {
+            assert_eq!(lift.map_span(5, position, 1), Err(UnmappedReason::NoAlignment), "position {}", position);
+        }
+        assert_eq!(lift.map_span(5, 199, 3), Err(UnmappedReason::SpansGap));
+        assert_eq!(lift.map_span(5, 200, 2), Err(UnmappedReason::SpansGap));
+        assert_eq!(lift.map_span(5, 0, 1), Err(UnmappedReason::NoAlignment));
+
+        let genome = Genome23Record {
+            rsid: "rs1".to_string(),
+            chromosome: "5".to_string(),
+            position: 205,
+            genotype: "AG".to_string(),
+        };
+        assert_eq!(lift.lift_genome23(&genome).err(), Some(UnmappedReason::NoAlignment));
+    }
+
+    #[test]
+    fn test_reverse_complemented_alleles() {
+        let lift = liftover();
+
+        // Multi-base alleles are reversed as well as complemented
+        let (lifted, flipped) = lift.lift_vcf_record(&vcf(5, 211, "ACG", "ATG")).unwrap();
+        assert!(flipped);
+        assert_eq!(lifted.position, 1838);
+        assert_eq!(lifted.ref_allele, "CGT");
+        assert_eq!(lifted.alt_allele, "CAT");
+
+        // A/T stays an A/T site with the alleles exchanged
+        let (lifted, flipped) = lift.lift_vcf_record(&vcf(5, 150, "A", "T")).unwrap();
+        assert!(flipped);
+        assert_eq!((lifted.ref_allele.as_str(), lifted.alt_allele.as_str()), ("T", "A"));
+
+        // Forward blocks keep the alleles
+        let (lifted, flipped) = lift.lift_vcf_record(&vcf(1, 100, "AC", "A")).unwrap();
+        assert!(!flipped);
+        assert_eq!(lifted.position, 10_100);
+        assert_eq!((lifted.ref_allele.as_str(), lifted.alt_allele.as_str()), ("AC", "A"));
+
+        // Panel genotypes are allele indices and survive the flip unchanged
+        let panel = ReferencePanelVariant {
+            chromosome: 5,
+            position: 101,
+            rsid: Some("rs1".to_string()),
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            phased: true,
+            allele_freq: Some(0.3),
+            minor_allele_freq: Some(0.3),
+            imputation_quality: None,
+            is_typed: true,
+            sample_genotypes: vec![Genotype::haploid(Some(1)); 2],
+        };
+        let (lifted, flipped) = lift.lift_panel_variant(&panel).unwrap();
+        assert!(flipped);
+        assert_eq!(lifted.position, 1950);
+        assert_eq!((lifted.ref_allele.as_str(), lifted.alt_allele.as_str()), ("T", "C"));
+        assert_eq!(lifted.sample_genotypes, panel.sample_genotypes);
+
+        let genome = Genome23Record {
+            rsid: "rs2".to_string(),
+            chromosome: "5".to_string(),
+            position: 250,
+            genotype: "C".to_string(),
+        };
+        assert_eq!(lift.lift_genome23(&genome).unwrap().0.genotype, "G");
+    }
+
+    #[test]
+    fn test_unmapped_counts_in_stats() {
+        let lift = liftover();
+        let mut stats = lift.stats();
+
+        let records = [
+            vcf(1, 100, "A", "G"),
+            vcf(5, 101, "A", "G"),
+            vcf(1, 505, "A", "G"),
+            vcf(5, 205, "A", "G"),
+            vcf(5, 199, "ACG", "ATG"),
+            vcf(3, 10, "A", "G"),
+            vcf(5, 150, "A", "AT"),
+        ];
+        for record in &records {
+            stats.record(&lift.lift_vcf_record(record));
+        }
+
+        assert_eq!(stats.lifted, 2);
+        assert_eq!(stats.strand_flipped, 1);
+        assert_eq!(stats.unmapped_count(), 5);
+        assert_eq!(stats.unmapped[&UnmappedReason::NoAlignment], 2);
+        assert_eq!(stats.unmapped[&UnmappedReason::SpansGap], 1);
+        assert_eq!(stats.unmapped[&UnmappedReason::UnplacedContig], 1);
+        assert_eq!(stats.unmapped[&UnmappedReason::ReverseStrandIndel], 1);
+        assert!(!stats.unmapped.contains_key(&UnmappedReason::MultipleAlignments));
+        assert_eq!(
+            stats.summary(),
+            "GRCh37 -> GRCh38: 2 lifted (1 strand-flipped, 0 REF mismatches), 5 unmapped \
+             (2 no_alignment, 1 spans_gap, 1 unplaced_contig, 1 reverse_strand_indel)"
+        );
+    }
+
+    #[test]
+    fn test_ref_mismatch_counts_in_stats() {
+        let lift = liftover();
+        let mut stats = lift.stats();
+
+        let lifted: Vec<VCFRecord> = [vcf(1, 100, "A", "G"), vcf(1, 200, "C", "T"), vcf(1, 300, "G", "A"), vcf(1, 400, "T", "C")]
+            .iter()
+            .map(|r| lift.lift_vcf_record(r).unwrap().0)
+            .collect();
+
+        let panel_variant = |position: u64, ref_allele: &str| ReferencePanelVariant {
+            chromosome: 1,
+            position,
+            rsid: None,
+            ref_allele: ref_allele.to_string(),
+            alt_allele: "N".to_string(),
+            phased: true,
+            allele_freq: None,
+            minor_allele_freq: None,
+            imputation_quality: None,
+            is_typed: false,
+            sample_genotypes: Vec::new(),
+        };
+        // 10100 agrees, 10200 disagrees, 10300 agrees with one of two
+        // multi-allelic rows and 10400 is not in the panel
+        let panel = vec![
+            panel_variant(10_100, "A"),
+            panel_variant(10_200, "G"),
+            panel_variant(10_300, "C"),
+            panel_variant(10_300, "G"),
+        ];
+        stats.count_ref_mismatches(&lifted, &panel);
+        assert_eq!(stats.ref_mismatch, 1);
+
+        // Job metadata stores the stats as JSON
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["ref_mismatch"], 1);
+        assert_eq!(json["lifted"], 0);
+        assert_eq!(json["unmapped"], serde_json::json!({}));
+
+        stats.record(&lift.lift_vcf_record(&vcf(1, 505, "A", "G")));
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["unmapped"]["no_alignment"], 1);
+    }
+
+    #[test]
+    fn test_builds_from_file_name() {
+        assert_eq!(
+            LiftOver::builds_from_file_name("hg38ToHg19.over.chain.gz"),
+            Some((GenomeBuild::GRCh38, GenomeBuild::GRCh37))
+        );
+        assert_eq!(
+            LiftOver::builds_from_file_name("GRCh37_to_GRCh38.chain.gz"),
+            Some((GenomeBuild::GRCh37, GenomeBuild::GRCh38))
+        );
+        assert_eq!(LiftOver::builds_from_file_name("genes.chain"), None);
+    }
+}
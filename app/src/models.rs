@@ -8,7 +8,25 @@
 // Version: 2.0.0
 // ==============================================================================
 
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Sample ID of the user (after the 50 reference samples samp1-samp50)
+pub const USER_SAMPLE_ID: &str = "samp51";
+
+/// Number of reference panel samples ahead of the user
+pub const REFERENCE_SAMPLE_COUNT: usize = 50;
+
+/// Sample IDs of the reference panel samples (samp1-samp50)
+///
+/// Extra samples from a multi-sample VCF follow the user and are not part
+/// of the reference.
+pub fn reference_sample_ids() -> Vec<String> {
+    (1..=REFERENCE_SAMPLE_COUNT).map(|i| format!("samp{}", i)).collect()
+}
 
 /// Source of genomic data
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -31,16 +49,189 @@ impl DataSource {
     }
 }
 
+/// Genotype parsing error
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("Invalid genotype: {0:?}")]
+pub struct GenotypeParseError(pub String);
+
+/// Genotype call at one site (VCF GT notation)
+///
+/// Holds one allele index per copy (0 = REF, 1 = ALT, `None` = missing "."),
+/// whether the call is phased ("|") or unphased ("/"), and the ploidy:
+/// 2 for diploid sites, 1 for haploid sites (male non-PAR X, Y, MT) and 0
+/// where the chromosome is absent (chrY in females), written as ".".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Genotype {
+    alleles: [Option<u8>; 2],
+    ploidy: u8,
+    phased: bool,
+}
+
+impl Genotype {
+    /// Diploid genotype ("0|1", "1/1", "./.")
+    pub fn diploid(first: Option<u8>, second: Option<u8>, phased: bool) -> Self {
+        Self {
+            alleles: [first, second],
+            ploidy: 2,
+            phased,
+        }
+    }
+
+    /// Haploid genotype ("0", "1", ".")
+    pub fn haploid(allele: Option<u8>) -> Self {
+        Self {
+            alleles: [allele, None],
+            ploidy: 1,
+            phased: false,
+        }
+    }
+
+    /// Missing genotype for the given ploidy ("./.", "." when haploid or absent)
+    pub fn missing(ploidy: u8) -> Self {
+        Self {
+            alleles: [None, None],
+            ploidy: ploidy.min(2),
+            phased: false,
+        }
+    }
+
+    /// Phased diploid genotype carrying `alt_count` ALT alleles
+    /// (0 → "0|0", 1 → "0|1", 2 or more → "1|1")
+    pub fn from_alt_count(alt_count: u8) -> Self {
+        Self::diploid(Some(u8::from(alt_count >= 2)), Some(u8::from(alt_count >= 1)), true)
+    }
+
+    /// Hard call for a dosage at a site of the given ploidy
+    ///
+    /// Diploid: "0|0" below 0.5, "0|1" below 1.5, else "1|1". Haploid: "0"
+    /// below 0.5, else "1". Absent (ploidy 0): ".".
+    pub fn from_dosage(dosage: f64, ploidy: u8) -> Self {
+        match ploidy {
+            0 => Self::missing(0),
+            1 => Self::haploid(Some(u8::from(dosage >= 0.5))),
+            _ if dosage < 0.5 => Self::from_alt_count(0),
+            _ if dosage < 1.5 => Self::from_alt_count(1),
+            _ => Self::from_alt_count(2),
+        }
+    }
+
+    /// Allele indices, one per copy
+    pub fn alleles(&self) -> &[Option<u8>] {
+        &self.alleles[..usize::from(self.ploidy)]
+    }
+
+    pub fn ploidy(&self) -> u8 {
+        self.ploidy
+    }
+
+    pub fn is_phased(&self) -> bool {
+        self.phased
+    }
+
+    /// Whether any allele is missing (always true when absent)
+    pub fn is_missing(&self) -> bool {
+        self.alleles().is_empty() || self.alleles().iter().any(Option::is_none)
+    }
+
+    /// Number of non-REF alleles, or None if the genotype is missing
+    pub fn alt_dosage(&self) -> Option<f64> {
+        if self.is_missing() {
+            return None;
+        }
+        Some(self.alleles().iter().filter(|a| **a != Some(0)).count() as f64)
+    }
+
+    /// Recode for one ALT allele (1-based) of a split multi-allelic record
+    ///
+    /// The selected allele becomes 1 and other ALT alleles become 0;
+    /// phasing and missing alleles are kept.
+    pub fn for_alt_allele(self, allele: usize) -> Self {
+        let recode = |a: Option<u8>| a.map(|a| u8::from(usize::from(a) == allele));
+        Self {
+            alleles: [recode(self.alleles[0]), recode(self.alleles[1])],
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Genotype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ploidy == 0 {
+            return f.write_str(".");
+        }
+
+        let separator = if self.phased { "|" } else { "/" };
+        for (idx, allele) in self.alleles().iter().enumerate() {
+            if idx > 0 {
+                f.write_str(separator)?;
+            }
+            match allele {
+                Some(a) => write!(f, "{}", a)?,
+                None => f.write_str(".")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Genotype {
+    type Err = GenotypeParseError;
+
+    /// Parse a VCF GT value ("0|1", "1/1", "./.", "1", ".")
+    ///
+    /// Each allele must be "." or an unsigned index; mixed separators,
+    /// more than two alleles and anything else are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || GenotypeParseError(s.to_string());
+        let allele = |part: &str| -> Result<Option<u8>, GenotypeParseError> {
+            match part {
+                "." => Ok(None),
+                _ if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                    part.parse().map(Some).map_err(|_| error())
+                }
+                _ => Err(error()),
+            }
+        };
+
+        let phased = s.contains('|');
+        if phased && s.contains('/') {
+            return Err(error());
+        }
+
+        let parts: Vec<&str> = s.split(['|', '/']).collect();
+        match parts.as_slice() {
+            [a] => Ok(Self::haploid(allele(a)?)),
+            [a, b] => Ok(Self::diploid(allele(a)?, allele(b)?, phased)),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Serialize for Genotype {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Genotype {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
 /// Sample-specific genomic data at a variant position
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SampleData {
     /// Sample identifier (e.g., "samp1", "samp2", ..., "samp50", "samp51")
     pub sample_id: String,
 
-    /// Phased genotype (e.g., "0|0", "0|1", "1|0", "1|1")
-    pub genotype: String,
+    /// Genotype ("0|1"; a single allele at haploid sites such as male
+    /// non-PAR X, Y and MT; "." where the chromosome is absent)
+    pub genotype: Genotype,
 
-    /// Allele dosage (0.0 to 2.0)
+    /// Allele dosage (0.0 to 2.0; 0.0 to 1.0 at haploid sites, or 0.0/2.0
+    /// under `HaploidConvention::Homozygous`)
     pub dosage: f64,
 
     /// Source of this sample's data
@@ -56,7 +247,7 @@ pub struct MultiSampleVariant {
     /// rsID (e.g., "rs12345")
     pub rsid: String,
 
-    /// Chromosome (1-22)
+    /// Chromosome code (1-22, 23=X, 24=Y, 26=MT; see `chromosomes`)
     pub chromosome: u8,
 
     /// Position (GRCh37/hg19)
@@ -84,6 +275,7 @@ pub struct MultiSampleVariant {
 /// Reference panel variant (50 samples only)
 #[derive(Debug, Clone)]
 pub struct ReferencePanelVariant {
+    /// Chromosome code (1-22, 23=X, 24=Y, 26=MT)
     pub chromosome: u8,
     pub position: u64,
     pub rsid: Option<String>,
@@ -94,8 +286,8 @@ pub struct ReferencePanelVariant {
     pub minor_allele_freq: Option<f64>,
     pub imputation_quality: Option<f64>,
     pub is_typed: bool,
-    /// Sample genotypes: Vec of 50 genotype strings ("0|0", etc.)
-    pub sample_genotypes: Vec<String>,
+    /// Genotypes of the 50 reference samples
+    pub sample_genotypes: Vec<Genotype>,
 }
 
 /// Quality threshold for filtering imputed variants
@@ -115,7 +307,7 @@ pub enum QualityThreshold {
 pub struct MergedVariant {
     /// SNP identifier (rsID)
     pub rsid: String,
-    /// Chromosome number (1-22)
+    /// Chromosome code (1-22, 23=X, 24=Y, 26=MT)
     pub chromosome: u8,
     /// Base pair position (GRCh37/hg19)
     pub position: u64,
@@ -170,4 +362,64 @@ mod tests {
         assert_eq!(DataSource::Imputed.as_str(), "Imputed");
         assert_eq!(DataSource::ImputedLowQual.as_str(), "ImputedLowQual");
     }
+
+    #[test]
+    fn test_genotype_parse_and_format() {
+        for text in ["0|0", "0|1", "1|0", "1/1", "./.", ".|1", "0", "1", "."] {
+            assert_eq!(text.parse::<Genotype>().unwrap().to_string(), text);
+        }
+
+        let het: Genotype = "0|1".parse().unwrap();
+        assert!(het.is_phased());
+        assert_eq!(het.ploidy(), 2);
+        assert_eq!(het.alt_dosage(), Some(1.0));
+        assert_eq!("1".parse::<Genotype>().unwrap().alt_dosage(), Some(1.0));
+        assert_eq!("./.".parse::<Genotype>().unwrap().alt_dosage(), None);
+        assert!("0|.".parse::<Genotype>().unwrap().is_missing());
+
+        for garbage in ["", "A|G", "0|1|1", "0|1/1", "-1|0", "+1|0", "0 |1", "256|0"] {
+            assert!(garbage.parse::<Genotype>().is_err(), "{:?}", garbage);
+        }
+    }
+
+    #[test]
+    fn test_genotype_constructors() {
+        assert_eq!(Genotype::from_dosage(0.9, 2).to_string(), "0|1");
+        assert_eq!(Genotype::from_dosage(1.6, 2).to_string(), "1|1");
+        assert_eq!(Genotype::from_dosage(0.9, 1).to_string(), "1");
+        assert_eq!(Genotype::from_dosage(0.0, 0).to_string(), ".");
+        assert_eq!(Genotype::missing(2).to_string(), "./.");
+        assert_eq!(Genotype::missing(1).to_string(), ".");
+        assert!(Genotype::missing(0).is_missing());
+
+        // Split multi-allelic record: "1|2" for ALT 2 becomes "0|1"
+        let multi: Genotype = "1|2".parse().unwrap();
+        assert_eq!(multi.for_alt_allele(2).to_string(), "0|1");
+        assert_eq!(multi.for_alt_allele(1).to_string(), "1|0");
+    }
+
+    #[test]
+    fn test_sample_data_serializes_genotype_as_string() {
+        let sample = SampleData {
+            sample_id: USER_SAMPLE_ID.to_string(),
+            genotype: Genotype::haploid(Some(1)),
+            dosage: 1.0,
+            source: DataSource::Genotyped,
+            imputation_quality: None,
+        };
+
+        let json = serde_json::to_value(&sample).unwrap();
+        assert_eq!(json["genotype"], "1");
+        let parsed: SampleData = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.genotype, sample.genotype);
+
+        let invalid = serde_json::json!({
+            "sample_id": "samp1",
+            "genotype": "A|G",
+            "dosage": 0.0,
+            "source": "Genotyped",
+            "imputation_quality": null,
+        });
+        assert!(serde_json::from_value::<SampleData>(invalid).is_err());
+    }
 }
@@ -27,7 +27,11 @@ use parquet::file::properties::WriterProperties;
 // SQLite for queryable database
 use rusqlite::{params, Connection};
 
-use crate::parsers::PgsDataset;
+use crate::chromosomes::{chromosome_name, ALL_CHROMOSOMES, CHROMOSOME_ENCODING};
+use crate::concordance::ConcordanceReport;
+use crate::genome_build::{GenomeBuild, REFERENCE_PANEL_BUILD};
+use crate::parsers::{PgsDataset, PgsNormalization};
+use crate::pgs_interpretation::PgsInterpretation;
 use crate::models::{DataSource, MergedVariant, MultiSampleVariant, SampleData};
 
 /// Supported output formats for web delivery
@@ -86,7 +90,7 @@ pub struct GeneticAnalysisOutput {
     /// Metadata about the analysis
     pub metadata: OutputMetadata,
 
-    /// Merged variants per chromosome (chr1 - chr22)
+    /// Merged variants per chromosome (chr1 - chr22, X, Y, MT)
     pub chromosomes: HashMap<u8, Vec<MergedVariantOutput>>,
 
     /// Polygenic scores (unscaled)
@@ -102,13 +106,13 @@ pub struct MultiSampleGeneticOutput {
     /// Metadata about the analysis
     pub metadata: OutputMetadata,
 
-    /// Multi-sample variants per chromosome (chr1 - chr22)
+    /// Multi-sample variants per chromosome (chr1 - chr22, X, Y, MT)
     pub chromosomes: HashMap<u8, Vec<MultiSampleVariantOutput>>,
 
     /// Polygenic scores (unscaled) - for all 51 samples
     pub pgs_unscaled: Vec<PgsRecordOutput>,
 
-    /// Polygenic scores (z-score normalized) - for all 51 samples
+    /// Polygenic scores (normalized, see `metadata.pgs_normalization`) - for all 51 samples
     pub pgs_scaled: Vec<PgsRecordOutput>,
 }
 
@@ -126,6 +130,12 @@ pub struct OutputMetadata {
     pub imputed_snps: usize,
     pub low_quality_snps: usize,
     pub pgs_traits: Vec<String>,
+    /// Normalization used for the scaled scores (e.g., "reference_zscore")
+    #[serde(default)]
+    pub pgs_normalization: Option<String>,
+    /// Genome build of the output coordinates
+    #[serde(default)]
+    pub genome_build: GenomeBuild,
 }
 
 /// Merged variant for output (simplified from internal representation)
@@ -180,6 +190,12 @@ pub struct OutputGenerator {
     job_id: String,
     user_id: String,
     output_dir: PathBuf,
+    // Description of the user's raw genotype file (provider and chip version)
+    genome_file: String,
+    // Genome build of the merged coordinates (reference panel build)
+    genome_build: GenomeBuild,
+    // Extra imputed samples (e.g. family members) merged after the user
+    extra_samples: Vec<String>,
     // Streaming state (None if not in streaming mode)
     streaming_state: Option<StreamingState>,
 }
@@ -187,7 +203,7 @@ pub struct OutputGenerator {
 /// VCF output format preference
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VcfFormat {
-    /// Single merged VCF file for all 22 chromosomes
+    /// Single merged VCF file for all chromosomes
     Merged,
     /// Separate VCF files per chromosome (chr1.vcf.gz, chr2.vcf.gz, etc.)
     PerChromosome,
@@ -220,6 +236,11 @@ struct StreamingState {
     genotyped_variants: usize,
     low_quality_variants: usize,
     chromosomes_processed: u8,
+    // PGS traits and normalization written by append_pgs_scores()
+    pgs_traits: Vec<String>,
+    pgs_normalization: Option<PgsNormalization>,
+    // Genotyped vs imputed concordance (JSON) set by set_concordance()
+    concordance: Option<String>,
 }
 
 impl OutputGenerator {
@@ -228,10 +249,41 @@ impl OutputGenerator {
             job_id,
             user_id,
             output_dir,
+            genome_file: "23andMe genome data".to_string(),
+            genome_build: REFERENCE_PANEL_BUILD,
+            extra_samples: Vec::new(),
             streaming_state: None,
         }
     }
 
+    /// Set the genome file description recorded in output metadata
+    ///
+    /// # Arguments
+    /// * `genome_file` - Provider and chip version (e.g., "AncestryDNA V2.0 raw genotype data")
+    pub fn with_genome_file(mut self, genome_file: impl Into<String>) -> Self {
+        self.genome_file = genome_file.into();
+        self
+    }
+
+    /// Set the genome build recorded in output metadata and VCF headers
+    ///
+    /// # Arguments
+    /// * `genome_build` - Build of the merged variant positions
+    pub fn with_genome_build(mut self, genome_build: GenomeBuild) -> Self {
+        self.genome_build = genome_build;
+        self
+    }
+
+    /// Set the extra imputed samples written after the user in VCF headers
+    ///
+    /// # Arguments
+    /// * `extra_samples` - Sample names from the imputed VCF header, in the
+    ///   order they follow the user sample in each variant
+    pub fn with_extra_samples(mut self, extra_samples: Vec<String>) -> Self {
+        self.extra_samples = extra_samples;
+        self
+    }
+
     /// Generate output in specified formats (single-sample, deprecated)
     ///
     /// # Arguments
@@ -386,7 +438,7 @@ impl OutputGenerator {
                 job_id: self.job_id.clone(),
                 user_id: self.user_id.clone(),
                 processing_date: chrono::Utc::now().to_rfc3339(),
-                genome_file: "23andMe genome data".to_string(),
+                genome_file: self.genome_file.clone(),
                 imputation_server: "Michigan Imputation Server 2".to_string(),
                 reference_panel: "openSNP (50 samples)".to_string(),
                 total_snps,
@@ -394,6 +446,8 @@ impl OutputGenerator {
                 imputed_snps: total_snps - genotyped_snps,
                 low_quality_snps,
                 pgs_traits,
+                pgs_normalization: pgs_data.map(|d| d.normalization.as_str().to_string()),
+                genome_build: self.genome_build,
             },
             chromosomes,
             pgs_unscaled,
@@ -420,7 +474,7 @@ impl OutputGenerator {
                             .iter()
                             .map(|s| SampleDataOutput {
                                 sample_id: s.sample_id.clone(),
-                                genotype: s.genotype.clone(),
+                                genotype: s.genotype.to_string(),
                                 dosage: s.dosage,
                                 source: format!("{:?}", s.source),
                                 imputation_quality: s.imputation_quality,
@@ -510,7 +564,7 @@ impl OutputGenerator {
                 job_id: self.job_id.clone(),
                 user_id: self.user_id.clone(),
                 processing_date: chrono::Utc::now().to_rfc3339(),
-                genome_file: "23andMe genome data".to_string(),
+                genome_file: self.genome_file.clone(),
                 imputation_server: "Michigan Imputation Server 2".to_string(),
                 reference_panel: "openSNP (50 samples) + user (1 sample) = 51 total".to_string(),
                 total_snps,
@@ -518,6 +572,8 @@ impl OutputGenerator {
                 imputed_snps: total_snps - genotyped_snps,
                 low_quality_snps,
                 pgs_traits,
+                pgs_normalization: pgs_data.map(|d| d.normalization.as_str().to_string()),
+                genome_build: self.genome_build,
             },
             chromosomes,
             pgs_unscaled,
@@ -772,7 +828,7 @@ impl OutputGenerator {
             all_rows.iter().map(|(_, s)| s.sample_id.as_str()).collect::<Vec<_>>(),
         ));
         let genotype_array: ArrayRef = Arc::new(StringArray::from(
-            all_rows.iter().map(|(_, s)| s.genotype.as_str()).collect::<Vec<_>>(),
+            all_rows.iter().map(|(_, s)| s.genotype.to_string()).collect::<Vec<_>>(),
         ));
         let dosage_array: ArrayRef = Arc::new(Float64Array::from(
             all_rows.iter().map(|(_, s)| s.dosage).collect::<Vec<_>>(),
@@ -894,6 +950,9 @@ impl OutputGenerator {
         let genotyped_snps_str = output.metadata.genotyped_snps.to_string();
         let imputed_snps_str = output.metadata.imputed_snps.to_string();
         let low_quality_snps_str = output.metadata.low_quality_snps.to_string();
+        let chromosome_encoding = CHROMOSOME_ENCODING.to_string();
+        let pgs_normalization = output.metadata.pgs_normalization.clone().unwrap_or_default();
+        let genome_build = output.metadata.genome_build.as_str().to_string();
 
         let metadata_items = vec![
             ("job_id", &output.metadata.job_id),
@@ -906,6 +965,9 @@ impl OutputGenerator {
             ("genotyped_snps", &genotyped_snps_str),
             ("imputed_snps", &imputed_snps_str),
             ("low_quality_snps", &low_quality_snps_str),
+            ("chromosome_encoding", &chromosome_encoding),
+            ("pgs_normalization", &pgs_normalization),
+            ("genome_build", &genome_build),
         ];
 
         for (key, value) in metadata_items {
@@ -1065,6 +1127,9 @@ impl OutputGenerator {
         let genotyped_snps_str = output.metadata.genotyped_snps.to_string();
         let imputed_snps_str = output.metadata.imputed_snps.to_string();
         let low_quality_snps_str = output.metadata.low_quality_snps.to_string();
+        let chromosome_encoding = CHROMOSOME_ENCODING.to_string();
+        let pgs_normalization = output.metadata.pgs_normalization.clone().unwrap_or_default();
+        let genome_build = output.metadata.genome_build.as_str().to_string();
 
         let metadata_items = vec![
             ("job_id", &output.metadata.job_id),
@@ -1077,6 +1142,9 @@ impl OutputGenerator {
             ("genotyped_snps", &genotyped_snps_str),
             ("imputed_snps", &imputed_snps_str),
             ("low_quality_snps", &low_quality_snps_str),
+            ("chromosome_encoding", &chromosome_encoding),
+            ("pgs_normalization", &pgs_normalization),
+            ("genome_build", &genome_build),
         ];
 
         for (key, value) in metadata_items {
@@ -1198,13 +1266,14 @@ impl OutputGenerator {
         writeln!(file, "##fileformat=VCFv4.3")?;
         writeln!(file, "##fileDate={}", chrono::Utc::now().format("%Y%m%d"))?;
         writeln!(file, "##source=genetics-processor-v1.0.0")?;
+        writeln!(file, "##reference={}", output.metadata.genome_build.as_str())?;
         writeln!(file, "##INFO=<ID=DS,Number=1,Type=Float,Description=\"Dosage\">")?;
         writeln!(file, "##INFO=<ID=IQ,Number=1,Type=Float,Description=\"Imputation Quality (R²)\">")?;
         writeln!(file, "##INFO=<ID=SRC,Number=1,Type=String,Description=\"Data Source (Genotyped/Imputed/ImputedLowQual)\">")?;
         writeln!(file, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
 
         // Write variants chromosome by chromosome
-        for chr_num in 1..=22u8 {
+        for chr_num in ALL_CHROMOSOMES {
             if let Some(variants) = output.chromosomes.get(&chr_num) {
                 for variant in variants {
                     // Build INFO field with dosage, quality, and source
@@ -1219,7 +1288,7 @@ impl OutputGenerator {
                     writeln!(
                         file,
                         "chr{}\t{}\t{}\t{}\t{}\t.\t.\t{}",
-                        chr_num,
+                        chromosome_name(chr_num),
                         variant.position,
                         variant.rsid,
                         variant.ref_allele,
@@ -1231,7 +1300,7 @@ impl OutputGenerator {
         }
 
         info!(
-            "VCF output complete: {} variants across 22 chromosomes",
+            "VCF output complete: {} variants across all chromosomes",
             output.metadata.total_snps
         );
 
@@ -1256,6 +1325,7 @@ impl OutputGenerator {
         writeln!(writer, "##fileformat=VCFv4.3")?;
         writeln!(writer, "##fileDate={}", chrono::Utc::now().format("%Y%m%d"))?;
         writeln!(writer, "##source=genetics-processor-v1.0.0")?;
+        writeln!(writer, "##reference={}", output.metadata.genome_build.as_str())?;
         writeln!(writer, "##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">")?;
         writeln!(writer, "##INFO=<ID=MAF,Number=1,Type=Float,Description=\"Minor Allele Frequency\">")?;
         writeln!(writer, "##INFO=<ID=TYPED,Number=0,Type=Flag,Description=\"Variant was genotyped (not imputed)\">")?;
@@ -1282,7 +1352,7 @@ impl OutputGenerator {
         writeln!(writer)?;
 
         // Write variants chromosome by chromosome
-        for chr_num in 1..=22u8 {
+        for chr_num in ALL_CHROMOSOMES {
             if let Some(variants) = output.chromosomes.get(&chr_num) {
                 for variant in variants {
                     // Build INFO field with allele frequencies
@@ -1306,7 +1376,7 @@ impl OutputGenerator {
                     write!(
                         writer,
                         "chr{}\t{}\t{}\t{}\t{}\t.\t.\t{}\tGT:DS:IQ",
-                        chr_num,
+                        chromosome_name(chr_num),
                         variant.position,
                         variant.rsid,
                         variant.ref_allele,
@@ -1338,7 +1408,7 @@ impl OutputGenerator {
         writer.finish().context("Failed to finalize gzip compression")?;
 
         info!(
-            "Multi-sample VCF output complete: {} variants × 51 samples across 22 chromosomes",
+            "Multi-sample VCF output complete: {} variants × 51 samples across all chromosomes",
             output.metadata.total_snps
         );
 
@@ -1403,6 +1473,9 @@ impl OutputGenerator {
             genotyped_variants: 0,
             low_quality_variants: 0,
             chromosomes_processed: 0,
+            pgs_traits: Vec::new(),
+            pgs_normalization: None,
+            concordance: None,
         };
 
         // Initialize each format
@@ -1477,6 +1550,32 @@ impl OutputGenerator {
                     )
                     .context("Failed to create pgs_scaled table")?;
 
+                    conn.execute(
+                        "CREATE TABLE pgs_interpretation (
+                            sample_id TEXT NOT NULL,
+                            trait_label TEXT NOT NULL,
+                            raw_score REAL NOT NULL,
+                            reference_count INTEGER NOT NULL,
+                            reference_mean REAL NOT NULL,
+                            reference_sd REAL NOT NULL,
+                            z_score REAL NOT NULL,
+                            percentile REAL NOT NULL,
+                            normal_percentile REAL NOT NULL,
+                            r2 REAL,
+                            predicted_z REAL,
+                            predicted_z_lower REAL,
+                            predicted_z_upper REAL,
+                            predicted_value REAL,
+                            predicted_value_lower REAL,
+                            predicted_value_upper REAL,
+                            confidence_level REAL NOT NULL,
+                            units TEXT,
+                            PRIMARY KEY (sample_id, trait_label)
+                        )",
+                        [],
+                    )
+                    .context("Failed to create pgs_interpretation table")?;
+
                     // Create metadata table (will populate in finalize)
                     conn.execute(
                         "CREATE TABLE metadata (
@@ -1512,6 +1611,7 @@ impl OutputGenerator {
                             writeln!(writer, "##fileformat=VCFv4.3")?;
                             writeln!(writer, "##fileDate={}", chrono::Utc::now().format("%Y%m%d"))?;
                             writeln!(writer, "##source=genetics-processor-v1.0.0")?;
+                            writeln!(writer, "##reference={}", self.genome_build.as_str())?;
                             writeln!(writer, "##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">")?;
                             writeln!(writer, "##INFO=<ID=MAF,Number=1,Type=Float,Description=\"Minor Allele Frequency\">")?;
                             writeln!(writer, "##INFO=<ID=TYPED,Number=0,Type=Flag,Description=\"Variant was genotyped (not imputed)\">")?;
@@ -1519,12 +1619,16 @@ impl OutputGenerator {
                             writeln!(writer, "##FORMAT=<ID=DS,Number=1,Type=Float,Description=\"Dosage\">")?;
                             writeln!(writer, "##FORMAT=<ID=IQ,Number=1,Type=Float,Description=\"Imputation Quality (R²)\">")?;
 
-                            // Write header line with sample IDs (samp1-samp50 + user)
+                            // Write header line with sample IDs (samp1-samp50 + user + extra samples)
                             write!(writer, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT")?;
                             for i in 1..=50 {
                                 write!(writer, "\tsamp{}", i)?;
                             }
-                            writeln!(writer, "\tuser")?;
+                            write!(writer, "\tuser")?;
+                            for name in &self.extra_samples {
+                                write!(writer, "\t{}", name)?;
+                            }
+                            writeln!(writer)?;
 
                             state.vcf_file = Some(writer);
                             state.vcf_path = Some(path);
@@ -1566,7 +1670,7 @@ impl OutputGenerator {
     /// After this call, the chromosome data can be dropped from memory.
     ///
     /// # Arguments
-    /// * `chromosome` - Chromosome number (1-22)
+    /// * `chromosome` - Chromosome code (1-22, 23=X, 24=Y, 26=MT)
     /// * `variants` - Variants for this chromosome
     ///
     /// # Returns
@@ -1631,7 +1735,7 @@ impl OutputGenerator {
                                         variant.minor_allele_freq,
                                         if variant.is_typed { 1 } else { 0 },
                                         sample.sample_id,
-                                        sample.genotype,
+                                        sample.genotype.to_string(),
                                         sample.dosage,
                                         format!("{:?}", sample.source),
                                         sample.imputation_quality,
@@ -1677,7 +1781,7 @@ impl OutputGenerator {
                                     write!(
                                         file,
                                         "chr{}\t{}\t{}\t{}\t{}\t.\t.\t{}\tGT:DS:IQ",
-                                        chromosome,
+                                        chromosome_name(chromosome),
                                         variant.position,
                                         variant.rsid,
                                         variant.ref_allele,
@@ -1713,7 +1817,7 @@ impl OutputGenerator {
                                 // Extract filename stem (without .vcf.gz double extension)
                                 let full_name = base_path.file_name().unwrap().to_str().unwrap();
                                 let base_filename = full_name.trim_end_matches(".vcf.gz");
-                                let chr_filename = format!("{}_chr{}.vcf.gz", base_filename, chromosome);
+                                let chr_filename = format!("{}_chr{}.vcf.gz", base_filename, chromosome_name(chromosome));
                                 let chr_path = base_path.parent().unwrap().join(&chr_filename);
 
                                 // Create chromosome-specific VCF file
@@ -1725,6 +1829,7 @@ impl OutputGenerator {
                                 writeln!(writer, "##fileformat=VCFv4.3")?;
                                 writeln!(writer, "##fileDate={}", chrono::Utc::now().format("%Y%m%d"))?;
                                 writeln!(writer, "##source=genetics-processor-v1.0.0")?;
+                                writeln!(writer, "##reference={}", self.genome_build.as_str())?;
                                 writeln!(writer, "##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">")?;
                                 writeln!(writer, "##INFO=<ID=MAF,Number=1,Type=Float,Description=\"Minor Allele Frequency\">")?;
                                 writeln!(writer, "##INFO=<ID=TYPED,Number=0,Type=Flag,Description=\"Variant was genotyped (not imputed)\">")?;
@@ -1732,12 +1837,16 @@ impl OutputGenerator {
                                 writeln!(writer, "##FORMAT=<ID=DS,Number=1,Type=Float,Description=\"Dosage\">")?;
                                 writeln!(writer, "##FORMAT=<ID=IQ,Number=1,Type=Float,Description=\"Imputation Quality (R²)\">")?;
 
-                                // Write header line with sample IDs (samp1-samp50 + user)
+                                // Write header line with sample IDs (samp1-samp50 + user + extra samples)
                                 write!(writer, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT")?;
                                 for i in 1..=50 {
                                     write!(writer, "\tsamp{}", i)?;
                                 }
-                                writeln!(writer, "\tuser")?;
+                                write!(writer, "\tuser")?;
+                                for name in &self.extra_samples {
+                                    write!(writer, "\t{}", name)?;
+                                }
+                                writeln!(writer)?;
 
                                 // Write variants for this chromosome
                                 for variant in variants {
@@ -1762,7 +1871,7 @@ impl OutputGenerator {
                                     write!(
                                         writer,
                                         "chr{}\t{}\t{}\t{}\t{}\t.\t.\t{}\tGT:DS:IQ",
-                                        chromosome,
+                                        chromosome_name(chromosome),
                                         variant.position,
                                         variant.rsid,
                                         variant.ref_allele,
@@ -1803,7 +1912,7 @@ impl OutputGenerator {
 
                         let chr_filename = format!("{}_chr{}.parquet",
                             base_path.file_name().unwrap().to_str().unwrap(),
-                            chromosome);
+                            chromosome_name(chromosome));
                         let chr_path = base_path.parent().unwrap().join(&chr_filename);
 
                         // Create Arrow schema
@@ -1879,7 +1988,7 @@ impl OutputGenerator {
                                 chunk_rows.iter().map(|(_, s)| s.sample_id.as_str()).collect::<Vec<_>>(),
                             ));
                             let genotype_array: ArrayRef = Arc::new(StringArray::from(
-                                chunk_rows.iter().map(|(_, s)| s.genotype.as_str()).collect::<Vec<_>>(),
+                                chunk_rows.iter().map(|(_, s)| s.genotype.to_string()).collect::<Vec<_>>(),
                             ));
                             let dosage_array: ArrayRef = Arc::new(Float64Array::from(
                                 chunk_rows.iter().map(|(_, s)| s.dosage).collect::<Vec<_>>(),
@@ -1941,6 +2050,263 @@ impl OutputGenerator {
         Ok(())
     }
 
+    /// Write polygenic scores to streaming output
+    ///
+    /// Fills the SQLite `pgs_unscaled`/`pgs_scaled` tables and writes
+    /// `GenomicData_<job>_pgs.parquet` (sample_id, trait_label, value,
+    /// scaled_value). VCF output has no PGS scores section. Call once, before
+    /// `finalize_streaming_output()`.
+    ///
+    /// # Arguments
+    /// * `pgs_data` - Unscaled and scaled scores (uploaded or computed)
+    pub async fn append_pgs_scores(&mut self, pgs_data: &PgsDataset) -> Result<()> {
+        let state = self.streaming_state.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Streaming not initialized. Call initialize_streaming_output() first."))?;
+
+        let mut traits: Vec<String> = pgs_data.unscaled.iter().map(|r| r.label.clone()).collect();
+        traits.sort();
+        traits.dedup();
+
+        if let Some(conn) = state.sqlite_conn.as_mut() {
+            let tx = conn.transaction().context("Failed to start PGS transaction")?;
+            {
+                let mut stmt = tx
+                    .prepare("INSERT OR REPLACE INTO pgs_unscaled (sample_id, trait_label, value) VALUES (?1, ?2, ?3)")
+                    .context("Failed to prepare pgs_unscaled insert")?;
+                for record in &pgs_data.unscaled {
+                    stmt.execute(params![record.sample_id, record.label, record.value])
+                        .context("Failed to insert unscaled PGS")?;
+                }
+
+                let mut stmt = tx
+                    .prepare("INSERT OR REPLACE INTO pgs_scaled (sample_id, trait_label, value) VALUES (?1, ?2, ?3)")
+                    .context("Failed to prepare pgs_scaled insert")?;
+                for record in &pgs_data.scaled {
+                    stmt.execute(params![record.sample_id, record.label, record.value])
+                        .context("Failed to insert scaled PGS")?;
+                }
+            }
+            tx.commit().context("Failed to commit PGS data")?;
+            info!("  ✓ SQLite: {} PGS records for {} traits", pgs_data.unscaled.len(), traits.len());
+        }
+
+        if state.parquet_base_path.is_some() {
+            let path = self.output_dir.join(format!("GenomicData_{}_pgs.parquet", self.job_id));
+
+            let scaled: HashMap<(&str, &str), f64> = pgs_data.scaled.iter()
+                .map(|r| ((r.sample_id.as_str(), r.label.as_str()), r.value))
+                .collect();
+
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("sample_id", DataType::Utf8, false),
+                Field::new("trait_label", DataType::Utf8, false),
+                Field::new("value", DataType::Float64, false),
+                Field::new("scaled_value", DataType::Float64, true),
+            ]));
+
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(
+                        pgs_data.unscaled.iter().map(|r| r.sample_id.as_str()).collect::<Vec<_>>(),
+                    )) as ArrayRef,
+                    Arc::new(StringArray::from(
+                        pgs_data.unscaled.iter().map(|r| r.label.as_str()).collect::<Vec<_>>(),
+                    )),
+                    Arc::new(Float64Array::from(
+                        pgs_data.unscaled.iter().map(|r| r.value).collect::<Vec<_>>(),
+                    )),
+                    Arc::new(Float64Array::from(
+                        pgs_data.unscaled.iter()
+                            .map(|r| scaled.get(&(r.sample_id.as_str(), r.label.as_str())).copied())
+                            .collect::<Vec<_>>(),
+                    )),
+                ],
+            )
+            .context("Failed to create PGS RecordBatch")?;
+
+            let file = std::fs::File::create(&path).context("Failed to create PGS Parquet file")?;
+            let props = WriterProperties::builder()
+                .set_compression(parquet::basic::Compression::SNAPPY)
+                .build();
+            let mut writer = ArrowWriter::try_new(file, schema, Some(props))
+                .context("Failed to create PGS Parquet writer")?;
+            writer.write(&batch).context("Failed to write PGS Parquet data")?;
+            writer.close().context("Failed to close PGS Parquet writer")?;
+
+            info!("  ✓ Parquet: PGS scores written to {:?}", path);
+        }
+
+        state.pgs_traits = traits;
+        state.pgs_normalization = Some(pgs_data.normalization);
+        Ok(())
+    }
+
+    /// Record genotyped vs imputed concordance for the output metadata
+    ///
+    /// Stored as JSON under the SQLite `metadata` key "concordance". Call
+    /// before `finalize_streaming_output()`.
+    pub fn set_concordance(&mut self, report: &ConcordanceReport) -> Result<()> {
+        let state = self.streaming_state.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Streaming not initialized. Call initialize_streaming_output() first."))?;
+
+        state.concordance = Some(serde_json::to_string(report).context("Failed to serialize concordance")?);
+        Ok(())
+    }
+
+    /// Write PGS interpretations to streaming output
+    ///
+    /// Fills the SQLite `pgs_interpretation` table, writes
+    /// `GenomicData_<job>_pgs_interpretation.parquet` and, for VCF output,
+    /// a tab-separated `GenomicData_<job>_pgs_interpretation.tsv` alongside
+    /// the VCF. Call after `append_pgs_scores()`.
+    ///
+    /// # Arguments
+    /// * `interpretations` - Per-trait interpretations from `pgs_interpretation::interpret`
+    pub async fn append_pgs_interpretation(&mut self, interpretations: &[PgsInterpretation]) -> Result<()> {
+        use std::io::Write;
+
+        let state = self.streaming_state.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Streaming not initialized. Call initialize_streaming_output() first."))?;
+
+        if let Some(conn) = state.sqlite_conn.as_mut() {
+            let tx = conn.transaction().context("Failed to start PGS interpretation transaction")?;
+            {
+                let mut stmt = tx
+                    .prepare(
+                        "INSERT OR REPLACE INTO pgs_interpretation (
+                            sample_id, trait_label, raw_score, reference_count, reference_mean, reference_sd,
+                            z_score, percentile, normal_percentile, r2, predicted_z, predicted_z_lower,
+                            predicted_z_upper, predicted_value, predicted_value_lower, predicted_value_upper,
+                            confidence_level, units
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+                    )
+                    .context("Failed to prepare pgs_interpretation insert")?;
+                for i in interpretations {
+                    stmt.execute(params![
+                        i.sample_id, i.trait_label, i.raw_score, i.reference_count as i64,
+                        i.reference_mean, i.reference_sd, i.z_score, i.percentile, i.normal_percentile,
+                        i.r2, i.predicted_z, i.predicted_z_lower, i.predicted_z_upper,
+                        i.predicted_value, i.predicted_value_lower, i.predicted_value_upper,
+                        i.confidence_level, i.units,
+                    ])
+                    .context("Failed to insert PGS interpretation")?;
+                }
+            }
+            tx.commit().context("Failed to commit PGS interpretation")?;
+            info!("  ✓ SQLite: {} PGS interpretations", interpretations.len());
+        }
+
+        if state.parquet_base_path.is_some() {
+            let path = self.output_dir.join(format!("GenomicData_{}_pgs_interpretation.parquet", self.job_id));
+
+            let f64_column = |f: fn(&PgsInterpretation) -> f64| -> ArrayRef {
+                Arc::new(Float64Array::from(interpretations.iter().map(f).collect::<Vec<_>>()))
+            };
+            let opt_column = |f: fn(&PgsInterpretation) -> Option<f64>| -> ArrayRef {
+                Arc::new(Float64Array::from(interpretations.iter().map(f).collect::<Vec<_>>()))
+            };
+
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("sample_id", DataType::Utf8, false),
+                Field::new("trait_label", DataType::Utf8, false),
+                Field::new("raw_score", DataType::Float64, false),
+                Field::new("reference_count", DataType::UInt64, false),
+                Field::new("reference_mean", DataType::Float64, false),
+                Field::new("reference_sd", DataType::Float64, false),
+                Field::new("z_score", DataType::Float64, false),
+                Field::new("percentile", DataType::Float64, false),
+                Field::new("normal_percentile", DataType::Float64, false),
+                Field::new("r2", DataType::Float64, true),
+                Field::new("predicted_z", DataType::Float64, true),
+                Field::new("predicted_z_lower", DataType::Float64, true),
+                Field::new("predicted_z_upper", DataType::Float64, true),
+                Field::new("predicted_value", DataType::Float64, true),
+                Field::new("predicted_value_lower", DataType::Float64, true),
+                Field::new("predicted_value_upper", DataType::Float64, true),
+                Field::new("confidence_level", DataType::Float64, false),
+                Field::new("units", DataType::Utf8, true),
+            ]));
+
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(
+                        interpretations.iter().map(|i| i.sample_id.as_str()).collect::<Vec<_>>(),
+                    )) as ArrayRef,
+                    Arc::new(StringArray::from(
+                        interpretations.iter().map(|i| i.trait_label.as_str()).collect::<Vec<_>>(),
+                    )),
+                    f64_column(|i| i.raw_score),
+                    Arc::new(UInt64Array::from(
+                        interpretations.iter().map(|i| i.reference_count as u64).collect::<Vec<_>>(),
+                    )),
+                    f64_column(|i| i.reference_mean),
+                    f64_column(|i| i.reference_sd),
+                    f64_column(|i| i.z_score),
+                    f64_column(|i| i.percentile),
+                    f64_column(|i| i.normal_percentile),
+                    opt_column(|i| i.r2),
+                    opt_column(|i| i.predicted_z),
+                    opt_column(|i| i.predicted_z_lower),
+                    opt_column(|i| i.predicted_z_upper),
+                    opt_column(|i| i.predicted_value),
+                    opt_column(|i| i.predicted_value_lower),
+                    opt_column(|i| i.predicted_value_upper),
+                    f64_column(|i| i.confidence_level),
+                    Arc::new(StringArray::from(
+                        interpretations.iter().map(|i| i.units.as_deref()).collect::<Vec<_>>(),
+                    )),
+                ],
+            )
+            .context("Failed to create PGS interpretation RecordBatch")?;
+
+            let file = std::fs::File::create(&path).context("Failed to create PGS interpretation Parquet file")?;
+            let props = WriterProperties::builder()
+                .set_compression(parquet::basic::Compression::SNAPPY)
+                .build();
+            let mut writer = ArrowWriter::try_new(file, schema, Some(props))
+                .context("Failed to create PGS interpretation Parquet writer")?;
+            writer.write(&batch).context("Failed to write PGS interpretation Parquet data")?;
+            writer.close().context("Failed to close PGS interpretation Parquet writer")?;
+
+            info!("  ✓ Parquet: PGS interpretations written to {:?}", path);
+        }
+
+        if state.formats.contains(&OutputFormat::Vcf) {
+            // VCF has no place for per-sample summaries, so write a sidecar table
+            let path = self.output_dir.join(format!("GenomicData_{}_pgs_interpretation.tsv", self.job_id));
+            let mut writer = std::io::BufWriter::new(
+                std::fs::File::create(&path).context("Failed to create PGS interpretation TSV")?,
+            );
+            let opt = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+            writeln!(
+                writer,
+                "sample_id\ttrait_label\traw_score\treference_count\treference_mean\treference_sd\t\
+                 z_score\tpercentile\tnormal_percentile\tr2\tpredicted_z\tpredicted_z_lower\t\
+                 predicted_z_upper\tpredicted_value\tpredicted_value_lower\tpredicted_value_upper\t\
+                 confidence_level\tunits"
+            )?;
+            for i in interpretations {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    i.sample_id, i.trait_label, i.raw_score, i.reference_count, i.reference_mean,
+                    i.reference_sd, i.z_score, i.percentile, i.normal_percentile, opt(i.r2),
+                    opt(i.predicted_z), opt(i.predicted_z_lower), opt(i.predicted_z_upper),
+                    opt(i.predicted_value), opt(i.predicted_value_lower), opt(i.predicted_value_upper),
+                    i.confidence_level, i.units.as_deref().unwrap_or(""),
+                )?;
+            }
+            writer.flush().context("Failed to write PGS interpretation TSV")?;
+
+            info!("  ✓ VCF: PGS interpretations written to {:?}", path);
+        }
+
+        Ok(())
+    }
+
     /// Finalize streaming output and return file paths
     ///
     /// This closes all file handles, writes metadata, creates indexes, and
@@ -1971,9 +2337,16 @@ impl OutputGenerator {
                         let imputed_snps_str = (state.total_variants - state.genotyped_variants).to_string();
                         let low_quality_snps_str = state.low_quality_variants.to_string();
                         let processing_date = chrono::Utc::now().to_rfc3339();
-                        let genome_file = "23andMe genome data".to_string();
+                        let genome_file = self.genome_file.clone();
                         let imputation_server = "Michigan Imputation Server 2".to_string();
                         let reference_panel = "openSNP (50 samples) + user (1 sample) = 51 total".to_string();
+                        let chromosome_encoding = CHROMOSOME_ENCODING.to_string();
+                        let pgs_traits = state.pgs_traits.join(",");
+                        let pgs_normalization = state.pgs_normalization
+                            .map(|n| n.as_str().to_string())
+                            .unwrap_or_default();
+                        let genome_build = self.genome_build.as_str().to_string();
+                        let concordance = state.concordance.clone().unwrap_or_default();
 
                         let metadata_items = vec![
                             ("job_id", &self.job_id),
@@ -1986,6 +2359,11 @@ impl OutputGenerator {
                             ("genotyped_snps", &genotyped_snps_str),
                             ("imputed_snps", &imputed_snps_str),
                             ("low_quality_snps", &low_quality_snps_str),
+                            ("chromosome_encoding", &chromosome_encoding),
+                            ("pgs_traits", &pgs_traits),
+                            ("pgs_normalization", &pgs_normalization),
+                            ("genome_build", &genome_build),
+                            ("concordance", &concordance),
                         ];
 
                         for (key, value) in metadata_items {
@@ -2048,8 +2426,8 @@ impl OutputGenerator {
                             // Per-chromosome VCF files are already finalized during append_chromosome()
                             info!("✓ VCF finalized: Keeping {} per-chromosome VCF files", state.vcf_files.len());
 
-                            for (idx, chr_file) in state.vcf_files.iter().enumerate() {
-                                info!("  chr{}: {:?}", idx + 1, chr_file.file_name().unwrap());
+                            for chr_file in &state.vcf_files {
+                                info!("  {:?}", chr_file.file_name().unwrap());
                             }
 
                             // All chromosome files will be included in ZIP archive automatically
@@ -2070,8 +2448,8 @@ impl OutputGenerator {
                         info!("✓ Parquet finalized: Keeping {} partitioned chromosome files for optimal query performance",
                               state.parquet_files.len());
 
-                        for (idx, chr_file) in state.parquet_files.iter().enumerate() {
-                            info!("  chr{}: {:?}", idx + 1, chr_file.file_name().unwrap());
+                        for chr_file in &state.parquet_files {
+                            info!("  {:?}", chr_file.file_name().unwrap());
                         }
 
                         // All chromosome files will be included in ZIP archive automatically
@@ -0,0 +1,319 @@
+// ==============================================================================
+// ancestrydna.rs - AncestryDNA Raw Data Parser
+// ==============================================================================
+// Description: Parser for AncestryDNA raw genome data files
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Format: Tab-delimited text with header comments and a column header row
+// Example:
+//   #AncestryDNA raw data download
+//   rsid    chromosome    position    allele1    allele2
+//   rs3131972    1    752721    A    G
+//   rs12562034    1    768448    0    0
+//   rs5939319    23    2700157    G    G
+// ==============================================================================
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::genome23andme::{Genome23ParseError, Genome23Record};
+use super::raw_genotype::normalize_chromosome;
+use super::report::ParseReport;
+
+/// Parser for AncestryDNA raw genome files
+///
+/// Produces the same `Genome23Record` output as `Genome23Parser` so records
+/// can be merged with imputed data without any provider-specific handling.
+#[derive(Debug, Clone)]
+pub struct AncestryDnaParser {
+    /// Chromosomes to include, using 23andMe naming (e.g., "1", "X", "MT")
+    /// If empty, includes all chromosomes
+    pub include_chromosomes: Vec<String>,
+}
+
+impl Default for AncestryDnaParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AncestryDnaParser {
+    /// Create a new parser that includes all chromosomes
+    pub fn new() -> Self {
+        Self {
+            include_chromosomes: Vec::new(),
+        }
+    }
+
+    /// Create a parser that only includes autosomal chromosomes (1-22)
+    pub fn autosomal_only() -> Self {
+        Self {
+            include_chromosomes: (1..=22).map(|n| n.to_string()).collect(),
+        }
+    }
+
+    /// Create a parser with specific chromosomes to include
+    pub fn with_chromosomes(chromosomes: Vec<String>) -> Self {
+        Self {
+            include_chromosomes: chromosomes,
+        }
+    }
+
+    /// Parse an AncestryDNA genome file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the AncestryDNA raw data file (AncestryDNA.txt)
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Genome23Record>)` - Successfully parsed records
+    /// * `Err(Genome23ParseError)` - Parse error
+    ///
+    /// # Format
+    /// The file is tab-delimited with 5 columns:
+    /// - rsid: SNP identifier
+    /// - chromosome: Numeric chromosome code (1-22, 23=X, 24=Y, 25=PAR, 26=MT)
+    /// - position: Base pair position (GRCh37)
+    /// - allele1, allele2: Single-base alleles, "0" for no-call
+    ///
+    /// Lines starting with '#' and the column header row are skipped.
+    /// Chromosome codes are normalized to 23andMe names (PAR sites map to "X")
+    /// and no-calls are reported as "--".
+    pub fn parse(&self, path: impl AsRef<Path>) -> Result<Vec<Genome23Record>, Genome23ParseError> {
+        Ok(self.parse_with_report(path)?.0)
+    }
+
+    /// Parse an AncestryDNA genome file, also returning a parse report
+    ///
+    /// See `parse` for the format.
+    pub fn parse_with_report(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        let file = File::open(path.as_ref())?;
+        let reader = BufReader::new(file);
+
+        let mut records = Vec::new();
+        let mut report = ParseReport::for_path(path.as_ref());
+        let mut line_number = 0;
+
+        for line_result in reader.lines() {
+            line_number += 1;
+            let line = line_result?;
+            let trimmed = line.trim();
+
+            // Skip comment lines (start with '#') and the column header row
+            if trimmed.starts_with('#') || trimmed.is_empty() || is_header_row(trimmed) {
+                continue;
+            }
+
+            report.records_read += 1;
+            let record = self.parse_line(&line, line_number)?;
+
+            // Filter by chromosome if specified
+            if !self.include_chromosomes.is_empty()
+                && !self.include_chromosomes.contains(&record.chromosome)
+            {
+                report.filtered_by_chromosome += 1;
+                continue;
+            }
+
+            if record.genotype == "--" {
+                report.no_calls += 1;
+            }
+            records.push(record);
+        }
+
+        if records.is_empty() {
+            return Err(Genome23ParseError::EmptyFile);
+        }
+
+        report.records_accepted = records.len();
+        Ok((records, report))
+    }
+
+    /// Parse a single line from the AncestryDNA file
+    fn parse_line(&self, line: &str, line_number: usize) -> Result<Genome23Record, Genome23ParseError> {
+        let fields: Vec<&str> = line.split('\t').collect();
+
+        if fields.len() != 5 {
+            return Err(Genome23ParseError::InvalidFormat {
+                line: line_number,
+                details: format!("Expected 5 tab-delimited fields, found {}", fields.len()),
+            });
+        }
+
+        let rsid = fields[0].trim().to_string();
+        let chromosome_code = fields[1].trim();
+        let position_str = fields[2].trim();
+        let allele1 = fields[3].trim();
+        let allele2 = fields[4].trim();
+
+        let chromosome = normalize_chromosome(chromosome_code).ok_or_else(|| {
+            Genome23ParseError::InvalidFormat {
+                line: line_number,
+                details: format!("Unknown chromosome code: {}", chromosome_code),
+            }
+        })?;
+
+        // Parse position
+        let position = position_str.parse::<u64>().map_err(|_| {
+            Genome23ParseError::InvalidPosition {
+                line: line_number,
+                value: position_str.to_string(),
+            }
+        })?;
+
+        // AncestryDNA reports no-calls as "0" in either allele column
+        let genotype = if allele1 == "0" || allele2 == "0" {
+            "--".to_string()
+        } else {
+            format!("{}{}", allele1, allele2)
+        };
+
+        Ok(Genome23Record {
+            rsid,
+            chromosome,
+            position,
+            genotype,
+        })
+    }
+}
+
+/// Check whether a line is the AncestryDNA column header row
+fn is_header_row(line: &str) -> bool {
+    line.split('\t')
+        .next()
+        .map(|field| field.trim().eq_ignore_ascii_case("rsid"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// Create a temporary test file with sample AncestryDNA data
+    fn create_test_file(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_valid_file() {
+        let contents = "\
+#AncestryDNA raw data download
+#array version: V2.0
+rsid\tchromosome\tposition\tallele1\tallele2
+rs3131972\t1\t752721\tA\tG
+rs12562034\t1\t768448\t0\t0
+rs12345678\t2\t100000\tT\tT
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::new();
+
+        let records = parser.parse(file.path()).unwrap();
+
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].rsid, "rs3131972");
+        assert_eq!(records[0].chromosome, "1");
+        assert_eq!(records[0].position, 752721);
+        assert_eq!(records[0].genotype, "AG");
+
+        // No-call alleles become "--"
+        assert_eq!(records[1].genotype, "--");
+
+        assert_eq!(records[2].chromosome, "2");
+        assert_eq!(records[2].genotype, "TT");
+    }
+
+    #[test]
+    fn test_sex_chromosome_codes() {
+        let contents = "\
+rsid\tchromosome\tposition\tallele1\tallele2
+rs1\t23\t2700157\tG\tG
+rs2\t24\t2655180\tC\tC
+rs3\t25\t2699555\tA\tG
+rs4\t26\t16089\tT\tT
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::new();
+
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].chromosome, "X");
+        assert_eq!(records[1].chromosome, "Y");
+        assert_eq!(records[2].chromosome, "X"); // PAR reported on X
+        assert_eq!(records[3].chromosome, "MT");
+    }
+
+    #[test]
+    fn test_autosomal_only() {
+        let contents = "\
+rsid\tchromosome\tposition\tallele1\tallele2
+rs1\t1\t100\tA\tA
+rs2\t23\t200\tG\tG
+rs3\t22\t300\tC\tT
+rs4\t26\t400\tT\tT
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::autosomal_only();
+
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].chromosome, "1");
+        assert_eq!(records[1].chromosome, "22");
+    }
+
+    #[test]
+    fn test_invalid_chromosome_code() {
+        let contents = "\
+rsid\tchromosome\tposition\tallele1\tallele2
+rs1\t27\t100\tA\tA
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::new();
+
+        match parser.parse(file.path()).unwrap_err() {
+            Genome23ParseError::InvalidFormat { line, .. } => assert_eq!(line, 2),
+            _ => panic!("Expected InvalidFormat error"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_format_too_few_fields() {
+        let contents = "\
+rsid\tchromosome\tposition\tallele1\tallele2
+rs1\t1\t100\tAA
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::new();
+
+        match parser.parse(file.path()).unwrap_err() {
+            Genome23ParseError::InvalidFormat { line, .. } => assert_eq!(line, 2),
+            _ => panic!("Expected InvalidFormat error"),
+        }
+    }
+
+    #[test]
+    fn test_header_only_file() {
+        let contents = "\
+#AncestryDNA raw data download
+rsid\tchromosome\tposition\tallele1\tallele2
+";
+        let file = create_test_file(contents);
+        let parser = AncestryDnaParser::new();
+
+        match parser.parse(file.path()).unwrap_err() {
+            Genome23ParseError::EmptyFile => {}
+            _ => panic!("Expected EmptyFile error"),
+        }
+    }
+}
@@ -15,10 +15,20 @@
 //   rs9283150    1    565508    AA
 // ==============================================================================
 
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Lines};
 use std::path::Path;
 use thiserror::Error;
+use tracing::debug;
+
+use super::report::{IssueCategory, ParseReport};
+
+/// Error budget used by `Genome23Parser::lenient` (matches the VCF parser default)
+pub const DEFAULT_LENIENT_MAX_ERRORS: usize = 1000;
+
+/// Records grouped by chromosome name, then indexed by position
+pub type GenomeByChromosome = HashMap<String, HashMap<u64, Genome23Record>>;
 
 /// 23andMe genome record
 #[derive(Debug, Clone, PartialEq)]
@@ -29,16 +39,37 @@ pub struct Genome23Record {
     pub chromosome: String,
     /// Base pair position (GRCh37/hg19)
     pub position: u64,
-    /// Two-letter genotype (e.g., "TT", "AG", "--" for no-call)
+    /// Two-letter genotype (e.g., "TT", "AG", "DI" for indels, "--" for no-call)
     pub genotype: String,
 }
 
+/// How to resolve records that share an rsID or a chromosome position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Keep the first record in file order
+    KeepFirst,
+    /// Keep the last record in file order
+    KeepLast,
+    /// Keep the first called genotype, falling back to the first no-call
+    #[default]
+    PreferCalled,
+    /// Drop every record involved in a duplicate
+    DropAll,
+}
+
 /// Parser for 23andMe raw genome files
 #[derive(Debug, Clone)]
 pub struct Genome23Parser {
     /// Chromosomes to include (e.g., vec!["1", "2", ..., "22"])
     /// If empty, includes all chromosomes
     pub include_chromosomes: Vec<String>,
+
+    /// Maximum number of malformed lines to skip before failing
+    /// (0 = strict, the first malformed line fails the parse)
+    pub max_errors: usize,
+
+    /// Resolution for duplicate rsIDs and positions
+    pub duplicate_policy: DuplicatePolicy,
 }
 
 /// Errors that can occur during 23andMe file parsing
@@ -55,6 +86,26 @@ pub enum Genome23ParseError {
 
     #[error("File is empty or contains only comments")]
     EmptyFile,
+
+    #[error("Unsupported raw genotype format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Too many malformed lines ({count} > {max})")]
+    TooManyErrors { count: usize, max: usize },
+}
+
+impl Genome23ParseError {
+    /// Parse report category for a rejected line
+    pub fn category(&self) -> IssueCategory {
+        match self {
+            Genome23ParseError::InvalidPosition { .. } => IssueCategory::InvalidPosition,
+            Genome23ParseError::InvalidFormat { .. }
+            | Genome23ParseError::EmptyFile
+            | Genome23ParseError::UnsupportedFormat(_)
+            | Genome23ParseError::TooManyErrors { .. } => IssueCategory::InvalidFormat,
+            Genome23ParseError::IoError(_) => IssueCategory::ReadError,
+        }
+    }
 }
 
 impl Default for Genome23Parser {
@@ -66,25 +117,43 @@ impl Default for Genome23Parser {
 impl Genome23Parser {
     /// Create a new parser that includes all chromosomes
     pub fn new() -> Self {
-        Self {
-            include_chromosomes: Vec::new(),
-        }
+        Self::with_chromosomes(Vec::new())
     }
 
     /// Create a parser that only includes autosomal chromosomes (1-22)
     pub fn autosomal_only() -> Self {
-        Self {
-            include_chromosomes: (1..=22).map(|n| n.to_string()).collect(),
-        }
+        Self::with_chromosomes((1..=22).map(|n| n.to_string()).collect())
     }
 
     /// Create a parser with specific chromosomes to include
     pub fn with_chromosomes(chromosomes: Vec<String>) -> Self {
         Self {
             include_chromosomes: chromosomes,
+            max_errors: 0,
+            duplicate_policy: DuplicatePolicy::default(),
         }
     }
 
+    /// Set maximum number of malformed lines to skip before failing
+    pub fn with_max_errors(mut self, max: usize) -> Self {
+        self.max_errors = max;
+        self
+    }
+
+    /// Skip malformed lines (up to `DEFAULT_LENIENT_MAX_ERRORS`) instead of failing
+    ///
+    /// Skipped lines are recorded in the parse report. Use this for browser
+    /// downloads, which are occasionally truncated mid-line.
+    pub fn lenient(self) -> Self {
+        self.with_max_errors(DEFAULT_LENIENT_MAX_ERRORS)
+    }
+
+    /// Set the resolution for duplicate rsIDs and positions
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
     /// Parse a 23andMe genome file
     ///
     /// # Arguments
@@ -101,40 +170,91 @@ impl Genome23Parser {
     /// - position: Base pair position (GRCh37)
     /// - genotype: Two-letter genotype or "--" for no-call
     ///
-    /// Lines starting with '#' are treated as comments and skipped.
+    /// Lines starting with '#' are treated as comments and skipped. Malformed
+    /// lines fail the parse unless an error budget is set (`with_max_errors`,
+    /// `lenient`). Records sharing an rsID or position are resolved by
+    /// `duplicate_policy`.
     pub fn parse(&self, path: impl AsRef<Path>) -> Result<Vec<Genome23Record>, Genome23ParseError> {
-        let file = File::open(path.as_ref())?;
-        let reader = BufReader::new(file);
+        Ok(self.parse_with_report(path)?.0)
+    }
 
-        let mut records = Vec::new();
-        let mut line_number = 0;
+    /// Parse a 23andMe genome file, also returning a parse report
+    ///
+    /// See `parse` for the format. The report counts records read, filtered
+    /// by chromosome and no-calls, and lists skipped lines and duplicates.
+    pub fn parse_with_report(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        let mut stream = self.records(path)?;
+        let mut resolver = DuplicateResolver::new(self.duplicate_policy);
+
+        while let Some(record) = stream.next() {
+            resolver.insert(record?, stream.line_number, &mut stream.report);
+        }
 
-        for line_result in reader.lines() {
-            line_number += 1;
-            let line = line_result?;
+        let records: Vec<Genome23Record> = resolver.into_records().collect();
+        if records.is_empty() {
+            return Err(Genome23ParseError::EmptyFile);
+        }
 
-            // Skip comment lines (start with '#')
-            if line.trim().starts_with('#') || line.trim().is_empty() {
-                continue;
-            }
+        let mut report = stream.into_report();
+        report.records_accepted = records.len();
+        report.no_calls = records.iter().filter(|r| r.genotype == "--").count();
 
-            let record = self.parse_line(&line, line_number)?;
+        Ok((records, report))
+    }
 
-            // Filter by chromosome if specified
-            if !self.include_chromosomes.is_empty()
-                && !self.include_chromosomes.contains(&record.chromosome)
-            {
-                continue;
-            }
+    /// Stream records from a 23andMe genome file without loading it into memory
+    ///
+    /// Comment lines are skipped and the chromosome filter is applied while
+    /// reading. Malformed lines within the error budget are skipped. Unlike
+    /// `parse`, an empty file yields an empty iterator and duplicates are
+    /// returned as-is.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the 23andMe raw data file (genome_*.txt)
+    ///
+    /// # Returns
+    /// * `Ok(Genome23Records)` - Iterator over parsed records
+    /// * `Err(Genome23ParseError)` - File could not be opened
+    pub fn records(&self, path: impl AsRef<Path>) -> Result<Genome23Records<'_>, Genome23ParseError> {
+        let file = File::open(path.as_ref())?;
 
-            records.push(record);
-        }
+        Ok(Genome23Records {
+            parser: self,
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+            error_count: 0,
+            report: ParseReport::for_path(path.as_ref()),
+        })
+    }
 
-        if records.is_empty() {
-            return Err(Genome23ParseError::EmptyFile);
-        }
+    /// Parse a 23andMe genome file in a single pass, grouped by chromosome
+    ///
+    /// # Arguments
+    /// * `path` - Path to the 23andMe raw data file (genome_*.txt)
+    ///
+    /// # Returns
+    /// * `Ok(GenomeByChromosome)` - Chromosome name -> (position -> record)
+    /// * `Err(Genome23ParseError)` - Parse error
+    ///
+    /// Duplicate rsIDs and positions are resolved by `duplicate_policy`.
+    pub fn parse_grouped_by_chromosome(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<GenomeByChromosome, Genome23ParseError> {
+        Ok(self.parse_grouped_with_report(path)?.0)
+    }
 
-        Ok(records)
+    /// Parse a 23andMe genome file grouped by chromosome, also returning a
+    /// parse report (see `parse_grouped_by_chromosome`)
+    pub fn parse_grouped_with_report(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(GenomeByChromosome, ParseReport), Genome23ParseError> {
+        let (records, report) = self.parse_with_report(path)?;
+        Ok((group_by_chromosome(records.into_iter().map(Ok))?, report))
     }
 
     /// Parse a single line from the 23andMe file
@@ -170,6 +290,200 @@ impl Genome23Parser {
     }
 }
 
+/// Streaming iterator over 23andMe records (see `Genome23Parser::records`)
+pub struct Genome23Records<'a> {
+    parser: &'a Genome23Parser,
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+    error_count: usize,
+    report: ParseReport,
+}
+
+impl Genome23Records<'_> {
+    /// Parse report for the records read so far
+    pub fn report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Consume the iterator, returning its parse report
+    pub fn into_report(self) -> ParseReport {
+        self.report
+    }
+}
+
+impl Iterator for Genome23Records<'_> {
+    type Item = Result<Genome23Record, Genome23ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line_result in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = match line_result {
+                Ok(line) => line,
+                Err(e) => {
+                    let e = Genome23ParseError::from(e);
+                    self.report.record_issue(self.line_number, e.category(), e.to_string());
+                    return Some(Err(e));
+                }
+            };
+
+            // Skip comment lines (start with '#')
+            if line.trim().starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+
+            self.report.records_read += 1;
+
+            let record = match self.parser.parse_line(&line, self.line_number) {
+                Ok(record) => record,
+                Err(e) => {
+                    self.report.record_issue(self.line_number, e.category(), e.to_string());
+                    self.error_count += 1;
+
+                    if self.error_count > self.parser.max_errors {
+                        // Strict parsers report the offending line itself
+                        if self.parser.max_errors == 0 {
+                            return Some(Err(e));
+                        }
+                        return Some(Err(Genome23ParseError::TooManyErrors {
+                            count: self.error_count,
+                            max: self.parser.max_errors,
+                        }));
+                    }
+
+                    debug!("{}: skipping line {}: {}", self.report.source, self.line_number, e);
+                    continue;
+                }
+            };
+
+            // Filter by chromosome if specified
+            if !self.parser.include_chromosomes.is_empty()
+                && !self.parser.include_chromosomes.contains(&record.chromosome)
+            {
+                self.report.filtered_by_chromosome += 1;
+                continue;
+            }
+
+            self.report.records_accepted += 1;
+            if record.genotype == "--" {
+                self.report.no_calls += 1;
+            }
+
+            return Some(Ok(record));
+        }
+
+        None
+    }
+}
+
+/// Applies a `DuplicatePolicy` to records in file order
+///
+/// Records are matched on (chromosome, position) first, then on rsID.
+/// Each duplicate is recorded in the parse report.
+struct DuplicateResolver {
+    policy: DuplicatePolicy,
+    /// Kept records in file order (None = dropped by `DropAll`)
+    records: Vec<Option<Genome23Record>>,
+    by_position: HashMap<(String, u64), usize>,
+    by_rsid: HashMap<String, usize>,
+}
+
+impl DuplicateResolver {
+    fn new(policy: DuplicatePolicy) -> Self {
+        Self {
+            policy,
+            records: Vec::new(),
+            by_position: HashMap::new(),
+            by_rsid: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, record: Genome23Record, line: usize, report: &mut ParseReport) {
+        let existing = self
+            .by_position
+            .get(&(record.chromosome.clone(), record.position))
+            .or_else(|| self.by_rsid.get(&record.rsid))
+            .copied();
+
+        let Some(index) = existing else {
+            self.index(&record, self.records.len());
+            self.records.push(Some(record));
+            return;
+        };
+
+        let reason = match &self.records[index] {
+            Some(kept) => format!(
+                "{} at {}:{} duplicates {} at {}:{}",
+                record.rsid, record.chromosome, record.position,
+                kept.rsid, kept.chromosome, kept.position
+            ),
+            None => format!("{} duplicates a dropped record", record.rsid),
+        };
+        report.record_issue(line, IssueCategory::Duplicate, reason);
+
+        let replace = match (&self.records[index], self.policy) {
+            // The group was already dropped
+            (None, _) => false,
+            (Some(_), DuplicatePolicy::KeepFirst) => false,
+            (Some(_), DuplicatePolicy::KeepLast) => true,
+            (Some(kept), DuplicatePolicy::PreferCalled) => {
+                kept.genotype == "--" && record.genotype != "--"
+            }
+            (Some(_), DuplicatePolicy::DropAll) => {
+                self.records[index] = None;
+                false
+            }
+        };
+
+        if replace {
+            // Only drop keys the replaced record owns; its rsID may map to
+            // another kept record that it replaced by position
+            if let Some(old) = self.records[index].take() {
+                let position = (old.chromosome, old.position);
+                if self.by_position.get(&position) == Some(&index) {
+                    self.by_position.remove(&position);
+                }
+                if self.by_rsid.get(&old.rsid) == Some(&index) {
+                    self.by_rsid.remove(&old.rsid);
+                }
+            }
+            self.index(&record, index);
+            self.records[index] = Some(record);
+        }
+    }
+
+    fn index(&mut self, record: &Genome23Record, index: usize) {
+        self.by_position
+            .insert((record.chromosome.clone(), record.position), index);
+        if !record.rsid.is_empty() && record.rsid != "." {
+            self.by_rsid.entry(record.rsid.clone()).or_insert(index);
+        }
+    }
+
+    fn into_records(self) -> impl Iterator<Item = Genome23Record> {
+        self.records.into_iter().flatten()
+    }
+}
+
+/// Group a stream of records by chromosome, indexed by position
+///
+/// If a position appears more than once on a chromosome, the last record wins.
+pub fn group_by_chromosome<I>(records: I) -> Result<GenomeByChromosome, Genome23ParseError>
+where
+    I: IntoIterator<Item = Result<Genome23Record, Genome23ParseError>>,
+{
+    let mut grouped: GenomeByChromosome = HashMap::new();
+
+    for record in records {
+        let record = record?;
+        grouped
+            .entry(record.chromosome.clone())
+            .or_default()
+            .insert(record.position, record);
+    }
+
+    Ok(grouped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -352,4 +666,147 @@ rs6\tMT\t600\tAA
         assert_eq!(records[4].chromosome, "22");
         assert_eq!(records[5].chromosome, "MT");
     }
+
+    #[test]
+    fn test_records_iterator() {
+        let contents = "\
+# rsid\tchromosome\tposition\tgenotype
+rs1\t1\t100\tAA
+rs2\tX\t200\tAG
+rs3\t2\t300\tGG
+rs4\t2\tBAD\tTT
+";
+        let file = create_test_file(contents);
+        let parser = Genome23Parser::autosomal_only();
+
+        let mut records = parser.records(file.path()).unwrap();
+        assert_eq!(records.next().unwrap().unwrap().rsid, "rs1");
+        // chrX is filtered out while streaming
+        assert_eq!(records.next().unwrap().unwrap().rsid, "rs3");
+        match records.next().unwrap() {
+            Err(Genome23ParseError::InvalidPosition { line, .. }) => assert_eq!(line, 5),
+            _ => panic!("Expected InvalidPosition error"),
+        }
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_grouped_by_chromosome() {
+        let contents = "\
+# rsid\tchromosome\tposition\tgenotype
+rs1\t1\t100\tAA
+rs2\t1\t200\tAG
+rs3\t2\t300\tGG
+rs4\tMT\t400\tTT
+";
+        let file = create_test_file(contents);
+        let parser = Genome23Parser::new();
+
+        let grouped = parser.parse_grouped_by_chromosome(file.path()).unwrap();
+        assert_eq!(grouped.len(), 3);
+        assert_eq!(grouped["1"].len(), 2);
+        assert_eq!(grouped["1"][&200].rsid, "rs2");
+        assert_eq!(grouped["2"][&300].genotype, "GG");
+        assert_eq!(grouped["MT"][&400].rsid, "rs4");
+    }
+
+    #[test]
+    fn test_lenient_skips_truncated_line() {
+        let contents = "\
+# rsid\tchromosome\tposition\tgenotype
+rs1\t1\t100\tAA
+rs2\t1\tBAD\tAG
+rs3\t2\t300\tGG
+rs4\t2\t40";
+        let file = create_test_file(contents);
+        let parser = Genome23Parser::new().lenient();
+
+        let (records, report) = parser.parse_with_report(file.path()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(report.records_read, 4);
+        assert_eq!(report.records_accepted, 2);
+        assert_eq!(report.issue_count(), 2);
+        assert_eq!(report.issues[0].line, 3);
+        assert_eq!(report.issues[0].category, IssueCategory::InvalidPosition);
+        assert_eq!(report.issues[1].line, 5);
+        assert_eq!(report.issues[1].category, IssueCategory::InvalidFormat);
+    }
+
+    #[test]
+    fn test_error_budget_exceeded() {
+        let contents = "\
+rs1\t1\t100\tAA
+rs2\t1\tBAD\tAG
+rs3\t1\tBAD\tGG
+";
+        let file = create_test_file(contents);
+        let parser = Genome23Parser::new().with_max_errors(1);
+
+        match parser.parse(file.path()).unwrap_err() {
+            Genome23ParseError::TooManyErrors { count, max } => {
+                assert_eq!(count, 2);
+                assert_eq!(max, 1);
+            }
+            e => panic!("Expected TooManyErrors, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let contents = "\
+rs1\t1\t100\t--
+rs1b\t1\t100\tAG
+rs2\t1\t200\tCC
+rs2\t1\t250\tCT
+rs3\t1\t300\tTT
+";
+        let file = create_test_file(contents);
+        let resolve = |policy| {
+            Genome23Parser::new()
+                .with_duplicate_policy(policy)
+                .parse_with_report(file.path())
+                .unwrap()
+        };
+
+        let (records, report) = resolve(DuplicatePolicy::PreferCalled);
+        let genotypes: Vec<&str> = records.iter().map(|r| r.genotype.as_str()).collect();
+        assert_eq!(genotypes, ["AG", "CC", "TT"]);
+        assert_eq!(report.issue_counts[&IssueCategory::Duplicate], 2);
+        assert_eq!(report.no_calls, 0);
+
+        let (records, _) = resolve(DuplicatePolicy::KeepFirst);
+        assert_eq!(records[0].genotype, "--");
+        assert_eq!(records[1].position, 200);
+
+        let (records, _) = resolve(DuplicatePolicy::KeepLast);
+        assert_eq!(records[0].rsid, "rs1b");
+        assert_eq!(records[1].position, 250);
+
+        let (records, report) = resolve(DuplicatePolicy::DropAll);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rsid, "rs3");
+        assert_eq!(report.records_accepted, 1);
+    }
+
+    #[test]
+    fn test_keep_last_rsid_owned_by_other_record() {
+        // rs2 at 1:100 replaces rs1 by position but rs2 stays keyed to the
+        // record at 1:200; replacing 1:100 again must not forget that key
+        let contents = "\
+rs1\t1\t100\tAA
+rs2\t1\t200\tCC
+rs2\t1\t100\tAG
+rs4\t1\t100\tGG
+rs2\t1\t300\tCT
+";
+        let file = create_test_file(contents);
+        let (records, report) = Genome23Parser::new()
+            .with_duplicate_policy(DuplicatePolicy::KeepLast)
+            .parse_with_report(file.path())
+            .unwrap();
+
+        let kept: Vec<(&str, u64)> = records.iter().map(|r| (r.rsid.as_str(), r.position)).collect();
+        assert_eq!(kept, [("rs4", 100), ("rs2", 300)]);
+        assert_eq!(report.issue_counts[&IssueCategory::Duplicate], 3);
+    }
 }
@@ -0,0 +1,293 @@
+// ==============================================================================
+// genotype_csv.rs - MyHeritage / FamilyTreeDNA Raw Data Parser
+// ==============================================================================
+// Description: Parser for quoted CSV raw genome data (MyHeritage, FTDNA)
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Format: Comma-separated, quoted fields, optional '#' comment block
+// Example:
+//   # MyHeritage DNA raw data.
+//   RSID,CHROMOSOME,POSITION,RESULT
+//   "rs4477212","1","82154","AA"
+//   "rs3131972","1","752721","AG"
+//   "rs12562034","1","768448","--"
+// ==============================================================================
+
+use std::path::Path;
+
+use super::genome23andme::{Genome23ParseError, Genome23Record};
+use super::raw_genotype::{normalize_chromosome, GenotypeProvider};
+use super::report::ParseReport;
+
+/// Expected CSV column headers
+const EXPECTED_HEADERS: [&str; 4] = ["RSID", "CHROMOSOME", "POSITION", "RESULT"];
+
+/// Check whether a raw line is the MyHeritage/FTDNA CSV column header
+/// (fields may be quoted)
+pub fn is_genotype_csv_header(line: &str) -> bool {
+    let normalized: String = line.trim().chars().filter(|c| *c != '"').collect();
+    normalized.eq_ignore_ascii_case(&EXPECTED_HEADERS.join(","))
+}
+
+/// Parser for MyHeritage and FamilyTreeDNA raw genome CSV files
+///
+/// Produces the same `Genome23Record` output as `Genome23Parser`, with
+/// chromosome names normalized to 23andMe naming and no-calls as "--".
+#[derive(Debug, Clone)]
+pub struct GenotypeCsvParser {
+    /// Provider the file came from (MyHeritage or FamilyTreeDNA)
+    pub provider: GenotypeProvider,
+    /// Chromosomes to include (e.g., vec!["1", "2", ..., "22"])
+    /// If empty, includes all chromosomes
+    pub include_chromosomes: Vec<String>,
+}
+
+impl GenotypeCsvParser {
+    /// Create a parser for MyHeritage raw data that includes all chromosomes
+    pub fn myheritage() -> Self {
+        Self {
+            provider: GenotypeProvider::MyHeritage,
+            include_chromosomes: Vec::new(),
+        }
+    }
+
+    /// Create a parser for FamilyTreeDNA raw data that includes all chromosomes
+    pub fn ftdna() -> Self {
+        Self {
+            provider: GenotypeProvider::FamilyTreeDna,
+            include_chromosomes: Vec::new(),
+        }
+    }
+
+    /// Restrict parsing to specific chromosomes
+    pub fn with_chromosomes(mut self, chromosomes: Vec<String>) -> Self {
+        self.include_chromosomes = chromosomes;
+        self
+    }
+
+    /// Parse a MyHeritage/FTDNA genome CSV file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the raw data CSV file
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Genome23Record>)` - Successfully parsed records
+    /// * `Err(Genome23ParseError)` - Parse error
+    ///
+    /// # Format
+    /// The file is comma-separated with a `RSID,CHROMOSOME,POSITION,RESULT`
+    /// header row. Fields may be quoted. Lines starting with '#' are skipped.
+    pub fn parse(&self, path: impl AsRef<Path>) -> Result<Vec<Genome23Record>, Genome23ParseError> {
+        Ok(self.parse_with_report(path)?.0)
+    }
+
+    /// Parse a MyHeritage/FTDNA genome CSV file, also returning a parse report
+    ///
+    /// See `parse` for the format.
+    pub fn parse_with_report(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_path(path.as_ref())
+            .map_err(csv_error)?;
+
+        // Verify header row
+        let headers = reader.headers().map_err(csv_error)?.clone();
+        let header_line = headers.position().map(|p| p.line() as usize).unwrap_or(1);
+        let matches_expected = headers.len() == EXPECTED_HEADERS.len()
+            && headers
+                .iter()
+                .zip(EXPECTED_HEADERS.iter())
+                .all(|(actual, expected)| actual.eq_ignore_ascii_case(expected));
+
+        if !matches_expected {
+            return Err(Genome23ParseError::InvalidFormat {
+                line: header_line,
+                details: format!(
+                    "Expected header {}, found {}",
+                    EXPECTED_HEADERS.join(","),
+                    headers.iter().collect::<Vec<_>>().join(",")
+                ),
+            });
+        }
+
+        let mut records = Vec::new();
+        let mut report = ParseReport::for_path(path.as_ref());
+
+        for result in reader.records() {
+            let row = result.map_err(csv_error)?;
+            let line_number = row.position().map(|p| p.line() as usize).unwrap_or(0);
+
+            report.records_read += 1;
+            let record = self.parse_row(&row, line_number)?;
+
+            // Filter by chromosome if specified
+            if !self.include_chromosomes.is_empty()
+                && !self.include_chromosomes.contains(&record.chromosome)
+            {
+                report.filtered_by_chromosome += 1;
+                continue;
+            }
+
+            if record.genotype == "--" {
+                report.no_calls += 1;
+            }
+            records.push(record);
+        }
+
+        if records.is_empty() {
+            return Err(Genome23ParseError::EmptyFile);
+        }
+
+        report.records_accepted = records.len();
+        Ok((records, report))
+    }
+
+    /// Parse a single CSV row
+    fn parse_row(
+        &self,
+        row: &csv::StringRecord,
+        line_number: usize,
+    ) -> Result<Genome23Record, Genome23ParseError> {
+        let rsid = row[0].to_string();
+        let chromosome_code = &row[1];
+        let position_str = &row[2];
+        let result = &row[3];
+
+        let chromosome = normalize_chromosome(chromosome_code).ok_or_else(|| {
+            Genome23ParseError::InvalidFormat {
+                line: line_number,
+                details: format!("Unknown chromosome: {}", chromosome_code),
+            }
+        })?;
+
+        // Parse position
+        let position = position_str.parse::<u64>().map_err(|_| {
+            Genome23ParseError::InvalidPosition {
+                line: line_number,
+                value: position_str.to_string(),
+            }
+        })?;
+
+        // No-calls are written as "--" (occasionally blank or "00")
+        let genotype = if result.is_empty() || result == "00" || result.contains('-') {
+            "--".to_string()
+        } else {
+            result.to_uppercase()
+        };
+
+        Ok(Genome23Record {
+            rsid,
+            chromosome,
+            position,
+            genotype,
+        })
+    }
+}
+
+/// Map a csv error to a parse error, preserving the line number when known
+fn csv_error(err: csv::Error) -> Genome23ParseError {
+    let line = err.position().map(|p| p.line() as usize).unwrap_or(0);
+    match err.into_kind() {
+        csv::ErrorKind::Io(io_err) => Genome23ParseError::IoError(io_err),
+        kind => Genome23ParseError::InvalidFormat {
+            line,
+            details: format!("{:?}", kind),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_file(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_myheritage() {
+        let contents = "\
+# MyHeritage DNA raw data.
+# This file was generated on 2024-01-01
+RSID,CHROMOSOME,POSITION,RESULT
+\"rs4477212\",\"1\",\"82154\",\"AA\"
+\"rs3131972\",\"1\",\"752721\",\"AG\"
+\"rs12562034\",\"1\",\"768448\",\"--\"
+\"rs5939319\",\"X\",\"2700157\",\"GG\"
+";
+        let file = create_test_file(contents);
+        let records = GenotypeCsvParser::myheritage().parse(file.path()).unwrap();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].rsid, "rs4477212");
+        assert_eq!(records[0].chromosome, "1");
+        assert_eq!(records[0].position, 82154);
+        assert_eq!(records[0].genotype, "AA");
+        assert_eq!(records[2].genotype, "--");
+        assert_eq!(records[3].chromosome, "X");
+    }
+
+    #[test]
+    fn test_parse_ftdna_autosomal() {
+        let contents = "\
+RSID,CHROMOSOME,POSITION,RESULT
+\"rs1\",\"1\",\"100\",\"CT\"
+\"rs2\",\"XY\",\"200\",\"AG\"
+\"rs3\",\"22\",\"300\",\"GG\"
+";
+        let file = create_test_file(contents);
+        let parser = GenotypeCsvParser::ftdna()
+            .with_chromosomes((1..=22).map(|n| n.to_string()).collect());
+
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].genotype, "CT");
+        assert_eq!(records[1].chromosome, "22");
+    }
+
+    #[test]
+    fn test_is_genotype_csv_header() {
+        assert!(is_genotype_csv_header("RSID,CHROMOSOME,POSITION,RESULT"));
+        assert!(is_genotype_csv_header("\"RSID\",\"CHROMOSOME\",\"POSITION\",\"RESULT\"\r"));
+        assert!(is_genotype_csv_header("rsid,chromosome,position,result"));
+        assert!(!is_genotype_csv_header("rsid\tchromosome\tposition\tgenotype"));
+        assert!(!is_genotype_csv_header("RSID,CHROMOSOME,POSITION"));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let contents = "rsid,chr,pos\n\"rs1\",\"1\",\"100\"\n";
+        let file = create_test_file(contents);
+
+        match GenotypeCsvParser::ftdna().parse(file.path()).unwrap_err() {
+            Genome23ParseError::InvalidFormat { line, .. } => assert_eq!(line, 1),
+            _ => panic!("Expected InvalidFormat error"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_position() {
+        let contents = "RSID,CHROMOSOME,POSITION,RESULT\n\"rs1\",\"1\",\"abc\",\"AA\"\n";
+        let file = create_test_file(contents);
+
+        match GenotypeCsvParser::myheritage().parse(file.path()).unwrap_err() {
+            Genome23ParseError::InvalidPosition { line, value } => {
+                assert_eq!(line, 2);
+                assert_eq!(value, "abc");
+            }
+            _ => panic!("Expected InvalidPosition error"),
+        }
+    }
+}
@@ -0,0 +1,397 @@
+// ==============================================================================
+// imputation_info.rs - Imputation Server .info File Parser
+// ==============================================================================
+// Description: Parser for per-variant quality files (chrN.info[.gz]) written by
+//              Minimac on the Michigan and TOPMed imputation servers
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Format: tab-delimited table with header, "-" for values that do not apply
+// Example:
+//   SNP    REF(0)    ALT(1)    ALT_Frq    MAF    AvgCall    Rsq    Genotyped    LooRsq    EmpR    EmpRsq    Dose0    Dose1
+//   22:16050075    A    G    0.00010    0.00010    0.99990    0.00921    Imputed    -    -    -    -    -
+//   chr22:16050115:G:A    G    A    0.01540    0.01540    0.98450    0.81234    Genotyped    0.712    0.843    0.71129    0.97    0.01
+// ==============================================================================
+
+use flate2::read::MultiGzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+use super::report::{IssueCategory, ParseReport};
+use crate::chromosomes::parse_chromosome;
+use crate::models::DataSource;
+
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Info records by chromosome, keyed by (position, REF, ALT)
+pub type ImputationInfoByChromosome = HashMap<u8, HashMap<(u64, String, String), ImputationInfoRecord>>;
+
+/// How the imputation server obtained a variant ("Genotyped" column)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfoVariantStatus {
+    /// Imputed from the reference panel only
+    Imputed,
+    /// On the uploaded array and imputed (has leave-one-out EmpRsq)
+    Genotyped,
+    /// On the uploaded array but not in the reference panel
+    TypedOnly,
+}
+
+impl InfoVariantStatus {
+    /// Parse a "Genotyped" column value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "imputed" => Some(InfoVariantStatus::Imputed),
+            "genotyped" => Some(InfoVariantStatus::Genotyped),
+            "typed_only" => Some(InfoVariantStatus::TypedOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Per-variant imputation quality from an .info file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImputationInfoRecord {
+    /// Chromosome code (see `chromosomes`)
+    pub chromosome: u8,
+
+    /// Base pair position
+    pub position: u64,
+
+    /// Reference allele
+    pub ref_allele: String,
+
+    /// Alternate allele
+    pub alt_allele: String,
+
+    /// ALT allele frequency in the imputed samples
+    pub alt_frequency: Option<f64>,
+
+    /// Minor allele frequency in the imputed samples
+    pub maf: Option<f64>,
+
+    /// Estimated imputation R-squared
+    pub rsq: Option<f64>,
+
+    /// Empirical R-squared against the masked genotypes (typed variants only)
+    pub emp_rsq: Option<f64>,
+
+    /// Whether the variant was imputed or typed
+    pub status: InfoVariantStatus,
+}
+
+impl ImputationInfoRecord {
+    /// Whether the variant was on the uploaded array
+    pub fn is_typed(&self) -> bool {
+        matches!(self.status, InfoVariantStatus::Genotyped | InfoVariantStatus::TypedOnly)
+    }
+
+    /// Imputation quality, preferring EmpRsq over the estimated Rsq
+    pub fn quality(&self) -> Option<f64> {
+        self.emp_rsq.or(self.rsq)
+    }
+
+    /// Data source implied by the server's annotation
+    ///
+    /// Typed variants are `Genotyped`; imputed variants are `ImputedLowQual`
+    /// when their quality is below `low_quality_threshold`.
+    pub fn data_source(&self, low_quality_threshold: f64) -> DataSource {
+        if self.is_typed() {
+            DataSource::Genotyped
+        } else if self.quality().unwrap_or(1.0) < low_quality_threshold {
+            DataSource::ImputedLowQual
+        } else {
+            DataSource::Imputed
+        }
+    }
+}
+
+/// Errors that can occur during .info file parsing
+#[derive(Error, Debug)]
+pub enum ImputationInfoError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Missing required column: {0}")]
+    MissingColumn(String),
+
+    #[error("Invalid line format at line {line}: {details}")]
+    InvalidFormat { line: usize, details: String },
+
+    #[error("Invalid chromosome at line {line}: {value}")]
+    InvalidChromosome { line: usize, value: String },
+
+    #[error("Invalid position value at line {line}: {value}")]
+    InvalidPosition { line: usize, value: String },
+
+    #[error("Invalid value for {column} at line {line}: {value}")]
+    InvalidValue { line: usize, column: String, value: String },
+
+    #[error("File is empty or contains no variants")]
+    EmptyFile,
+}
+
+impl ImputationInfoError {
+    /// Parse report category for a rejected line
+    pub fn category(&self) -> IssueCategory {
+        match self {
+            ImputationInfoError::IoError(_) => IssueCategory::ReadError,
+            ImputationInfoError::MissingColumn(_) => IssueCategory::MissingField,
+            ImputationInfoError::InvalidFormat { .. } | ImputationInfoError::EmptyFile => {
+                IssueCategory::InvalidFormat
+            }
+            ImputationInfoError::InvalidChromosome { .. } => IssueCategory::InvalidChromosome,
+            ImputationInfoError::InvalidPosition { .. } => IssueCategory::InvalidPosition,
+            ImputationInfoError::InvalidValue { .. } => IssueCategory::InvalidValue,
+        }
+    }
+}
+
+/// Column indices of the info table
+struct Columns {
+    snp: usize,
+    ref_allele: usize,
+    alt_allele: usize,
+    alt_frequency: Option<usize>,
+    maf: Option<usize>,
+    rsq: Option<usize>,
+    genotyped: usize,
+    emp_rsq: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(line: &str) -> Result<Self, ImputationInfoError> {
+        // Minimac writes "REF(0)" and "ALT(1)"; accept the bare names too
+        let index: HashMap<String, usize> = line
+            .split('\t')
+            .enumerate()
+            .map(|(i, name)| (name.trim().trim_end_matches("(0)").trim_end_matches("(1)").to_string(), i))
+            .collect();
+
+        let required = |name: &str| {
+            index
+                .get(name)
+                .copied()
+                .ok_or_else(|| ImputationInfoError::MissingColumn(name.to_string()))
+        };
+
+        Ok(Columns {
+            snp: required("SNP")?,
+            ref_allele: required("REF")?,
+            alt_allele: required("ALT")?,
+            alt_frequency: index.get("ALT_Frq").copied(),
+            maf: index.get("MAF").copied(),
+            rsq: index.get("Rsq").copied(),
+            genotyped: required("Genotyped")?,
+            emp_rsq: index.get("EmpRsq").copied(),
+        })
+    }
+}
+
+/// Parser for imputation server .info files
+pub struct ImputationInfoParser;
+
+impl ImputationInfoParser {
+    /// Parse an .info file into records grouped by chromosome
+    ///
+    /// # Arguments
+    /// * `path` - Path to the info file (chrN.info or chrN.info.gz)
+    ///
+    /// # Returns
+    /// * `Ok((records, report))` - Records by chromosome and parse diagnostics
+    /// * `Err(ImputationInfoError)` - File could not be read or has no header
+    ///
+    /// Gzip compression is detected from the file contents. Malformed rows
+    /// are recorded in the report and skipped.
+    pub fn parse(
+        path: impl AsRef<Path>,
+    ) -> Result<(ImputationInfoByChromosome, ParseReport), ImputationInfoError> {
+        let mut reader = BufReader::new(File::open(path.as_ref())?);
+        let report = ParseReport::for_path(path.as_ref());
+
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Self::parse_reader(BufReader::new(MultiGzDecoder::new(reader)), report)
+        } else {
+            Self::parse_reader(reader, report)
+        }
+    }
+
+    /// Parse an uncompressed .info file from a reader
+    ///
+    /// # Arguments
+    /// * `reader` - Info file contents
+    /// * `report` - Empty report to fill (names the source)
+    pub fn parse_reader<R: Read>(
+        reader: BufReader<R>,
+        mut report: ParseReport,
+    ) -> Result<(ImputationInfoByChromosome, ParseReport), ImputationInfoError> {
+        let mut columns: Option<Columns> = None;
+        let mut records: ImputationInfoByChromosome = HashMap::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = idx + 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // The first line is the column header
+            let Some(cols) = &columns else {
+                columns = Some(Columns::from_header(&line)?);
+                continue;
+            };
+
+            report.records_read += 1;
+            match Self::parse_record(&line, line_number, cols) {
+                Ok(record) => {
+                    let key = (record.position, record.ref_allele.clone(), record.alt_allele.clone());
+                    records.entry(record.chromosome).or_default().insert(key, record);
+                    report.records_accepted += 1;
+                }
+                Err(e) => report.record_issue(line_number, e.category(), e.to_string()),
+            }
+        }
+
+        if report.records_accepted == 0 {
+            return Err(ImputationInfoError::EmptyFile);
+        }
+
+        Ok((records, report))
+    }
+
+    /// Parse one variant row
+    fn parse_record(
+        line: &str,
+        line_number: usize,
+        cols: &Columns,
+    ) -> Result<ImputationInfoRecord, ImputationInfoError> {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let field = |index: usize| fields.get(index).copied().filter(|value| !value.is_empty());
+        let number = |index: Option<usize>, column: &str| {
+            index
+                .and_then(field)
+                .filter(|value| *value != "-")
+                .map(|value| {
+                    value.parse::<f64>().ok().filter(|v| v.is_finite()).ok_or_else(|| {
+                        ImputationInfoError::InvalidValue {
+                            line: line_number,
+                            column: column.to_string(),
+                            value: value.to_string(),
+                        }
+                    })
+                })
+                .transpose()
+        };
+        let missing = |column: &str| ImputationInfoError::InvalidFormat {
+            line: line_number,
+            details: format!("Missing {}", column),
+        };
+
+        // SNP is "chr:pos" (Michigan) or "chr:pos:ref:alt" (TOPMed)
+        let snp = field(cols.snp).ok_or_else(|| missing("SNP"))?;
+        let mut parts = snp.split(':');
+        let chr_str = parts.next().unwrap_or("");
+        let pos_str = parts.next().ok_or_else(|| ImputationInfoError::InvalidFormat {
+            line: line_number,
+            details: format!("SNP is not chr:pos: {}", snp),
+        })?;
+
+        let chromosome = parse_chromosome(chr_str).ok_or_else(|| ImputationInfoError::InvalidChromosome {
+            line: line_number,
+            value: chr_str.to_string(),
+        })?;
+        let position = pos_str.parse::<u64>().map_err(|_| ImputationInfoError::InvalidPosition {
+            line: line_number,
+            value: pos_str.to_string(),
+        })?;
+
+        let status_str = field(cols.genotyped).ok_or_else(|| missing("Genotyped"))?;
+        let status = InfoVariantStatus::from_name(status_str).ok_or_else(|| {
+            ImputationInfoError::InvalidValue {
+                line: line_number,
+                column: "Genotyped".to_string(),
+                value: status_str.to_string(),
+            }
+        })?;
+
+        Ok(ImputationInfoRecord {
+            chromosome,
+            position,
+            ref_allele: field(cols.ref_allele).ok_or_else(|| missing("REF"))?.to_uppercase(),
+            alt_allele: field(cols.alt_allele).ok_or_else(|| missing("ALT"))?.to_uppercase(),
+            alt_frequency: number(cols.alt_frequency, "ALT_Frq")?,
+            maf: number(cols.maf, "MAF")?,
+            rsq: number(cols.rsq, "Rsq")?,
+            emp_rsq: number(cols.emp_rsq, "EmpRsq")?,
+            status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const INFO: &str = "\
+SNP\tREF(0)\tALT(1)\tALT_Frq\tMAF\tAvgCall\tRsq\tGenotyped\tLooRsq\tEmpR\tEmpRsq\tDose0\tDose1
+22:16050075\tA\tG\t0.00010\t0.00010\t0.99990\t0.00921\tImputed\t-\t-\t-\t-\t-
+chr22:16050115:G:A\tG\tA\t0.01540\t0.01540\t0.98450\t0.81234\tGenotyped\t0.712\t0.843\t0.71129\t0.97\t0.01
+22:16050213\tC\tT\t0.41\t0.41\t0.95\t0.92\tTyped_Only\t-\t-\t-\t-\t-
+chrUn:100\tA\tC\t0.1\t0.1\t0.9\t0.5\tImputed\t-\t-\t-\t-\t-
+22:16050300\tA\tC\t0.1\t0.1\t0.9\tbad\tImputed\t-\t-\t-\t-\t-
+";
+
+    #[test]
+    fn test_parse_gzip_info() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(INFO.as_bytes()).unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&encoder.finish().unwrap()).unwrap();
+        file.flush().unwrap();
+
+        let (records, report) = ImputationInfoParser::parse(file.path()).unwrap();
+        assert_eq!(report.records_read, 5);
+        assert_eq!(report.records_accepted, 3);
+        assert_eq!(report.issue_counts.get(&IssueCategory::InvalidChromosome), Some(&1));
+        assert_eq!(report.issue_counts.get(&IssueCategory::InvalidValue), Some(&1));
+
+        let chr22 = &records[&22];
+        let imputed = &chr22[&(16050075, "A".to_string(), "G".to_string())];
+        assert_eq!(imputed.status, InfoVariantStatus::Imputed);
+        assert_eq!(imputed.rsq, Some(0.00921));
+        assert_eq!(imputed.emp_rsq, None);
+        assert_eq!(imputed.data_source(0.3), DataSource::ImputedLowQual);
+
+        let genotyped = &chr22[&(16050115, "G".to_string(), "A".to_string())];
+        assert!(genotyped.is_typed());
+        assert_eq!(genotyped.quality(), Some(0.71129));
+        assert_eq!(genotyped.data_source(0.3), DataSource::Genotyped);
+
+        let typed_only = &chr22[&(16050213, "C".to_string(), "T".to_string())];
+        assert_eq!(typed_only.status, InfoVariantStatus::TypedOnly);
+        assert_eq!(typed_only.maf, Some(0.41));
+    }
+
+    #[test]
+    fn test_missing_header_column() {
+        let contents = "SNP\tREF(0)\tALT(1)\tRsq\n22:1\tA\tG\t0.9\n";
+        let result = ImputationInfoParser::parse_reader(
+            BufReader::new(contents.as_bytes()),
+            ParseReport::new("test"),
+        );
+        assert!(matches!(result, Err(ImputationInfoError::MissingColumn(ref c)) if c == "Genotyped"));
+    }
+}
@@ -10,8 +10,25 @@
 
 pub mod vcf;
 pub mod genome23andme;
+pub mod ancestrydna;
+pub mod genotype_csv;
+pub mod imputation_info;
+pub mod plink;
+pub mod raw_genotype;
 pub mod pgs;
+pub mod pgs_catalog;
+pub mod report;
 
-pub use vcf::{VCFParser, VCFRecord, VCFParseError};
-pub use genome23andme::{Genome23Parser, Genome23Record};
-pub use pgs::{PgsParser, PgsRecord, PgsDataset, PgsStats, PgsParseError};
+pub use vcf::{VCFParser, VCFRecord, VCFParseError, VCFSampleValues};
+pub use genome23andme::{DuplicatePolicy, GenomeByChromosome, Genome23Parser, Genome23Record};
+pub use ancestrydna::AncestryDnaParser;
+pub use genotype_csv::{is_genotype_csv_header, GenotypeCsvParser};
+pub use imputation_info::{
+    ImputationInfoByChromosome, ImputationInfoError, ImputationInfoParser, ImputationInfoRecord,
+    InfoVariantStatus,
+};
+pub use plink::{PlinkFileset, PlinkIndividual, PlinkParser};
+pub use raw_genotype::{DetectedProvider, GenotypeProvider, RawGenotypeSource};
+pub use pgs::{PgsParser, PgsRecord, PgsDataset, PgsNormalization, PgsStats, PgsParseError};
+pub use pgs_catalog::{PgsCatalogParser, ScoringFile, ScoringFileError, ScoringMetadata, ScoringVariant};
+pub use report::{IssueCategory, ParseIssue, ParseReport};
@@ -7,7 +7,8 @@
 // Modified: 2025-11-06
 // Version: 1.0.0
 // ==============================================================================
-// Format: CSV file with header
+// Format: delimited text file with header (comma, tab or semicolon separated,
+//         optionally gzip-compressed)
 // Example:
 //   ID,PGS_label,score_value
 //   sample1,Height,1.234
@@ -16,11 +17,17 @@
 // ==============================================================================
 
 use csv::ReaderBuilder;
+use flate2::read::MultiGzDecoder;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
 use std::path::Path;
 use thiserror::Error;
 
+use super::report::ParseReport;
+use crate::pgs_interpretation::normal_quantile;
+
 /// Polygenic score record
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PgsRecord {
@@ -37,14 +44,78 @@ pub struct PgsRecord {
     pub value: f64,
 }
 
+/// Normalization used to derive scaled PGS values (applied per label)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PgsNormalization {
+    /// Z-score using the mean and SD of the reference samples only, so the
+    /// user's own score does not shift the scale it is measured on
+    #[serde(rename = "reference_zscore")]
+    ReferenceZScore,
+    /// Z-score using the mean and SD of all samples, including the user
+    #[serde(rename = "zscore")]
+    ZScore,
+    /// Rank-based inverse normal transform: Φ⁻¹((rank - 3/8) / (n + 1/4)),
+    /// ties share their average rank
+    #[serde(rename = "rank_inverse_normal")]
+    RankInverseNormal,
+    /// Rescale to 0-1 using the minimum and maximum of all samples
+    #[serde(rename = "min_max")]
+    MinMax,
+    /// Scaled values are the unscaled values
+    #[serde(rename = "none")]
+    None,
+}
+
+impl PgsNormalization {
+    /// Parse a normalization name (as used in job payloads)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "reference_zscore" => Some(PgsNormalization::ReferenceZScore),
+            "zscore" => Some(PgsNormalization::ZScore),
+            "rank_inverse_normal" => Some(PgsNormalization::RankInverseNormal),
+            "min_max" => Some(PgsNormalization::MinMax),
+            "none" => Some(PgsNormalization::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PgsNormalization::ReferenceZScore => "reference_zscore",
+            PgsNormalization::ZScore => "zscore",
+            PgsNormalization::RankInverseNormal => "rank_inverse_normal",
+            PgsNormalization::MinMax => "min_max",
+            PgsNormalization::None => "none",
+        }
+    }
+}
+
 /// PGS dataset with both unscaled and scaled versions
 #[derive(Debug, Clone)]
 pub struct PgsDataset {
     /// Original unscaled PGS values
     pub unscaled: Vec<PgsRecord>,
 
-    /// Z-score normalized PGS values (per label)
+    /// Normalized PGS values (per label)
     pub scaled: Vec<PgsRecord>,
+
+    /// Normalization used to derive `scaled`
+    pub normalization: PgsNormalization,
+
+    /// Diagnostics for the parsed file (rows read and accepted)
+    pub report: ParseReport,
+}
+
+impl PgsDataset {
+    /// Recompute `scaled` with a different normalization
+    ///
+    /// # Arguments
+    /// * `method` - Normalization to apply
+    /// * `reference_ids` - Samples the reference statistics are taken from
+    pub fn rescale(&mut self, method: PgsNormalization, reference_ids: &[String]) {
+        self.scaled = PgsParser::normalize(&self.unscaled, method, reference_ids);
+        self.normalization = method;
+    }
 }
 
 /// Errors that can occur during PGS file parsing
@@ -63,6 +134,12 @@ pub enum PgsParseError {
     InvalidValue(String),
 }
 
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Field delimiters recognised in the header line
+const DELIMITERS: [u8; 3] = [b',', b'\t', b';'];
+
 /// PGS file parser with z-score scaling capability
 pub struct PgsParser;
 
@@ -93,18 +170,61 @@ impl PgsParser {
     /// - Each trait is normalized independently
     /// - If std_dev = 0 (constant values), scaled_value = 0
     ///
+    /// Fields may be separated by commas, tabs or semicolons (detected from
+    /// the header line) and the file may be gzip-compressed.
+    ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use genetics_processor::parsers::pgs::PgsParser;
     ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let dataset = PgsParser::parse("scores.txt")?;
     /// println!("Unscaled records: {}", dataset.unscaled.len());
     /// println!("Scaled records: {}", dataset.scaled.len());
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn parse(path: impl AsRef<Path>) -> Result<PgsDataset, PgsParseError> {
+        let file = File::open(path.as_ref())?;
+        Self::parse_reader(file, ParseReport::for_path(path.as_ref()))
+    }
+
+    /// Parse PGS scores from a reader (file contents or an in-memory upload)
+    ///
+    /// Accepts the same formats as `parse`, including gzip-compressed data.
+    ///
+    /// # Arguments
+    /// * `reader` - PGS scores file contents
+    /// * `report` - Empty report to fill (names the source)
+    pub fn parse_reader<R: Read>(reader: R, report: ParseReport) -> Result<PgsDataset, PgsParseError> {
+        let mut reader = BufReader::new(reader);
+
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Self::parse_text(BufReader::new(MultiGzDecoder::new(reader)), report)
+        } else {
+            Self::parse_text(reader, report)
+        }
+    }
+
+    /// Parse uncompressed PGS scores, detecting the delimiter from the header
+    fn parse_text<R: BufRead>(mut reader: R, mut report: ParseReport) -> Result<PgsDataset, PgsParseError> {
+        let mut header_line = String::new();
+        if reader.read_line(&mut header_line)? == 0 {
+            return Err(PgsParseError::EmptyFile);
+        }
+
+        let delimiter = DELIMITERS
+            .iter()
+            .copied()
+            .max_by_key(|d| header_line.bytes().filter(|b| b == d).count())
+            .filter(|d| header_line.as_bytes().contains(d))
+            .unwrap_or(b',');
+
         let mut reader = ReaderBuilder::new()
             .has_headers(true)
-            .from_path(path.as_ref())?;
+            .delimiter(delimiter)
+            .trim(csv::Trim::All)
+            .from_reader(Cursor::new(header_line.into_bytes()).chain(reader));
 
         // Get headers to determine format
         let headers = reader.headers()?.clone();
@@ -114,27 +234,39 @@ impl PgsParser {
 
         let unscaled = if first_col == "sample" || first_col == "\"sample\"" {
             // Wide-form format (Michigan Imputation Server)
-            Self::parse_wide_format(&mut reader, &headers)?
+            Self::parse_wide_format(&mut reader, &headers, &mut report)?
         } else {
             // Long-form format (original)
-            Self::parse_long_format(&mut reader)?
+            Self::parse_long_format(&mut reader, &mut report)?
         };
 
         if unscaled.is_empty() {
             return Err(PgsParseError::EmptyFile);
         }
 
+        // Any bad row fails the parse, so every row read was accepted
+        report.records_accepted = report.records_read;
+
         // Scale by PGS label (z-score normalization)
         let scaled = Self::scale_pgs(&unscaled);
 
-        Ok(PgsDataset { unscaled, scaled })
+        Ok(PgsDataset {
+            unscaled,
+            scaled,
+            normalization: PgsNormalization::ZScore,
+            report,
+        })
     }
 
     /// Parse long-form CSV (original format)
-    fn parse_long_format(reader: &mut csv::Reader<std::fs::File>) -> Result<Vec<PgsRecord>, PgsParseError> {
+    fn parse_long_format<R: Read>(
+        reader: &mut csv::Reader<R>,
+        report: &mut ParseReport,
+    ) -> Result<Vec<PgsRecord>, PgsParseError> {
         let mut unscaled = Vec::new();
 
         for (idx, result) in reader.deserialize().enumerate() {
+            report.records_read += 1;
             let record: PgsRecord = result.map_err(|e| {
                 PgsParseError::CsvError(csv::Error::from(e))
             })?;
@@ -153,9 +285,10 @@ impl PgsParser {
     }
 
     /// Parse wide-form CSV (Michigan Imputation Server format)
-    fn parse_wide_format(
-        reader: &mut csv::Reader<std::fs::File>,
+    fn parse_wide_format<R: Read>(
+        reader: &mut csv::Reader<R>,
         headers: &csv::StringRecord,
+        report: &mut ParseReport,
     ) -> Result<Vec<PgsRecord>, PgsParseError> {
         let mut unscaled = Vec::new();
 
@@ -167,6 +300,7 @@ impl PgsParser {
 
         // Read each row (one sample per row)
         for (row_idx, result) in reader.records().enumerate() {
+            report.records_read += 1;
             let record = result.map_err(PgsParseError::CsvError)?;
 
             // First column is sample ID
@@ -217,7 +351,7 @@ impl PgsParser {
     ///
     /// # Returns
     /// * Scaled PGS records with z-score normalized values
-    fn scale_pgs(records: &[PgsRecord]) -> Vec<PgsRecord> {
+    pub(crate) fn scale_pgs(records: &[PgsRecord]) -> Vec<PgsRecord> {
         // Group by label
         let mut by_label: HashMap<String, Vec<&PgsRecord>> = HashMap::new();
         for record in records {
@@ -264,6 +398,84 @@ impl PgsParser {
         scaled
     }
 
+    /// Normalize PGS values per label with the given method
+    ///
+    /// Constant groups (zero SD or range) scale to 0. For
+    /// `ReferenceZScore`, labels without any reference sample fall back to
+    /// all samples.
+    ///
+    /// # Arguments
+    /// * `records` - Unscaled PGS records
+    /// * `method` - Normalization to apply
+    /// * `reference_ids` - Samples the reference statistics are taken from
+    ///   (the user and any extra VCF samples are left out)
+    ///
+    /// # Returns
+    /// * Scaled PGS records
+    pub fn normalize(
+        records: &[PgsRecord],
+        method: PgsNormalization,
+        reference_ids: &[String],
+    ) -> Vec<PgsRecord> {
+        if method == PgsNormalization::ZScore {
+            return Self::scale_pgs(records);
+        }
+
+        let reference_ids: HashSet<&str> = reference_ids.iter().map(String::as_str).collect();
+
+        let mut by_label: HashMap<&str, Vec<&PgsRecord>> = HashMap::new();
+        for record in records {
+            by_label.entry(record.label.as_str()).or_default().push(record);
+        }
+
+        let mut scaled = Vec::with_capacity(records.len());
+
+        for (label, group) in by_label {
+            let values: Vec<f64> = group.iter().map(|r| r.value).collect();
+
+            let scaled_values = match method {
+                PgsNormalization::ReferenceZScore => {
+                    let reference: Vec<f64> = group
+                        .iter()
+                        .filter(|r| reference_ids.contains(r.sample_id.as_str()))
+                        .map(|r| r.value)
+                        .collect();
+                    let reference = if reference.is_empty() { &values } else { &reference };
+
+                    let n = reference.len() as f64;
+                    let mean = reference.iter().sum::<f64>() / n;
+                    let std_dev = (reference.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+                    values
+                        .iter()
+                        .map(|v| if std_dev > 0.0 { (v - mean) / std_dev } else { 0.0 })
+                        .collect()
+                }
+                PgsNormalization::RankInverseNormal => rank_inverse_normal(&values),
+                PgsNormalization::MinMax => {
+                    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                    let range = max - min;
+
+                    values
+                        .iter()
+                        .map(|v| if range > 0.0 { (v - min) / range } else { 0.0 })
+                        .collect()
+                }
+                PgsNormalization::None => values.clone(),
+                PgsNormalization::ZScore => unreachable!("handled by scale_pgs"),
+            };
+
+            scaled.extend(group.iter().zip(scaled_values).map(|(record, value)| PgsRecord {
+                sample_id: record.sample_id.clone(),
+                label: label.to_string(),
+                value,
+            }));
+        }
+
+        scaled
+    }
+
     /// Get statistics for a specific PGS label
     ///
     /// # Arguments
@@ -305,6 +517,33 @@ impl PgsParser {
     }
 }
 
+/// Rank-based inverse normal transform (Blom offsets, average ranks for ties)
+fn rank_inverse_normal(values: &[f64]) -> Vec<f64> {
+    let n = values.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+
+    let mut ranks = vec![0.0; n];
+    let mut start = 0;
+    while start < n {
+        let mut end = start + 1;
+        while end < n && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // 1-based ranks start+1..=end share their average
+        let average_rank = (start + 1 + end) as f64 / 2.0;
+        for idx in &order[start..end] {
+            ranks[*idx] = average_rank;
+        }
+        start = end;
+    }
+
+    ranks
+        .into_iter()
+        .map(|rank| normal_quantile((rank - 0.375) / (n as f64 + 0.25)))
+        .collect()
+}
+
 /// Statistics for a PGS label
 #[derive(Debug, Clone, PartialEq)]
 pub struct PgsStats {
@@ -484,6 +723,78 @@ mod tests {
         assert!(stats.is_none(), "Should return None for missing label");
     }
 
+    #[test]
+    fn test_normalization_methods() {
+        let records: Vec<PgsRecord> = [("s1", 1.0), ("s2", 2.0), ("s3", 3.0), ("user", 9.0), ("mother", 50.0)]
+            .iter()
+            .map(|(id, value)| PgsRecord {
+                sample_id: id.to_string(),
+                label: "Height".to_string(),
+                value: *value,
+            })
+            .collect();
+        let value_of = |scaled: &[PgsRecord], id: &str| {
+            scaled.iter().find(|r| r.sample_id == id).unwrap().value
+        };
+
+        let reference: Vec<String> = ["s1", "s2", "s3"].iter().map(|id| id.to_string()).collect();
+
+        // Reference-only z: neither the user nor the extra sample moves the
+        // mean (2.0) or SD (0.8165)
+        let scaled = PgsParser::normalize(&records, PgsNormalization::ReferenceZScore, &reference);
+        assert!(value_of(&scaled, "s2").abs() < 1e-10);
+        assert!((value_of(&scaled, "user") - 7.0 / (2.0f64 / 3.0).sqrt()).abs() < 1e-10);
+
+        let scaled = PgsParser::normalize(&records, PgsNormalization::MinMax, &reference);
+        assert_eq!(value_of(&scaled, "s1"), 0.0);
+        assert_eq!(value_of(&scaled, "mother"), 1.0);
+        assert!((value_of(&scaled, "s3") - 2.0 / 49.0).abs() < 1e-10);
+
+        // Ranks 1-5 of n=5 are symmetric around the middle sample
+        let scaled = PgsParser::normalize(&records, PgsNormalization::RankInverseNormal, &reference);
+        assert!((value_of(&scaled, "s1") + value_of(&scaled, "mother")).abs() < 1e-9);
+        assert!(value_of(&scaled, "s3").abs() < 1e-9);
+
+        let scaled = PgsParser::normalize(&records, PgsNormalization::None, &reference);
+        assert_eq!(value_of(&scaled, "user"), 9.0);
+
+        assert_eq!(PgsNormalization::from_name("Rank_Inverse_Normal"), Some(PgsNormalization::RankInverseNormal));
+        assert_eq!(PgsNormalization::from_name("quantile"), None);
+    }
+
+    #[test]
+    fn test_rank_inverse_normal_ties() {
+        let scaled = rank_inverse_normal(&[5.0, 5.0, 1.0]);
+        assert_eq!(scaled[0], scaled[1]);
+        assert!(scaled[2] < scaled[0]);
+    }
+
+    #[test]
+    fn test_parse_delimiters_and_gzip() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let long_tab = "ID\tPGS_label\tscore_value\nsamp1\tHeight\t1.5\nsamp2\tHeight\t-0.5\n";
+        let dataset = PgsParser::parse_reader(long_tab.as_bytes(), ParseReport::default()).unwrap();
+        assert_eq!(dataset.unscaled.len(), 2);
+        assert_eq!(dataset.unscaled[0].value, 1.5);
+
+        let wide_semicolon = "\"sample\";\"PGS000001\";\"PGS000002\"\n\"samp1\";0.25;-1\n";
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(wide_semicolon.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let dataset = PgsParser::parse_reader(&compressed[..], ParseReport::default()).unwrap();
+        assert_eq!(dataset.unscaled.len(), 2);
+        assert_eq!(dataset.unscaled[1].label, "PGS000002");
+        assert_eq!(dataset.unscaled[1].value, -1.0);
+
+        assert!(matches!(
+            PgsParser::parse_reader(&b""[..], ParseReport::default()),
+            Err(PgsParseError::EmptyFile)
+        ));
+    }
+
     #[test]
     fn test_single_value_per_label() {
         // Edge case: only one value for a label
@@ -0,0 +1,604 @@
+// ==============================================================================
+// pgs_catalog.rs - PGS Catalog Scoring File Parser
+// ==============================================================================
+// Description: Parser for PGS Catalog scoring files (PGS000xxx.txt[.gz]),
+//              including harmonized (hm_chr/hm_pos) coordinates
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Format: '#' metadata header, then a tab-delimited variant table
+// Example:
+//   ###PGS CATALOG SCORING FILE - see https://www.pgscatalog.org/downloads/
+//   #format_version=2.0
+//   #pgs_id=PGS000001
+//   #trait_reported=Breast cancer
+//   #genome_build=GRCh37
+//   #variants_number=77
+//   #HmPOS_build=GRCh38
+//   rsID    chr_name    chr_position    effect_allele    other_allele    effect_weight    hm_chr    hm_pos
+//   rs78540526    11    69331418    C    T    0.16    11    69516650
+//
+// Format 1.0 headers ("# PGS ID = PGS000001", "# Original Genome Build = ...")
+// are also accepted.
+// ==============================================================================
+
+use flate2::read::MultiGzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::chromosomes::parse_chromosome;
+use crate::genome_build::GenomeBuild;
+use super::report::{IssueCategory, ParseReport};
+
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Scoring file header metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoringMetadata {
+    /// PGS Catalog identifier (e.g., "PGS000001")
+    pub pgs_id: Option<String>,
+
+    /// Author-reported score name
+    pub pgs_name: Option<String>,
+
+    /// Trait as reported by the authors
+    pub trait_reported: Option<String>,
+
+    /// Trait mapped to an ontology term label
+    pub trait_mapped: Option<String>,
+
+    /// Ontology identifiers for the mapped trait (e.g., "EFO_0000305")
+    pub trait_efo: Option<String>,
+
+    /// Build of `chr_name`/`chr_position` as submitted
+    pub genome_build: GenomeBuild,
+
+    /// Build of `hm_chr`/`hm_pos` in harmonized files
+    pub harmonized_build: Option<GenomeBuild>,
+
+    /// Declared number of variants
+    pub variants_number: Option<usize>,
+
+    /// Weight type (e.g., "beta", "log(OR)", "NR")
+    pub weight_type: Option<String>,
+
+    /// Scoring file format version
+    pub format_version: Option<String>,
+}
+
+/// One weighted variant from a scoring file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoringVariant {
+    /// rsID (harmonized `hm_rsID` when available)
+    pub rsid: Option<String>,
+
+    /// Chromosome code in the submitted build (see `chromosomes`)
+    pub chromosome: Option<u8>,
+
+    /// Position in the submitted build
+    pub position: Option<u64>,
+
+    /// Chromosome code in the harmonized build
+    pub harmonized_chromosome: Option<u8>,
+
+    /// Position in the harmonized build
+    pub harmonized_position: Option<u64>,
+
+    /// Allele whose dosage is multiplied by the weight
+    pub effect_allele: String,
+
+    /// Non-effect allele, if given
+    pub other_allele: Option<String>,
+
+    /// Per-allele weight
+    pub effect_weight: f64,
+
+    /// Effect allele frequency reported by the authors
+    pub allele_frequency: Option<f64>,
+}
+
+/// Parsed scoring file with typed weights
+#[derive(Debug, Clone)]
+pub struct ScoringFile {
+    /// Header metadata (build, trait, declared variant count)
+    pub metadata: ScoringMetadata,
+
+    /// Weighted variants in file order
+    pub variants: Vec<ScoringVariant>,
+
+    /// Diagnostics for the parsed file
+    pub report: ParseReport,
+}
+
+impl ScoringFile {
+    /// Score identifier, falling back to the score name
+    pub fn label(&self) -> String {
+        self.metadata
+            .pgs_id
+            .clone()
+            .or_else(|| self.metadata.pgs_name.clone())
+            .unwrap_or_else(|| self.report.source.clone())
+    }
+
+    /// Chromosome and position of a variant in the requested build
+    ///
+    /// Uses harmonized coordinates when the file was harmonized to `build`,
+    /// otherwise the submitted coordinates when they are in `build`.
+    ///
+    /// # Returns
+    /// * `Some((chromosome, position))` - Coordinates in `build`
+    /// * `None` - No coordinates in `build` (match on rsID instead)
+    pub fn locus(&self, variant: &ScoringVariant, build: GenomeBuild) -> Option<(u8, u64)> {
+        if self.metadata.harmonized_build == Some(build) {
+            if let (Some(chr), Some(pos)) = (variant.harmonized_chromosome, variant.harmonized_position) {
+                return Some((chr, pos));
+            }
+        }
+
+        if self.metadata.genome_build == build {
+            if let (Some(chr), Some(pos)) = (variant.chromosome, variant.position) {
+                return Some((chr, pos));
+            }
+        }
+
+        None
+    }
+}
+
+/// Errors that can occur during scoring file parsing
+#[derive(Error, Debug)]
+pub enum ScoringFileError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Missing required column: {0}")]
+    MissingColumn(String),
+
+    #[error("Invalid line format at line {line}: {details}")]
+    InvalidFormat { line: usize, details: String },
+
+    #[error("Invalid effect weight at line {line}: {value}")]
+    InvalidWeight { line: usize, value: String },
+
+    #[error("Invalid position value at line {line}: {value}")]
+    InvalidPosition { line: usize, value: String },
+
+    #[error("File is empty or contains no variants")]
+    EmptyFile,
+}
+
+impl ScoringFileError {
+    /// Parse report category for a rejected line
+    pub fn category(&self) -> IssueCategory {
+        match self {
+            ScoringFileError::IoError(_) => IssueCategory::ReadError,
+            ScoringFileError::MissingColumn(_) => IssueCategory::MissingField,
+            ScoringFileError::InvalidFormat { .. } | ScoringFileError::EmptyFile => {
+                IssueCategory::InvalidFormat
+            }
+            ScoringFileError::InvalidWeight { .. } => IssueCategory::InvalidValue,
+            ScoringFileError::InvalidPosition { .. } => IssueCategory::InvalidPosition,
+        }
+    }
+}
+
+/// Column indices of the variant table
+struct Columns {
+    rsid: Option<usize>,
+    chr_name: Option<usize>,
+    chr_position: Option<usize>,
+    effect_allele: usize,
+    other_allele: Option<usize>,
+    effect_weight: usize,
+    allele_frequency: Option<usize>,
+    hm_rsid: Option<usize>,
+    hm_chr: Option<usize>,
+    hm_pos: Option<usize>,
+    is_dominant: Option<usize>,
+    is_recessive: Option<usize>,
+    is_haplotype: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(line: &str) -> Result<Self, ScoringFileError> {
+        let index: HashMap<&str, usize> = line
+            .split('\t')
+            .enumerate()
+            .map(|(i, name)| (name.trim(), i))
+            .collect();
+
+        let required = |name: &str| {
+            index
+                .get(name)
+                .copied()
+                .ok_or_else(|| ScoringFileError::MissingColumn(name.to_string()))
+        };
+
+        let columns = Columns {
+            rsid: index.get("rsID").copied(),
+            chr_name: index.get("chr_name").copied(),
+            chr_position: index.get("chr_position").copied(),
+            effect_allele: required("effect_allele")?,
+            other_allele: index
+                .get("other_allele")
+                .or_else(|| index.get("reference_allele"))
+                .copied(),
+            effect_weight: required("effect_weight")?,
+            allele_frequency: index.get("allelefrequency_effect").copied(),
+            hm_rsid: index.get("hm_rsID").copied(),
+            hm_chr: index.get("hm_chr").copied(),
+            hm_pos: index.get("hm_pos").copied(),
+            is_dominant: index.get("is_dominant").copied(),
+            is_recessive: index.get("is_recessive").copied(),
+            is_haplotype: index.get("is_haplotype").copied(),
+        };
+
+        // Variants must be locatable by rsID or by position
+        if columns.rsid.is_none()
+            && columns.hm_rsid.is_none()
+            && (columns.chr_name.is_none() || columns.chr_position.is_none())
+        {
+            return Err(ScoringFileError::MissingColumn(
+                "rsID or chr_name/chr_position".to_string(),
+            ));
+        }
+
+        Ok(columns)
+    }
+
+    /// Name of the non-additive effect model flagged on a row, if any
+    ///
+    /// Weights are applied per effect allele copy, so dominant, recessive
+    /// and haplotype weights cannot be scored.
+    fn non_additive_model(&self, line: &str) -> Option<&'static str> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let flagged = |index: Option<usize>| {
+            index
+                .and_then(|i| fields.get(i))
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+        };
+
+        if flagged(self.is_dominant) {
+            Some("dominant")
+        } else if flagged(self.is_recessive) {
+            Some("recessive")
+        } else if flagged(self.is_haplotype) {
+            Some("haplotype")
+        } else {
+            None
+        }
+    }
+}
+
+/// Parser for PGS Catalog scoring files
+pub struct PgsCatalogParser;
+
+impl PgsCatalogParser {
+    /// Parse a PGS Catalog scoring file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the scoring file (PGS000xxx.txt or .txt.gz)
+    ///
+    /// # Returns
+    /// * `Ok(ScoringFile)` - Metadata and weighted variants
+    /// * `Err(ScoringFileError)` - Parse error
+    ///
+    /// Gzip compression is detected from the file contents. Any malformed
+    /// variant row fails the parse, since a missing weight changes the score.
+    /// Dominant, recessive and haplotype variants are skipped and counted
+    /// in the report as unsupported.
+    pub fn parse(path: impl AsRef<Path>) -> Result<ScoringFile, ScoringFileError> {
+        let mut reader = BufReader::new(File::open(path.as_ref())?);
+        let report = ParseReport::for_path(path.as_ref());
+
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Self::parse_reader(BufReader::new(MultiGzDecoder::new(reader)), report)
+        } else {
+            Self::parse_reader(reader, report)
+        }
+    }
+
+    /// Parse an uncompressed scoring file from a reader
+    ///
+    /// # Arguments
+    /// * `reader` - Scoring file contents
+    /// * `report` - Empty report to fill (names the source)
+    pub fn parse_reader<R: Read>(
+        reader: BufReader<R>,
+        mut report: ParseReport,
+    ) -> Result<ScoringFile, ScoringFileError> {
+        let mut metadata = ScoringMetadata::default();
+        let mut columns: Option<Columns> = None;
+        let mut variants = Vec::new();
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = idx + 1;
+
+            if let Some(comment) = line.strip_prefix('#') {
+                Self::parse_metadata_line(comment, &mut metadata);
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // The first non-comment line is the column header
+            let Some(cols) = &columns else {
+                columns = Some(Columns::from_header(&line)?);
+                continue;
+            };
+
+            report.records_read += 1;
+            if let Some(model) = cols.non_additive_model(&line) {
+                report.record_issue(
+                    line_number,
+                    IssueCategory::Unsupported,
+                    format!("Skipped {} effect variant", model),
+                );
+                continue;
+            }
+            variants.push(Self::parse_variant(&line, line_number, cols)?);
+        }
+
+        if variants.is_empty() {
+            return Err(ScoringFileError::EmptyFile);
+        }
+        report.records_accepted = variants.len();
+
+        if let Some(declared) = metadata.variants_number {
+            if declared != variants.len() {
+                warn!(
+                    "{}: header declares {} variants, found {}",
+                    report.source, declared, variants.len()
+                );
+            }
+        }
+
+        Ok(ScoringFile { metadata, variants, report })
+    }
+
+    /// Apply a "key=value" (format 2.0) or "Key Name = value" (format 1.0)
+    /// header line to the metadata
+    fn parse_metadata_line(comment: &str, metadata: &mut ScoringMetadata) {
+        let Some((key, value)) = comment.trim_start_matches('#').split_once('=') else {
+            return;
+        };
+
+        let key = key.trim().to_ascii_lowercase().replace(' ', "_");
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+
+        match key.as_str() {
+            "pgs_id" => metadata.pgs_id = Some(value.to_string()),
+            "pgs_name" => metadata.pgs_name = Some(value.to_string()),
+            "trait_reported" | "reported_trait" => metadata.trait_reported = Some(value.to_string()),
+            "trait_mapped" | "mapped_trait(s)_(efo_label)" => metadata.trait_mapped = Some(value.to_string()),
+            "trait_efo" | "mapped_trait(s)_(efo_id)" => metadata.trait_efo = Some(value.to_string()),
+            "genome_build" | "original_genome_build" => metadata.genome_build = GenomeBuild::from_name(value),
+            "hmpos_build" => metadata.harmonized_build = Some(GenomeBuild::from_name(value)),
+            "variants_number" | "number_of_variants" => metadata.variants_number = value.parse().ok(),
+            "weight_type" => metadata.weight_type = Some(value.to_string()),
+            "format_version" => metadata.format_version = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    /// Parse one variant row
+    fn parse_variant(
+        line: &str,
+        line_number: usize,
+        cols: &Columns,
+    ) -> Result<ScoringVariant, ScoringFileError> {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| fields.get(i).copied())
+                .filter(|value| !value.is_empty() && *value != "NA")
+        };
+        let position = |index: Option<usize>| {
+            field(index)
+                .map(|value| {
+                    value.parse::<u64>().map_err(|_| ScoringFileError::InvalidPosition {
+                        line: line_number,
+                        value: value.to_string(),
+                    })
+                })
+                .transpose()
+        };
+
+        let effect_allele = field(Some(cols.effect_allele)).ok_or_else(|| {
+            ScoringFileError::InvalidFormat {
+                line: line_number,
+                details: "Missing effect_allele".to_string(),
+            }
+        })?;
+
+        let weight_str = field(Some(cols.effect_weight)).unwrap_or("");
+        let effect_weight = weight_str
+            .parse::<f64>()
+            .ok()
+            .filter(|weight| weight.is_finite())
+            .ok_or_else(|| ScoringFileError::InvalidWeight {
+                line: line_number,
+                value: weight_str.to_string(),
+            })?;
+
+        let rsid = field(cols.hm_rsid)
+            .or_else(|| field(cols.rsid))
+            .map(str::to_string);
+
+        let variant = ScoringVariant {
+            rsid,
+            chromosome: field(cols.chr_name).and_then(parse_chromosome),
+            position: position(cols.chr_position)?,
+            harmonized_chromosome: field(cols.hm_chr).and_then(parse_chromosome),
+            harmonized_position: position(cols.hm_pos)?,
+            effect_allele: effect_allele.to_string(),
+            other_allele: field(cols.other_allele).map(str::to_string),
+            effect_weight,
+            allele_frequency: field(cols.allele_frequency).and_then(|v| v.parse().ok()),
+        };
+
+        if variant.rsid.is_none()
+            && variant.position.is_none()
+            && variant.harmonized_position.is_none()
+        {
+            return Err(ScoringFileError::InvalidFormat {
+                line: line_number,
+                details: "Variant has neither rsID nor position".to_string(),
+            });
+        }
+
+        Ok(variant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const HARMONIZED: &str = "\
+###PGS CATALOG SCORING FILE - see https://www.pgscatalog.org/downloads/#dl_ftp_scoring for additional information
+#format_version=2.0
+##POLYGENIC SCORE (PGS) INFORMATION
+#pgs_id=PGS000001
+#pgs_name=PRS77_BC
+#trait_reported=Breast cancer
+#trait_mapped=breast carcinoma
+#trait_efo=EFO_0000305
+#genome_build=GRCh37
+#variants_number=2
+#weight_type=NR
+##HARMONIZATION DETAILS
+#HmPOS_build=GRCh38
+rsID\tchr_name\tchr_position\teffect_allele\tother_allele\teffect_weight\tallelefrequency_effect\thm_source\thm_rsID\thm_chr\thm_pos
+rs78540526\t11\t69331418\tC\tT\t0.16\t0.05\tENSEMBL\trs78540526\t11\t69516650
+rs75915166\tX\t69379161\tA\tC\t-0.06\t\tENSEMBL\trs75915166\tX\t69564393
+";
+
+    fn create_test_file(contents: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents).unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_harmonized_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(HARMONIZED.as_bytes()).unwrap();
+        let file = create_test_file(&encoder.finish().unwrap());
+
+        let scoring = PgsCatalogParser::parse(file.path()).unwrap();
+        assert_eq!(scoring.label(), "PGS000001");
+        assert_eq!(scoring.metadata.trait_reported.as_deref(), Some("Breast cancer"));
+        assert_eq!(scoring.metadata.genome_build, GenomeBuild::GRCh37);
+        assert_eq!(scoring.metadata.harmonized_build, Some(GenomeBuild::GRCh38));
+        assert_eq!(scoring.metadata.variants_number, Some(2));
+        assert_eq!(scoring.variants.len(), 2);
+        assert_eq!(scoring.report.records_accepted, 2);
+
+        let first = &scoring.variants[0];
+        assert_eq!(first.effect_allele, "C");
+        assert_eq!(first.effect_weight, 0.16);
+        assert_eq!(first.allele_frequency, Some(0.05));
+        assert_eq!(scoring.locus(first, GenomeBuild::GRCh37), Some((11, 69331418)));
+        assert_eq!(scoring.locus(first, GenomeBuild::GRCh38), Some((11, 69516650)));
+        assert_eq!(scoring.locus(first, GenomeBuild::NCBI36), None);
+
+        assert_eq!(scoring.variants[1].chromosome, Some(crate::chromosomes::CHR_X));
+        assert_eq!(scoring.variants[1].allele_frequency, None);
+    }
+
+    #[test]
+    fn test_parse_format_v1_header() {
+        let contents = "\
+# PGS ID = PGS000002
+# Reported Trait = Height
+# Original Genome Build = hg19
+# Number of Variants = 1
+rsID\teffect_allele\treference_allele\teffect_weight
+rs1\tA\tG\t-0.25
+";
+        let file = create_test_file(contents.as_bytes());
+
+        let scoring = PgsCatalogParser::parse(file.path()).unwrap();
+        assert_eq!(scoring.metadata.pgs_id.as_deref(), Some("PGS000002"));
+        assert_eq!(scoring.metadata.trait_reported.as_deref(), Some("Height"));
+        assert_eq!(scoring.metadata.genome_build, GenomeBuild::GRCh37);
+        assert_eq!(scoring.variants[0].rsid.as_deref(), Some("rs1"));
+        assert_eq!(scoring.variants[0].other_allele.as_deref(), Some("G"));
+        assert_eq!(scoring.locus(&scoring.variants[0], GenomeBuild::GRCh37), None);
+    }
+
+    #[test]
+    fn test_invalid_weight() {
+        let contents = "\
+#pgs_id=PGS000003
+rsID\tchr_name\tchr_position\teffect_allele\teffect_weight
+rs1\t1\t100\tA\t0.1
+rs2\t1\t200\tC\tNOT_A_NUMBER
+";
+        let file = create_test_file(contents.as_bytes());
+
+        match PgsCatalogParser::parse(file.path()).unwrap_err() {
+            ScoringFileError::InvalidWeight { line, value } => {
+                assert_eq!(line, 4);
+                assert_eq!(value, "NOT_A_NUMBER");
+            }
+            e => panic!("Expected InvalidWeight error, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_skips_non_additive_variants() {
+        let contents = "\
+#pgs_id=PGS000004
+rsID\teffect_allele\teffect_weight\tis_haplotype\tis_dominant\tis_recessive
+rs1\tA\t0.1\tFalse\tFalse\tFalse
+rs2\tC\t0.2\tFalse\tTrue\tFalse
+rs3\tG\t0.3\tFalse\tFalse\tTrue
+rs4\tT\t0.4\tTrue\tFalse\tFalse
+rs5\tA\t0.5\t\t\t
+";
+        let file = create_test_file(contents.as_bytes());
+
+        let scoring = PgsCatalogParser::parse(file.path()).unwrap();
+        let rsids: Vec<_> = scoring.variants.iter().map(|v| v.rsid.as_deref().unwrap()).collect();
+        assert_eq!(rsids, ["rs1", "rs5"]);
+        assert_eq!(scoring.report.records_read, 5);
+        assert_eq!(scoring.report.records_accepted, 2);
+        assert_eq!(scoring.report.issue_counts[&IssueCategory::Unsupported], 3);
+        assert_eq!(scoring.report.issues[0].line, 4);
+        assert_eq!(scoring.report.issues[0].reason, "Skipped dominant effect variant");
+    }
+
+    #[test]
+    fn test_missing_weight_column() {
+        let contents = "\
+rsID\teffect_allele\tdosage_1_weight
+rs1\tA\t0.1
+";
+        let file = create_test_file(contents.as_bytes());
+
+        match PgsCatalogParser::parse(file.path()).unwrap_err() {
+            ScoringFileError::MissingColumn(column) => assert_eq!(column, "effect_weight"),
+            e => panic!("Expected MissingColumn error, got {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,449 @@
+// ==============================================================================
+// plink.rs - PLINK Binary Fileset Parser
+// ==============================================================================
+// Description: Reader for PLINK 1 binary filesets (.bed/.bim/.fam) from
+//              research genotyping arrays
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Format: three files sharing a prefix
+//   .fam - one individual per line: FID IID father mother sex phenotype
+//   .bim - one variant per line: chromosome id cM position allele1 allele2
+//   .bed - magic bytes 0x6c 0x1b, mode 0x01 (variant-major), then per variant
+//          ceil(individuals / 4) bytes holding 2-bit genotypes, lowest bits
+//          first: 00 = allele1/allele1, 01 = missing, 10 = het,
+//          11 = allele2/allele2
+// ==============================================================================
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use super::genome23andme::{Genome23ParseError, Genome23Record};
+use super::raw_genotype::normalize_chromosome;
+use super::report::{IssueCategory, ParseReport};
+
+/// BED magic bytes followed by the variant-major mode byte
+const BED_MAGIC: [u8; 3] = [0x6c, 0x1b, 0x01];
+
+/// Check whether a file starts with the PLINK BED magic bytes
+pub fn is_bed_file(path: impl AsRef<Path>) -> bool {
+    let mut magic = [0u8; 3];
+    File::open(path.as_ref())
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .map(|_| magic == BED_MAGIC)
+        .unwrap_or(false)
+}
+
+/// Genotypes of every individual in a fileset, in .fam order
+pub type IndividualGenotypes = Vec<(PlinkIndividual, Vec<Genome23Record>)>;
+
+/// Paths of the three files in a PLINK binary fileset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlinkFileset {
+    pub bed: PathBuf,
+    pub bim: PathBuf,
+    pub fam: PathBuf,
+}
+
+impl PlinkFileset {
+    /// Fileset sharing the prefix of any of its files (or the bare prefix)
+    ///
+    /// `data/array.bed`, `data/array.fam` and `data/array` all give
+    /// `data/array.{bed,bim,fam}`.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let prefix = match path.extension().and_then(|e| e.to_str()) {
+            Some("bed" | "bim" | "fam") => path.with_extension(""),
+            _ => path.to_path_buf(),
+        };
+
+        let with_ext = |ext: &str| {
+            let mut file = prefix.clone().into_os_string();
+            file.push(".");
+            file.push(ext);
+            PathBuf::from(file)
+        };
+
+        Self {
+            bed: with_ext("bed"),
+            bim: with_ext("bim"),
+            fam: with_ext("fam"),
+        }
+    }
+
+    /// Check that all three files exist
+    pub fn exists(&self) -> bool {
+        self.bed.is_file() && self.bim.is_file() && self.fam.is_file()
+    }
+}
+
+/// Individual from a .fam file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlinkIndividual {
+    pub family_id: String,
+    pub individual_id: String,
+    /// 1 = male, 2 = female, 0 = unknown
+    pub sex: u8,
+}
+
+/// Variant from a .bim file (chromosome in 23andMe naming)
+#[derive(Debug, Clone, PartialEq)]
+struct BimVariant {
+    id: String,
+    chromosome: Option<String>,
+    position: u64,
+    allele1: String,
+    allele2: String,
+    line: usize,
+}
+
+/// Parser for PLINK binary filesets
+///
+/// Produces the same `Genome23Record` output as `Genome23Parser` for one
+/// individual, so a research array can stand in for a consumer raw data file.
+#[derive(Debug, Clone, Default)]
+pub struct PlinkParser {
+    /// Chromosomes to include, using 23andMe naming (e.g., "1", "X", "MT")
+    /// If empty, includes all chromosomes
+    pub include_chromosomes: Vec<String>,
+
+    /// Individual to read (IID or "FID_IID"); required when the .fam lists
+    /// more than one individual
+    pub individual: Option<String>,
+}
+
+impl PlinkParser {
+    /// Create a new parser that includes all chromosomes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a parser with specific chromosomes to include
+    pub fn with_chromosomes(chromosomes: Vec<String>) -> Self {
+        Self {
+            include_chromosomes: chromosomes,
+            individual: None,
+        }
+    }
+
+    /// Select the individual to read from a multi-individual fileset
+    pub fn with_individual(mut self, id: impl Into<String>) -> Self {
+        self.individual = Some(id.into());
+        self
+    }
+
+    /// Read the individuals listed in a fileset's .fam file
+    pub fn individuals(&self, fileset: &PlinkFileset) -> Result<Vec<PlinkIndividual>, Genome23ParseError> {
+        let reader = BufReader::new(File::open(&fileset.fam)?);
+        let mut individuals = Vec::new();
+
+        for (idx, line_result) in reader.lines().enumerate() {
+            let line = line_result?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            if fields.len() != 6 {
+                return Err(Genome23ParseError::InvalidFormat {
+                    line: idx + 1,
+                    details: format!("Expected 6 .fam fields, found {}", fields.len()),
+                });
+            }
+
+            individuals.push(PlinkIndividual {
+                family_id: fields[0].to_string(),
+                individual_id: fields[1].to_string(),
+                sex: fields[4].parse().unwrap_or(0),
+            });
+        }
+
+        if individuals.is_empty() {
+            return Err(Genome23ParseError::EmptyFile);
+        }
+        Ok(individuals)
+    }
+
+    /// Parse the selected individual's genotypes, also returning a parse report
+    ///
+    /// # Arguments
+    /// * `path` - Any file of the fileset (.bed, .bim or .fam) or its prefix
+    ///
+    /// # Returns
+    /// * `Ok((records, report))` - Genotypes with PLINK chromosome codes
+    ///   normalized to 23andMe names and missing calls as "--"
+    /// * `Err(Genome23ParseError)` - Missing file, bad .bed header, malformed
+    ///   .bim/.fam, or several individuals and none selected
+    ///
+    /// A multi-individual fileset is rejected unless an individual is
+    /// selected, rather than silently scoring whoever is listed first.
+    /// Variants on unplaced contigs (chromosome 0) or with multi-base alleles
+    /// are skipped and counted in the report.
+    pub fn parse_with_report(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        let fileset = PlinkFileset::from_path(path);
+        let individuals = self.individuals(&fileset)?;
+
+        let index = match &self.individual {
+            None if individuals.len() > 1 => {
+                return Err(Genome23ParseError::UnsupportedFormat(format!(
+                    "PLINK fileset lists {} individuals; upload a fileset with only the user (e.g. plink --keep)",
+                    individuals.len()
+                )));
+            }
+            None => 0,
+            Some(id) => individuals
+                .iter()
+                .position(|i| {
+                    &i.individual_id == id || format!("{}_{}", i.family_id, i.individual_id) == *id
+                })
+                .ok_or_else(|| {
+                    Genome23ParseError::UnsupportedFormat(format!("Individual {} not found in .fam", id))
+                })?,
+        };
+
+        let (mut records, report) = self.parse_individuals(&fileset, individuals.len(), &[index])?;
+        Ok((records.pop().unwrap_or_default(), report))
+    }
+
+    /// Parse every individual in a fileset
+    ///
+    /// # Returns
+    /// * Each individual with their genotypes (in .fam order), and one report
+    ///   for the fileset
+    pub fn parse_all(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(IndividualGenotypes, ParseReport), Genome23ParseError> {
+        let fileset = PlinkFileset::from_path(path);
+        let individuals = self.individuals(&fileset)?;
+        let indices: Vec<usize> = (0..individuals.len()).collect();
+
+        let (records, report) = self.parse_individuals(&fileset, individuals.len(), &indices)?;
+        Ok((individuals.into_iter().zip(records).collect(), report))
+    }
+
+    /// Decode the .bed genotypes of the given individuals
+    fn parse_individuals(
+        &self,
+        fileset: &PlinkFileset,
+        individual_count: usize,
+        indices: &[usize],
+    ) -> Result<(Vec<Vec<Genome23Record>>, ParseReport), Genome23ParseError> {
+        let variants = read_bim(&fileset.bim)?;
+        let mut report = ParseReport::for_path(&fileset.bed);
+
+        let mut bed = BufReader::new(File::open(&fileset.bed)?);
+        let mut magic = [0u8; 3];
+        bed.read_exact(&mut magic)?;
+        if magic != BED_MAGIC {
+            return Err(Genome23ParseError::UnsupportedFormat(
+                "Not a variant-major PLINK .bed file".to_string(),
+            ));
+        }
+
+        let bytes_per_variant = individual_count.div_ceil(4);
+        let mut block = vec![0u8; bytes_per_variant];
+        let mut records: Vec<Vec<Genome23Record>> = vec![Vec::new(); indices.len()];
+
+        for variant in &variants {
+            bed.read_exact(&mut block).map_err(|e| match e.kind() {
+                std::io::ErrorKind::UnexpectedEof => Genome23ParseError::InvalidFormat {
+                    line: variant.line,
+                    details: ".bed file is shorter than the .bim variant list".to_string(),
+                },
+                _ => Genome23ParseError::IoError(e),
+            })?;
+            report.records_read += 1;
+
+            let Some(chromosome) = &variant.chromosome else {
+                report.record_issue(variant.line, IssueCategory::InvalidChromosome, format!("{}: unplaced variant", variant.id));
+                continue;
+            };
+            if !self.include_chromosomes.is_empty() && !self.include_chromosomes.contains(chromosome) {
+                report.filtered_by_chromosome += 1;
+                continue;
+            }
+            if variant.allele1.len() > 1 || variant.allele2.len() > 1 {
+                report.record_issue(variant.line, IssueCategory::InvalidFormat, format!("{}: multi-base allele", variant.id));
+                continue;
+            }
+
+            for (out, &index) in records.iter_mut().zip(indices) {
+                let code = (block[index / 4] >> ((index % 4) * 2)) & 0b11;
+                let genotype = match code {
+                    0b00 => format!("{}{}", variant.allele1, variant.allele1),
+                    0b10 => format!("{}{}", variant.allele1, variant.allele2),
+                    0b11 => format!("{}{}", variant.allele2, variant.allele2),
+                    _ => "--".to_string(),
+                };
+                // "0" marks an unknown allele (monomorphic site)
+                let genotype = if genotype.contains('0') { "--".to_string() } else { genotype };
+
+                out.push(Genome23Record {
+                    rsid: variant.id.clone(),
+                    chromosome: chromosome.clone(),
+                    position: variant.position,
+                    genotype,
+                });
+            }
+        }
+
+        if records.iter().all(|r| r.is_empty()) {
+            return Err(Genome23ParseError::EmptyFile);
+        }
+
+        // Report accepted records and no-calls for the first selected individual
+        if let Some(first) = records.first() {
+            report.records_accepted = first.len();
+            report.no_calls = first.iter().filter(|r| r.genotype == "--").count();
+        }
+
+        Ok((records, report))
+    }
+}
+
+/// Read the variant list from a .bim file
+fn read_bim(path: &Path) -> Result<Vec<BimVariant>, Genome23ParseError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut variants = Vec::new();
+
+    for (idx, line_result) in reader.lines().enumerate() {
+        let line = line_result?;
+        let line_number = idx + 1;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        if fields.len() != 6 {
+            return Err(Genome23ParseError::InvalidFormat {
+                line: line_number,
+                details: format!("Expected 6 .bim fields, found {}", fields.len()),
+            });
+        }
+
+        let position = fields[3].parse::<u64>().map_err(|_| Genome23ParseError::InvalidPosition {
+            line: line_number,
+            value: fields[3].to_string(),
+        })?;
+
+        variants.push(BimVariant {
+            id: fields[1].to_string(),
+            chromosome: normalize_chromosome(fields[0].trim_start_matches("chr")),
+            position,
+            allele1: fields[4].to_uppercase(),
+            allele2: fields[5].to_uppercase(),
+            line: line_number,
+        });
+    }
+
+    Ok(variants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// Write a 3-individual, 3-variant fileset and return its .bed path
+    fn create_test_fileset(dir: &TempDir) -> PathBuf {
+        let prefix = dir.path().join("array");
+        std::fs::write(
+            prefix.with_extension("fam"),
+            "F1 I1 0 0 1 -9\nF2 I2 0 0 2 -9\nF3 I3 0 0 0 -9\n",
+        )
+        .unwrap();
+        std::fs::write(
+            prefix.with_extension("bim"),
+            "1\trs1\t0\t1000\tA\tG\n23\trs2\t0\t2000\tC\tT\n0\trs3\t0\t0\tA\tC\n",
+        )
+        .unwrap();
+
+        // Individuals packed lowest bits first: I1, I2, I3
+        // rs1: I1 = 00 (AA), I2 = 10 (AG), I3 = 11 (GG)
+        // rs2: I1 = 01 (missing), I2 = 11 (TT), I3 = 00 (CC)
+        let mut bed = File::create(prefix.with_extension("bed")).unwrap();
+        bed.write_all(&BED_MAGIC).unwrap();
+        bed.write_all(&[0b11_10_00, 0b00_11_01, 0]).unwrap();
+
+        prefix.with_extension("bed")
+    }
+
+    #[test]
+    fn test_parse_single_individual() {
+        let dir = TempDir::new().unwrap();
+        let bed = create_test_fileset(&dir);
+        assert!(is_bed_file(&bed));
+
+        let (records, report) = PlinkParser::new().with_individual("I2").parse_with_report(&bed).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].genotype, "AG");
+        assert_eq!(records[1].chromosome, "X");
+        assert_eq!(records[1].genotype, "TT");
+
+        assert_eq!(report.records_read, 3);
+        assert_eq!(report.issue_count(), 1);
+
+        // Individuals can be selected by FID_IID, from any file of the set
+        let fam = PlinkFileset::from_path(&bed).fam;
+        let (records, report) = PlinkParser::new().with_individual("F1_I1").parse_with_report(&fam).unwrap();
+        assert_eq!(records[0].genotype, "AA");
+        assert_eq!(records[1].genotype, "--");
+        assert_eq!(report.no_calls, 1);
+    }
+
+    #[test]
+    fn test_multi_individual_fileset_needs_selection() {
+        let dir = TempDir::new().unwrap();
+        let bed = create_test_fileset(&dir);
+
+        match PlinkParser::new().parse_with_report(&bed).unwrap_err() {
+            Genome23ParseError::UnsupportedFormat(msg) => assert!(msg.contains("3 individuals")),
+            e => panic!("Expected UnsupportedFormat, got {:?}", e),
+        }
+
+        // A single-individual fileset needs no selection
+        let fam = PlinkFileset::from_path(&bed).fam;
+        std::fs::write(&fam, "F1 I1 0 0 1 -9\n").unwrap();
+        let bed_path = PlinkFileset::from_path(&bed).bed;
+        let mut bed_file = File::create(&bed_path).unwrap();
+        bed_file.write_all(&BED_MAGIC).unwrap();
+        bed_file.write_all(&[0b00, 0b01, 0]).unwrap();
+        drop(bed_file);
+
+        let (records, _) = PlinkParser::new().parse_with_report(&bed).unwrap();
+        assert_eq!(records[0].genotype, "AA");
+        assert_eq!(records[1].genotype, "--");
+    }
+
+    #[test]
+    fn test_parse_all_individuals() {
+        let dir = TempDir::new().unwrap();
+        let bed = create_test_fileset(&dir);
+
+        let (individuals, _) = PlinkParser::with_chromosomes(vec!["1".to_string()]).parse_all(&bed).unwrap();
+        let genotypes: Vec<&str> = individuals.iter().map(|(_, r)| r[0].genotype.as_str()).collect();
+        assert_eq!(genotypes, vec!["AA", "AG", "GG"]);
+        assert_eq!(individuals[2].0.individual_id, "I3");
+        assert!(individuals.iter().all(|(_, r)| r.len() == 1));
+
+        assert!(PlinkParser::new().with_individual("missing").parse_with_report(&bed).is_err());
+    }
+
+    #[test]
+    fn test_detect_plink_fileset() {
+        use crate::parsers::raw_genotype::{detect_provider, GenotypeProvider};
+
+        let dir = TempDir::new().unwrap();
+        let bed = create_test_fileset(&dir);
+        assert_eq!(detect_provider(&bed).unwrap().provider, GenotypeProvider::Plink);
+
+        std::fs::remove_file(PlinkFileset::from_path(&bed).bim).unwrap();
+        assert!(detect_provider(&bed).is_err());
+    }
+}
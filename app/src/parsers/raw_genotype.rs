@@ -0,0 +1,467 @@
+// ==============================================================================
+// raw_genotype.rs - Raw Genotype Provider Detection
+// ==============================================================================
+// Description: Common interface over consumer genotyping raw data parsers and
+//              header sniffing to pick the right parser for an upload
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Supported providers:
+//   - 23andMe (v3/v4/v5, tab-delimited, 4 columns)
+//   - AncestryDNA (tab-delimited, 5 columns with allele1/allele2)
+//   - MyHeritage (quoted CSV, RSID,CHROMOSOME,POSITION,RESULT)
+//   - FamilyTreeDNA (quoted CSV, RSID,CHROMOSOME,POSITION,RESULT)
+//   - PLINK binary filesets from research arrays (.bed/.bim/.fam)
+// ==============================================================================
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::ancestrydna::AncestryDnaParser;
+use super::genotype_csv::{is_genotype_csv_header, GenotypeCsvParser};
+use super::plink::{is_bed_file, PlinkFileset, PlinkParser};
+use super::genome23andme::{
+    group_by_chromosome, GenomeByChromosome, Genome23ParseError, Genome23Parser, Genome23Record,
+};
+use super::report::ParseReport;
+
+/// Maximum number of leading lines inspected when sniffing the provider
+const SNIFF_LINES: usize = 50;
+
+/// 23andMe chip versions are not written to the file header, so they are
+/// inferred from the number of genotyped markers once the file is parsed:
+/// v3 ~960k, v5 ~630-640k, v4 ~570-600k
+const V3_MIN_MARKERS: usize = 900_000;
+const V5_MIN_MARKERS: usize = 615_000;
+
+/// Consumer genotyping provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenotypeProvider {
+    TwentyThreeAndMe,
+    AncestryDna,
+    MyHeritage,
+    FamilyTreeDna,
+    Plink,
+}
+
+impl GenotypeProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GenotypeProvider::TwentyThreeAndMe => "23andMe",
+            GenotypeProvider::AncestryDna => "AncestryDNA",
+            GenotypeProvider::MyHeritage => "MyHeritage",
+            GenotypeProvider::FamilyTreeDna => "FamilyTreeDNA",
+            GenotypeProvider::Plink => "PLINK",
+        }
+    }
+}
+
+/// Result of sniffing a raw genotype file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedProvider {
+    pub provider: GenotypeProvider,
+    /// Chip/array version if it could be determined (e.g., "v5", "V2.0")
+    pub chip_version: Option<String>,
+}
+
+impl DetectedProvider {
+    /// Human-readable description (e.g., "23andMe v5 raw genotype data")
+    pub fn description(&self) -> String {
+        match &self.chip_version {
+            Some(version) => format!("{} {} raw genotype data", self.provider.as_str(), version),
+            None => format!("{} raw genotype data", self.provider.as_str()),
+        }
+    }
+
+    /// Fill in what can only be known after parsing the file
+    ///
+    /// The 23andMe chip version is inferred from the number of data records.
+    pub fn record_parse(&mut self, report: &ParseReport) {
+        if self.provider == GenotypeProvider::TwentyThreeAndMe {
+            self.chip_version = Some(twenty_three_and_me_version(report.records_read).to_string());
+        }
+    }
+
+    /// JSON representation for job metadata
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "provider": self.provider.as_str(),
+            "chip_version": self.chip_version,
+        })
+    }
+}
+
+/// A parser for consumer raw genotype data
+///
+/// All implementations yield `Genome23Record`s (23andMe chromosome naming,
+/// two-letter genotypes, "--" for no-calls) so downstream merge code does
+/// not need to know which provider produced the file.
+pub trait RawGenotypeSource {
+    /// Provider handled by this parser
+    fn provider(&self) -> GenotypeProvider;
+
+    /// Parse all records from a raw genotype file, with a parse report
+    fn parse_file_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError>;
+
+    /// Parse all records from a raw genotype file
+    fn parse_file(&self, path: &Path) -> Result<Vec<Genome23Record>, Genome23ParseError> {
+        Ok(self.parse_file_with_report(path)?.0)
+    }
+
+    /// Parse a raw genotype file grouped by chromosome, with a parse report
+    ///
+    /// The default implementation groups the output of `parse_file_with_report`;
+    /// parsers that can stream should override it.
+    fn parse_grouped_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(GenomeByChromosome, ParseReport), Genome23ParseError> {
+        let (records, report) = self.parse_file_with_report(path)?;
+        Ok((group_by_chromosome(records.into_iter().map(Ok))?, report))
+    }
+
+    /// Parse a raw genotype file grouped by chromosome, indexed by position
+    fn parse_grouped_by_chromosome(&self, path: &Path) -> Result<GenomeByChromosome, Genome23ParseError> {
+        Ok(self.parse_grouped_with_report(path)?.0)
+    }
+}
+
+impl RawGenotypeSource for Genome23Parser {
+    fn provider(&self) -> GenotypeProvider {
+        GenotypeProvider::TwentyThreeAndMe
+    }
+
+    fn parse_file_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        self.parse_with_report(path)
+    }
+
+    fn parse_grouped_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(GenomeByChromosome, ParseReport), Genome23ParseError> {
+        Genome23Parser::parse_grouped_with_report(self, path)
+    }
+}
+
+impl RawGenotypeSource for AncestryDnaParser {
+    fn provider(&self) -> GenotypeProvider {
+        GenotypeProvider::AncestryDna
+    }
+
+    fn parse_file_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        self.parse_with_report(path)
+    }
+}
+
+impl RawGenotypeSource for GenotypeCsvParser {
+    fn provider(&self) -> GenotypeProvider {
+        self.provider
+    }
+
+    fn parse_file_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        self.parse_with_report(path)
+    }
+}
+
+impl RawGenotypeSource for PlinkParser {
+    fn provider(&self) -> GenotypeProvider {
+        GenotypeProvider::Plink
+    }
+
+    fn parse_file_with_report(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<Genome23Record>, ParseReport), Genome23ParseError> {
+        self.parse_with_report(path)
+    }
+}
+
+/// Inspect the header/comment block of a raw genotype file to determine
+/// which provider produced it
+///
+/// # Arguments
+/// * `path` - Path to the raw genotype file
+///
+/// # Returns
+/// * `Ok(DetectedProvider)` - Detected provider and chip version
+/// * `Err(Genome23ParseError::UnsupportedFormat)` - File layout not recognized
+pub fn detect_provider(path: impl AsRef<Path>) -> Result<DetectedProvider, Genome23ParseError> {
+    // PLINK filesets are binary, so check them before reading lines
+    let is_plink_member = matches!(
+        path.as_ref().extension().and_then(|e| e.to_str()),
+        Some("bim" | "fam")
+    );
+    if is_bed_file(path.as_ref()) || is_plink_member {
+        let fileset = PlinkFileset::from_path(path.as_ref());
+        if !fileset.exists() {
+            return Err(Genome23ParseError::UnsupportedFormat(
+                "PLINK fileset is incomplete (needs .bed, .bim and .fam)".to_string(),
+            ));
+        }
+        return Ok(DetectedProvider {
+            provider: GenotypeProvider::Plink,
+            chip_version: None,
+        });
+    }
+
+    let file = File::open(path.as_ref())?;
+    let reader = BufReader::new(file);
+
+    let mut comments = Vec::new();
+    let mut first_data_line = None;
+
+    for line_result in reader.lines().take(SNIFF_LINES) {
+        let line = line_result?;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            comments.push(trimmed.to_string());
+            continue;
+        }
+
+        first_data_line = Some(trimmed.to_string());
+        break;
+    }
+
+    let comment_block = comments.join("\n");
+    let first_data_line = first_data_line.ok_or(Genome23ParseError::EmptyFile)?;
+
+    if comment_block.contains("AncestryDNA") {
+        return Ok(DetectedProvider {
+            provider: GenotypeProvider::AncestryDna,
+            chip_version: ancestry_array_version(&comments),
+        });
+    }
+
+    if comment_block.contains("MyHeritage") {
+        return Ok(DetectedProvider {
+            provider: GenotypeProvider::MyHeritage,
+            chip_version: None,
+        });
+    }
+
+    if comment_block.contains("23andMe") {
+        return Ok(DetectedProvider {
+            provider: GenotypeProvider::TwentyThreeAndMe,
+            chip_version: None,
+        });
+    }
+
+    // No provider banner: fall back to the column layout
+    if is_genotype_csv_header(&first_data_line) {
+        return Ok(DetectedProvider {
+            provider: GenotypeProvider::FamilyTreeDna,
+            chip_version: None,
+        });
+    }
+
+    match first_data_line.split('\t').count() {
+        5 => Ok(DetectedProvider {
+            provider: GenotypeProvider::AncestryDna,
+            chip_version: None,
+        }),
+        4 => Ok(DetectedProvider {
+            provider: GenotypeProvider::TwentyThreeAndMe,
+            chip_version: None,
+        }),
+        n => Err(Genome23ParseError::UnsupportedFormat(format!(
+            "Unrecognized raw genotype layout ({} tab-delimited columns)",
+            n
+        ))),
+    }
+}
+
+/// Create the parser for a detected provider
+///
+/// # Arguments
+/// * `detected` - Result of `detect_provider`
+/// * `include_chromosomes` - Chromosomes to include (empty for all)
+pub fn source_for(
+    detected: &DetectedProvider,
+    include_chromosomes: Vec<String>,
+) -> Result<Box<dyn RawGenotypeSource + Send + Sync>, Genome23ParseError> {
+    match detected.provider {
+        GenotypeProvider::TwentyThreeAndMe => {
+            // Browser downloads are occasionally truncated mid-line
+            Ok(Box::new(Genome23Parser::with_chromosomes(include_chromosomes).lenient()))
+        }
+        GenotypeProvider::AncestryDna => {
+            Ok(Box::new(AncestryDnaParser::with_chromosomes(include_chromosomes)))
+        }
+        GenotypeProvider::MyHeritage => {
+            Ok(Box::new(GenotypeCsvParser::myheritage().with_chromosomes(include_chromosomes)))
+        }
+        GenotypeProvider::FamilyTreeDna => {
+            Ok(Box::new(GenotypeCsvParser::ftdna().with_chromosomes(include_chromosomes)))
+        }
+        GenotypeProvider::Plink => {
+            Ok(Box::new(PlinkParser::with_chromosomes(include_chromosomes)))
+        }
+    }
+}
+
+/// Detect the provider of a raw genotype file and create a matching parser
+///
+/// # Arguments
+/// * `path` - Path to the raw genotype file
+/// * `include_chromosomes` - Chromosomes to include (empty for all)
+pub fn open_raw_genotype_source(
+    path: impl AsRef<Path>,
+    include_chromosomes: Vec<String>,
+) -> Result<(DetectedProvider, Box<dyn RawGenotypeSource + Send + Sync>), Genome23ParseError> {
+    let detected = detect_provider(path)?;
+    let source = source_for(&detected, include_chromosomes)?;
+    Ok((detected, source))
+}
+
+/// Normalize a provider chromosome code to 23andMe naming
+///
+/// Numeric codes follow the AncestryDNA/PLINK convention: 23=X, 24=Y,
+/// 25=X pseudoautosomal region, 26=MT. "XY" (PAR) is reported as "X".
+/// Returns None for unrecognized codes.
+pub(crate) fn normalize_chromosome(code: &str) -> Option<String> {
+    match code {
+        "23" | "25" | "X" | "XY" => Some("X".to_string()),
+        "24" | "Y" => Some("Y".to_string()),
+        "26" | "MT" | "M" => Some("MT".to_string()),
+        _ => match code.parse::<u8>() {
+            Ok(n) if (1..=22).contains(&n) => Some(n.to_string()),
+            _ => None,
+        },
+    }
+}
+
+/// Extract the array version from AncestryDNA comments
+/// (e.g., "#Data was collected using AncestryDNA array version: V2.0")
+fn ancestry_array_version(comments: &[String]) -> Option<String> {
+    comments.iter().find_map(|line| {
+        let idx = line.find("array version:")?;
+        let version = line[idx + "array version:".len()..].trim();
+        if version.is_empty() {
+            None
+        } else {
+            Some(version.to_string())
+        }
+    })
+}
+
+/// Infer the 23andMe chip version from the number of data lines
+fn twenty_three_and_me_version(markers: usize) -> &'static str {
+    if markers >= V3_MIN_MARKERS {
+        "v3"
+    } else if markers >= V5_MIN_MARKERS {
+        "v5"
+    } else {
+        "v4"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_file(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_detect_23andme() {
+        let contents = "\
+# This data file generated by 23andMe at: Mon Jan 01 00:00:00 2024
+# rsid\tchromosome\tposition\tgenotype
+rs548049170\t1\t69869\tTT
+rs9283150\t1\t565508\tAA
+";
+        let file = create_test_file(contents);
+        let mut detected = detect_provider(file.path()).unwrap();
+
+        assert_eq!(detected.provider, GenotypeProvider::TwentyThreeAndMe);
+        // The chip version needs the marker count from the parse
+        assert_eq!(detected.chip_version, None);
+
+        let (_, report) = source_for(&detected, Vec::new())
+            .unwrap()
+            .parse_file_with_report(file.path())
+            .unwrap();
+        detected.record_parse(&report);
+        // Small files fall below the v5 marker count
+        assert_eq!(detected.chip_version.as_deref(), Some("v4"));
+        assert_eq!(detected.description(), "23andMe v4 raw genotype data");
+
+        assert_eq!(twenty_three_and_me_version(960_000), "v3");
+        assert_eq!(twenty_three_and_me_version(632_000), "v5");
+        assert_eq!(twenty_three_and_me_version(585_000), "v4");
+    }
+
+    #[test]
+    fn test_detect_ancestrydna_with_version() {
+        let contents = "\
+#AncestryDNA raw data download
+#Data was collected using AncestryDNA array version: V2.0
+rsid\tchromosome\tposition\tallele1\tallele2
+rs3131972\t1\t752721\tA\tG
+";
+        let file = create_test_file(contents);
+        let detected = detect_provider(file.path()).unwrap();
+
+        assert_eq!(detected.provider, GenotypeProvider::AncestryDna);
+        assert_eq!(detected.chip_version.as_deref(), Some("V2.0"));
+
+        let source = source_for(&detected, Vec::new()).unwrap();
+        let records = source.parse_file(file.path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].genotype, "AG");
+    }
+
+    #[test]
+    fn test_detect_csv_providers() {
+        let myheritage = create_test_file(
+            "# MyHeritage DNA raw data.\nRSID,CHROMOSOME,POSITION,RESULT\n\"rs1\",\"1\",\"100\",\"AA\"\n",
+        );
+        assert_eq!(
+            detect_provider(myheritage.path()).unwrap().provider,
+            GenotypeProvider::MyHeritage
+        );
+
+        let ftdna = create_test_file(
+            "\"RSID\",\"CHROMOSOME\",\"POSITION\",\"RESULT\"\n\"rs1\",\"1\",\"100\",\"AA\"\n",
+        );
+        let (detected, source) = open_raw_genotype_source(ftdna.path(), Vec::new()).unwrap();
+        assert_eq!(detected.provider, GenotypeProvider::FamilyTreeDna);
+        assert_eq!(source.provider(), GenotypeProvider::FamilyTreeDna);
+        assert_eq!(source.parse_file(ftdna.path()).unwrap()[0].genotype, "AA");
+    }
+
+    #[test]
+    fn test_detect_unrecognized_layout() {
+        let file = create_test_file("rs1,1,100\n");
+
+        match detect_provider(file.path()).unwrap_err() {
+            Genome23ParseError::UnsupportedFormat(_) => {}
+            _ => panic!("Expected UnsupportedFormat error"),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+// ==============================================================================
+// report.rs - Parse Diagnostics
+// ==============================================================================
+// Description: Structured per-file parse reports (counts by category and the
+//              first offending lines) shared by the VCF, raw genotype and PGS
+//              parsers
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Maximum number of individual issues kept per report
+///
+/// Every issue is still counted in `issue_counts`; only the first
+/// `MAX_REPORTED_ISSUES` keep their line number and reason.
+pub const MAX_REPORTED_ISSUES: usize = 25;
+
+/// Category of a rejected line or record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCategory {
+    /// Line could not be read or decoded
+    ReadError,
+    /// Wrong number of fields or malformed line
+    InvalidFormat,
+    /// Chromosome name not recognized
+    InvalidChromosome,
+    /// Position is not a valid integer
+    InvalidPosition,
+    /// Dosage outside 0.0-2.0
+    InvalidDosage,
+    /// Score or other numeric value could not be parsed or is not finite
+    InvalidValue,
+    /// Required field (ALT, DS, ...) is missing
+    MissingField,
+    /// Record shares an rsID or position with an earlier record
+    Duplicate,
+    /// Variant uses an effect model the scorer does not support
+    /// (dominant, recessive or haplotype weights)
+    Unsupported,
+}
+
+impl IssueCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueCategory::ReadError => "read_error",
+            IssueCategory::InvalidFormat => "invalid_format",
+            IssueCategory::InvalidChromosome => "invalid_chromosome",
+            IssueCategory::InvalidPosition => "invalid_position",
+            IssueCategory::InvalidDosage => "invalid_dosage",
+            IssueCategory::InvalidValue => "invalid_value",
+            IssueCategory::MissingField => "missing_field",
+            IssueCategory::Duplicate => "duplicate",
+            IssueCategory::Unsupported => "unsupported",
+        }
+    }
+}
+
+/// A single rejected line or record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseIssue {
+    /// Line number (VCF: record number after the header)
+    pub line: usize,
+    /// Issue category
+    pub category: IssueCategory,
+    /// Human-readable reason
+    pub reason: String,
+}
+
+/// Diagnostics for one parsed file
+///
+/// Explains why records were dropped: rejected lines by category (with the
+/// first few offending lines), quality and chromosome filtering, and no-calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParseReport {
+    /// File name the report describes
+    pub source: String,
+
+    /// Data records read (excluding headers and comments)
+    pub records_read: usize,
+
+    /// Records returned by the parser
+    pub records_accepted: usize,
+
+    /// Accepted genotype records that are no-calls ("--")
+    pub no_calls: usize,
+
+    /// Records dropped by the imputation quality filter
+    pub filtered_by_quality: usize,
+
+    /// Records dropped by the chromosome filter
+    pub filtered_by_chromosome: usize,
+
+    /// Rejected records by category
+    pub issue_counts: BTreeMap<IssueCategory, usize>,
+
+    /// First `MAX_REPORTED_ISSUES` rejected records with reasons
+    pub issues: Vec<ParseIssue>,
+}
+
+impl ParseReport {
+    /// Create an empty report for a source file name
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create an empty report named after a file's name
+    pub fn for_path(path: &Path) -> Self {
+        Self::new(
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+        )
+    }
+
+    /// Record a rejected line or record
+    pub fn record_issue(&mut self, line: usize, category: IssueCategory, reason: impl Into<String>) {
+        *self.issue_counts.entry(category).or_insert(0) += 1;
+
+        if self.issues.len() < MAX_REPORTED_ISSUES {
+            self.issues.push(ParseIssue {
+                line,
+                category,
+                reason: reason.into(),
+            });
+        }
+    }
+
+    /// Total number of rejected records
+    pub fn issue_count(&self) -> usize {
+        self.issue_counts.values().sum()
+    }
+
+    /// One-line summary for logging
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}: {} read, {} accepted",
+            self.source, self.records_read, self.records_accepted
+        );
+
+        if self.no_calls > 0 {
+            summary.push_str(&format!(", {} no-calls", self.no_calls));
+        }
+        if self.filtered_by_quality > 0 {
+            summary.push_str(&format!(", {} filtered by quality", self.filtered_by_quality));
+        }
+        if self.filtered_by_chromosome > 0 {
+            summary.push_str(&format!(", {} filtered by chromosome", self.filtered_by_chromosome));
+        }
+        for (category, count) in &self.issue_counts {
+            summary.push_str(&format!(", {} {}", count, category.as_str()));
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_limit_and_counts() {
+        let mut report = ParseReport::for_path(Path::new("/uploads/genome.txt"));
+        assert_eq!(report.source, "genome.txt");
+
+        for line in 0..MAX_REPORTED_ISSUES + 5 {
+            report.record_issue(line, IssueCategory::InvalidPosition, "bad position");
+        }
+        report.record_issue(99, IssueCategory::InvalidFormat, "3 fields");
+
+        assert_eq!(report.issue_count(), MAX_REPORTED_ISSUES + 6);
+        assert_eq!(report.issues.len(), MAX_REPORTED_ISSUES);
+        assert_eq!(report.issue_counts[&IssueCategory::InvalidPosition], MAX_REPORTED_ISSUES + 5);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["issue_counts"]["invalid_format"], 1);
+        assert!(report.summary().contains("1 invalid_format"));
+    }
+}
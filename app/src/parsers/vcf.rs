@@ -12,10 +12,20 @@
 // - noodles-vcf: https://docs.rs/noodles-vcf/0.81.0/noodles_vcf/
 // ==============================================================================
 
+use noodles_core::{Position, Region};
+use noodles_csi::{self as csi, BinningIndex};
+use noodles_tabix as tabix;
 use noodles_vcf as vcf;
 use noodles_vcf::variant::record::{AlternateBases, Ids};
-use std::path::Path;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
+use tracing::debug;
+
+use super::report::{IssueCategory, ParseReport};
+use crate::chromosomes::{self, chromosome_name};
+use crate::genome_build::GenomeBuild;
+use crate::models::{DataSource, Genotype, GenotypeParseError, SampleData};
 
 /// Parsed VCF record with relevant fields for genetic data processing
 #[derive(Debug, Clone)]
@@ -23,12 +33,15 @@ pub struct VCFRecord {
     /// rsID (e.g., "rs12345") or generated ID (e.g., "chr1:10177:A:G")
     pub rsid: String,
 
-    /// Chromosome number (1-22)
+    /// Chromosome code (1-22, 23=X, 24=Y, 26=MT)
     pub chromosome: u8,
 
     /// Base pair position on chromosome
     pub position: u64,
 
+    /// Build of `position`, detected from the file header (see `detect_build`)
+    pub genome_build: GenomeBuild,
+
     /// Reference allele (e.g., "A")
     pub ref_allele: String,
 
@@ -45,6 +58,64 @@ pub struct VCFRecord {
     /// Imputation quality (DR2 R-squared, 0.0-1.0)
     /// None if not available
     pub imputation_quality: Option<f64>,
+
+    /// Per-sample FORMAT values, in header sample order
+    /// (names in `VCFParser::sample_names`; `dosage` above is the last sample)
+    pub samples: Vec<VCFSampleValues>,
+}
+
+/// FORMAT values for one sample at one (biallelic) VCF record
+///
+/// Values of multi-allelic records are split per ALT allele along with
+/// the record, so every field refers to this record's ALT allele.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VCFSampleValues {
+    /// Genotype (GT), e.g. "0|1"; other ALT alleles of a split record become 0
+    pub genotype: Option<Genotype>,
+
+    /// ALT allele dosage (DS, or derived from HDS/GP), 0.0-2.0
+    pub dosage: Option<f64>,
+
+    /// Haplotype ALT allele dosages (HDS), one value per haplotype
+    pub haplotype_dosages: Option<Vec<f64>>,
+
+    /// Genotype probabilities (GP) for 0, 1 and 2 copies of the ALT allele
+    /// (0 and 1 copies for haploid samples)
+    pub genotype_probabilities: Option<Vec<f64>>,
+}
+
+impl VCFRecord {
+    /// Build merge-ready sample data for one sample of this record
+    ///
+    /// # Arguments
+    /// * `index` - Sample index in header order (see `VCFParser::sample_names`)
+    /// * `sample_id` - Sample identifier to use in the merged output
+    /// * `ploidy` - Sample ploidy at this site, used to call the genotype
+    ///   from the dosage when the record has no GT
+    ///
+    /// # Returns
+    /// * `Some(SampleData)` - Sample has a dosage at this record
+    /// * `None` - Sample index out of range or no dosage available
+    pub fn sample_data(&self, index: usize, sample_id: &str, ploidy: u8) -> Option<SampleData> {
+        let values = self.samples.get(index)?;
+        let dosage = values.dosage?;
+
+        let source = if self.imputation_quality.unwrap_or(1.0) < 0.3 {
+            DataSource::ImputedLowQual
+        } else {
+            DataSource::Imputed
+        };
+
+        Some(SampleData {
+            sample_id: sample_id.to_string(),
+            genotype: values
+                .genotype
+                .unwrap_or_else(|| Genotype::from_dosage(dosage, ploidy)),
+            dosage,
+            source,
+            imputation_quality: self.imputation_quality,
+        })
+    }
 }
 
 /// VCF parsing errors
@@ -68,10 +139,34 @@ pub enum VCFParseError {
     #[error("Invalid chromosome: {0}")]
     InvalidChromosome(String),
 
+    #[error(transparent)]
+    InvalidGenotype(#[from] GenotypeParseError),
+
+    #[error("VCF index error: {0}")]
+    IndexError(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
 
+impl VCFParseError {
+    /// Parse report category for a rejected record
+    pub fn category(&self) -> IssueCategory {
+        match self {
+            VCFParseError::RecordError(_) | VCFParseError::InvalidGenotype(_) => {
+                IssueCategory::InvalidFormat
+            }
+            VCFParseError::MissingField(_) => IssueCategory::MissingField,
+            VCFParseError::InvalidDosage(_) => IssueCategory::InvalidDosage,
+            VCFParseError::InvalidChromosome(_) => IssueCategory::InvalidChromosome,
+            VCFParseError::FileOpenError(_)
+            | VCFParseError::HeaderError(_)
+            | VCFParseError::IndexError(_)
+            | VCFParseError::IoError(_) => IssueCategory::ReadError,
+        }
+    }
+}
+
 /// VCF parser with configuration options
 pub struct VCFParser {
     /// Minimum imputation quality (DR2) to accept
@@ -86,6 +181,18 @@ pub struct VCFParser {
 
     /// Count of error records (for reporting)
     pub error_count: usize,
+
+    /// Count of multi-allelic records split into biallelic records (for reporting)
+    pub split_multiallelic_count: usize,
+
+    /// Sample names from the header of the last parsed file
+    pub sample_names: Vec<String>,
+
+    /// Genome build detected from the header of the last parsed file
+    pub genome_build: GenomeBuild,
+
+    /// Diagnostics for the last parsed file (rejected records and filtering)
+    pub report: ParseReport,
 }
 
 impl Default for VCFParser {
@@ -95,6 +202,10 @@ impl Default for VCFParser {
             max_errors: 1000,  // Fail if >1000 bad records
             skipped_count: 0,
             error_count: 0,
+            split_multiallelic_count: 0,
+            sample_names: Vec::new(),
+            genome_build: GenomeBuild::Unknown,
+            report: ParseReport::default(),
         }
     }
 }
@@ -119,6 +230,9 @@ impl VCFParser {
 
     /// Parse VCF file and return vector of records
     ///
+    /// Multi-allelic records are split into one biallelic `VCFRecord` per ALT
+    /// allele, each carrying the dosage of that ALT allele.
+    ///
     /// # Arguments
     /// * `path` - Path to VCF file (can be .vcf or .vcf.gz)
     ///
@@ -135,6 +249,7 @@ impl VCFParser {
     ///
     /// let records = parser.parse("chr22.dose.vcf.gz")?;
     /// println!("Parsed {} SNPs", records.len());
+    /// # Ok::<(), genetics_processor::parsers::VCFParseError>(())
     /// ```
     pub fn parse(&mut self, path: impl AsRef<Path>) -> Result<Vec<VCFRecord>, VCFParseError> {
         let path = path.as_ref();
@@ -149,56 +264,201 @@ impl VCFParser {
             .read_header()
             .map_err(|e| VCFParseError::HeaderError(format!("{}", e)))?;
 
+        self.sample_names = header.sample_names().iter().cloned().collect();
+        self.genome_build = detect_build(&header);
+
         // Parse records
         let mut vcf_records = Vec::new();
-        self.skipped_count = 0;
-        self.error_count = 0;
+        self.reset(path);
 
         for (line_num, result) in reader.records().enumerate() {
+            self.report.records_read += 1;
+
             match result {
                 Ok(record) => {
-                    match self.parse_record(&record, &header) {
-                        Ok(Some(vcf_record)) => vcf_records.push(vcf_record),
-                        Ok(None) => self.skipped_count += 1,  // Filtered by quality
-                        Err(e) => {
-                            eprintln!("Warning: Line {}: {}", line_num + 1, e);
-                            self.error_count += 1;
-
-                            if self.error_count > self.max_errors {
-                                return Err(VCFParseError::RecordError(
-                                    format!("Too many errors ({} > {})", self.error_count, self.max_errors)
-                                ));
-                            }
-                        }
+                    if let Err(e) = self.push_record(&record, &header, &mut vcf_records) {
+                        self.count_error(line_num + 1, &e)?;
                     }
                 }
                 Err(e) => {
-                    eprintln!("Warning: Line {}: Failed to read record: {}", line_num + 1, e);
-                    self.error_count += 1;
-
-                    if self.error_count > self.max_errors {
-                        return Err(VCFParseError::RecordError(
-                            format!("Too many errors ({} > {})", self.error_count, self.max_errors)
-                        ));
-                    }
+                    self.count_error(line_num + 1, &VCFParseError::IoError(e))?;
                 }
             }
         }
 
+        self.report.records_accepted = vcf_records.len();
         Ok(vcf_records)
     }
 
-    /// Parse a single VCF record
+    /// Query records overlapping a region of a bgzipped, indexed VCF file
+    ///
+    /// Uses the `.tbi` or `.csi` index next to the file (e.g.
+    /// `chr22.dose.vcf.gz.tbi`) to read only the blocks covering the region,
+    /// so a single gene can be checked without decompressing the whole file.
+    /// Uploads without an index get one built on the first query. Records
+    /// are split and filtered exactly as in `parse`.
+    ///
+    /// # Arguments
+    /// * `path` - Path to a bgzipped VCF file (.vcf.gz)
+    /// * `chrom` - Chromosome name ("22", "chr22", "X", "MT", ...), matched
+    ///   against the index regardless of "chr" prefix
+    /// * `start` - Region start (1-based, inclusive)
+    /// * `end` - Region end (1-based, inclusive)
+    ///
+    /// # Returns
+    /// * `Result<Vec<VCFRecord>, VCFParseError>` - Records in the region or error
+    ///
+    /// # Example
+    /// ```no_run
+    /// use genetics_processor::parsers::VCFParser;
+    ///
+    /// let mut parser = VCFParser::new();
+    ///
+    /// // APOE region on GRCh37
+    /// let records = parser.query("chr19.dose.vcf.gz", "19", 45_409_011, 45_412_650)?;
+    /// println!("Found {} variants in APOE", records.len());
+    /// # Ok::<(), genetics_processor::parsers::VCFParseError>(())
+    /// ```
+    pub fn query(
+        &mut self,
+        path: impl AsRef<Path>,
+        chrom: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<VCFRecord>, VCFParseError> {
+        let path = path.as_ref();
+
+        Self::ensure_index(path)?;
+        let index = read_index(path)?;
+        let region = build_region(&index, chrom, start, end)?;
+
+        let mut reader = vcf::io::indexed_reader::Builder::default()
+            .set_index(index)
+            .build_from_path(path)
+            .map_err(|e| VCFParseError::FileOpenError(format!("{}: {}", path.display(), e)))?;
+
+        // Read header
+        let header = reader
+            .read_header()
+            .map_err(|e| VCFParseError::HeaderError(format!("{}", e)))?;
+
+        self.sample_names = header.sample_names().iter().cloned().collect();
+        self.genome_build = detect_build(&header);
+
+        let query = reader
+            .query(&header, &region)
+            .map_err(|e| VCFParseError::IndexError(format!("Failed to query {}: {}", region, e)))?;
+
+        let mut vcf_records = Vec::new();
+        self.reset(path);
+
+        for (record_num, result) in query.enumerate() {
+            let record = result?;
+            self.report.records_read += 1;
+
+            if let Err(e) = self.push_record(&record, &header, &mut vcf_records) {
+                self.count_error(record_num + 1, &e)?;
+            }
+        }
+
+        self.report.records_accepted = vcf_records.len();
+        Ok(vcf_records)
+    }
+
+    /// Reset counters and the parse report before reading a file
+    fn reset(&mut self, path: &Path) {
+        self.skipped_count = 0;
+        self.error_count = 0;
+        self.split_multiallelic_count = 0;
+        self.report = ParseReport::for_path(path);
+    }
+
+    /// Parse a record and append its biallelic records that pass the quality filter
+    fn push_record(
+        &mut self,
+        record: &vcf::Record,
+        header: &vcf::Header,
+        vcf_records: &mut Vec<VCFRecord>,
+    ) -> Result<(), VCFParseError> {
+        let split_records = self.parse_record(record, header)?;
+
+        if split_records.len() > 1 {
+            self.split_multiallelic_count += 1;
+        }
+
+        for vcf_record in split_records {
+            if self.passes_quality(&vcf_record) {
+                vcf_records.push(vcf_record);
+            } else {
+                self.skipped_count += 1;  // Filtered by quality
+                self.report.filtered_by_quality += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record a bad record in the report, failing once the error budget is exceeded
+    fn count_error(&mut self, line: usize, error: &VCFParseError) -> Result<(), VCFParseError> {
+        debug!("{}: record {}: {}", self.report.source, line, error);
+        self.report.record_issue(line, error.category(), error.to_string());
+        self.error_count += 1;
+
+        if self.error_count > self.max_errors {
+            return Err(VCFParseError::RecordError(
+                format!("Too many errors ({} > {})", self.error_count, self.max_errors)
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Build a tabix (.tbi) index for a bgzipped VCF file
+    ///
+    /// Writes `<path>.tbi` next to the file. The file must be BGZF-compressed
+    /// (as produced by bgzip or the imputation servers); plain gzip files
+    /// cannot be indexed.
+    ///
+    /// # Returns
+    /// * `Ok(PathBuf)` - Path of the written index
+    /// * `Err(VCFParseError)` - File could not be read or indexed
+    pub fn build_index(path: impl AsRef<Path>) -> Result<PathBuf, VCFParseError> {
+        let path = path.as_ref();
+        let index_path = index_path(path, "tbi");
+
+        let index = vcf::fs::index(path)
+            .map_err(|e| VCFParseError::IndexError(format!("Failed to index {}: {}", path.display(), e)))?;
+
+        tabix::fs::write(&index_path, &index)?;
+
+        Ok(index_path)
+    }
+
+    /// Return the existing `.tbi`/`.csi` index for a VCF, building one if missing
+    ///
+    /// Called by `query`, so uploads that arrive without an index are only
+    /// indexed when something needs a region.
+    pub fn ensure_index(path: impl AsRef<Path>) -> Result<PathBuf, VCFParseError> {
+        let path = path.as_ref();
+
+        ["tbi", "csi"]
+            .iter()
+            .map(|ext| index_path(path, ext))
+            .find(|index_path| index_path.exists())
+            .map(Ok)
+            .unwrap_or_else(|| Self::build_index(path))
+    }
+
+    /// Parse a single VCF record into one biallelic record per ALT allele
     ///
     /// Returns:
-    /// - Ok(Some(record)) if successfully parsed and passes quality filter
-    /// - Ok(None) if filtered by quality threshold
+    /// - Ok(records) with one record per ALT allele (quality filter not applied)
     /// - Err if parsing failed
     fn parse_record(
         &self,
         record: &vcf::Record,
         header: &vcf::Header,
-    ) -> Result<Option<VCFRecord>, VCFParseError> {
+    ) -> Result<Vec<VCFRecord>, VCFParseError> {
         // Extract chromosome
         let chrom_str = record.reference_sequence_name();
         let chromosome = self.parse_chromosome(chrom_str)?;
@@ -210,72 +470,80 @@ impl VCFParser {
             None => return Err(VCFParseError::MissingField("Position".to_string())),
         };
 
-        // Extract rsID (or generate one if missing)
-        let rsid = self.extract_rsid(record, chromosome, position);
-
         // Extract REF allele
         let ref_allele = record.reference_bases().to_string();
 
-        // Extract ALT allele (take first if multiple)
-        let alt_alleles = record.alternate_bases();
-        let alt_allele = if alt_alleles.is_empty() {
-            return Err(VCFParseError::MissingField("ALT allele".to_string()));
-        } else {
-            alt_alleles.iter().next().unwrap()
-                .map_err(|e| VCFParseError::RecordError(format!("Failed to get ALT allele: {}", e)))?
-                .to_string()
-        };
-
-        // Extract dosage (DS field from FORMAT column)
-        let dosage = self.extract_dosage(record, header)?;
+        // Extract ALT alleles
+        let alt_alleles = record
+            .alternate_bases()
+            .iter()
+            .map(|alt| {
+                alt.map(str::to_string)
+                    .map_err(|e| VCFParseError::RecordError(format!("Failed to get ALT allele: {}", e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Validate dosage range
-        if !(0.0..=2.0).contains(&dosage) {
-            return Err(VCFParseError::InvalidDosage(dosage));
+        if alt_alleles.is_empty() {
+            return Err(VCFParseError::MissingField("ALT allele".to_string()));
         }
 
-        // Extract imputation quality (R2 from INFO)
-        let imputation_quality = self.extract_dr2(record, header);
+        // Extract per-ALT, per-sample FORMAT values (GT, DS, HDS, GP)
+        let sample_values = self.extract_samples(record, header, alt_alleles.len())?;
 
-        // Apply quality filter
-        if let Some(quality) = imputation_quality {
-            if quality < self.min_quality {
-                return Ok(None);  // Skip low-quality SNPs
+        // Extract imputation quality (R2 from INFO, per ALT if given per ALT)
+        let qualities = self.extract_dr2(record, alt_alleles.len());
+
+        let mut records = Vec::with_capacity(alt_alleles.len());
+        for ((alt_allele, samples), imputation_quality) in
+            alt_alleles.into_iter().zip(sample_values).zip(qualities)
+        {
+            // Spanning deletions ('*') cannot be matched against the reference panel
+            if alt_allele == "*" {
+                continue;
             }
+
+            // The last sample is the user; it must have a dosage
+            let dosage = samples
+                .last()
+                .and_then(|values| values.dosage)
+                .ok_or_else(|| VCFParseError::MissingField("DS not found in FORMAT".to_string()))?;
+
+            records.push(VCFRecord {
+                rsid: self.extract_rsid(record, chromosome, position, &alt_allele),
+                chromosome,
+                position,
+                genome_build: self.genome_build,
+                ref_allele: ref_allele.clone(),
+                alt_allele,
+                dosage,
+                imputation_quality,
+                samples,
+            });
         }
 
-        Ok(Some(VCFRecord {
-            rsid,
-            chromosome,
-            position,
-            ref_allele,
-            alt_allele,
-            dosage,
-            imputation_quality,
-        }))
+        Ok(records)
+    }
+
+    /// Check a record against the minimum imputation quality
+    ///
+    /// Records without an imputation quality are always kept.
+    fn passes_quality(&self, record: &VCFRecord) -> bool {
+        record
+            .imputation_quality
+            .map_or(true, |quality| quality >= self.min_quality)
     }
 
     /// Parse chromosome string to u8
+    ///
+    /// Handles "chr1"/"1" style names plus X, Y and MT (see `chromosomes`).
     fn parse_chromosome(&self, chrom: &str) -> Result<u8, VCFParseError> {
-        // Handle "chr1" or "1" format
-        let chrom_num = chrom.trim_start_matches("chr");
-
-        chrom_num
-            .parse::<u8>()
-            .map_err(|_| VCFParseError::InvalidChromosome(chrom.to_string()))
-            .and_then(|n| {
-                if (1..=22).contains(&n) {
-                    Ok(n)
-                } else {
-                    Err(VCFParseError::InvalidChromosome(format!(
-                        "{} (must be 1-22)", chrom
-                    )))
-                }
-            })
+        chromosomes::parse_chromosome(chrom).ok_or_else(|| {
+            VCFParseError::InvalidChromosome(format!("{} (must be 1-22, X, Y or MT)", chrom))
+        })
     }
 
-    /// Extract rsID or generate pseudo-ID
-    fn extract_rsid(&self, record: &vcf::Record, chromosome: u8, position: u64) -> String {
+    /// Extract rsID or generate pseudo-ID for the given ALT allele
+    fn extract_rsid(&self, record: &vcf::Record, chromosome: u8, position: u64, alt_allele: &str) -> String {
         // Get IDs from record
         let ids = record.ids();
 
@@ -283,109 +551,546 @@ impl VCFParser {
             // Generate pseudo-ID for novel variants
             // Format: chr{CHROM}:{POS}:{REF}:{ALT}
             let ref_bases = record.reference_bases();
-            let alt_bases = record.alternate_bases();
-            let alt_str = if let Some(alt_result) = alt_bases.iter().next() {
-                alt_result.unwrap_or("N")
-            } else {
-                "N"
-            };
 
-            format!("chr{}:{}:{}:{}", chromosome, position, ref_bases, alt_str)
+            format!("chr{}:{}:{}:{}", chromosome_name(chromosome), position, ref_bases, alt_allele)
         } else {
             // Use first rsID
             ids.iter().next().unwrap().to_string()
         }
     }
 
-    /// Extract dosage (DS) field from FORMAT column
+    /// Extract FORMAT values for every sample, split per ALT allele
     ///
-    /// Parses the raw VCF line to extract DS value from last sample
-    /// Uses Debug format to access samples string from noodles Record
-    fn extract_dosage(&self, record: &vcf::Record, _header: &vcf::Header) -> Result<f64, VCFParseError> {
-        let record_str = format!("{:?}", record);
-
-        // Find the samples field: samples: Samples("...")
-        let samples_prefix = "samples: Samples(\"";
-        let start_idx = record_str.find(samples_prefix)
-            .ok_or_else(|| VCFParseError::RecordError("Samples field not found in debug output".to_string()))?
-            + samples_prefix.len();
-
-        // Find the closing quote
-        let end_idx = record_str[start_idx..].find("\")")
-            .ok_or_else(|| VCFParseError::RecordError("Samples field end not found".to_string()))?
-            + start_idx;
-
-        // Extract samples string (FORMAT + all sample data, tab-separated)
-        let samples_str = &record_str[start_idx..end_idx];
-
-        // Split by tabs (in debug format they're literal \t, not escaped)
-        let fields: Vec<&str> = samples_str.split("\\t").collect();
-
-        if fields.is_empty() {
-            return Err(VCFParseError::MissingField("No sample data found".to_string()));
+    /// Returns one entry per ALT allele, each holding one `VCFSampleValues`
+    /// per sample in header order. Dosage sources, in order of preference:
+    /// - DS with one value per ALT allele
+    /// - HDS (haplotype dosages): summed over haplotypes for each ALT allele
+    /// - GP (genotype probabilities): expected ALT allele count
+    fn extract_samples(
+        &self,
+        record: &vcf::Record,
+        _header: &vcf::Header,
+        alt_count: usize,
+    ) -> Result<Vec<Vec<VCFSampleValues>>, VCFParseError> {
+        // Samples field is FORMAT followed by tab-separated sample columns
+        let samples = record.samples();
+        let fields: Vec<&str> = samples.as_ref().split('\t').collect();
+
+        // fields[0] is FORMAT, fields[1..] are samples
+        if fields.len() < 2 {
+            return Err(VCFParseError::MissingField("No sample columns found".to_string()));
         }
 
-        // First field is FORMAT
-        let format = fields[0];
+        let format_keys: Vec<&str> = fields[0].split(':').collect();
+        let mut per_alt: Vec<Vec<VCFSampleValues>> =
+            vec![Vec::with_capacity(fields.len() - 1); alt_count];
 
-        // Find DS position in FORMAT
-        let format_keys: Vec<&str> = format.split(':').collect();
-        let ds_index = format_keys.iter().position(|&k| k == "DS")
-            .ok_or_else(|| VCFParseError::MissingField("DS not found in FORMAT".to_string()))?;
+        for column in &fields[1..] {
+            let sample_values: Vec<&str> = column.split(':').collect();
 
-        // Last sample is at the end (fields[0] is FORMAT, fields[1..] are samples)
-        if fields.len() < 2 {
-            return Err(VCFParseError::MissingField("No sample columns found".to_string()));
+            // Look up a FORMAT field in this sample (missing values are None)
+            let field = |key: &str| -> Option<&str> {
+                format_keys
+                    .iter()
+                    .position(|&k| k == key)
+                    .and_then(|idx| sample_values.get(idx).copied())
+                    .filter(|value| *value != ".")
+            };
+
+            let genotype = field("GT").map(str::parse::<Genotype>).transpose()?;
+            let ds = field("DS").map(|v| parse_float_list("DS", v)).transpose()?;
+            let hds = field("HDS").map(|v| parse_float_list("HDS", v)).transpose()?;
+            let gp = field("GP").map(|v| parse_float_list("GP", v)).transpose()?;
+
+            let dosages = sample_dosages(ds.as_deref(), hds.as_deref(), gp.as_deref(), alt_count)?;
+
+            for (alt_idx, values) in per_alt.iter_mut().enumerate() {
+                let allele = alt_idx + 1;
+                let dosage = dosages.as_ref().map(|d| d[alt_idx]);
+
+                // Validate dosage range
+                if let Some(dosage) = dosage {
+                    if !(0.0..=2.0).contains(&dosage) {
+                        return Err(VCFParseError::InvalidDosage(dosage));
+                    }
+                }
+
+                values.push(VCFSampleValues {
+                    genotype: genotype.map(|gt| if alt_count > 1 { gt.for_alt_allele(allele) } else { gt }),
+                    dosage,
+                    haplotype_dosages: hds.as_deref().and_then(|h| split_hds(h, alt_count, allele)),
+                    genotype_probabilities: gp.as_deref().and_then(|g| split_gp(g, alt_count, allele)),
+                });
+            }
         }
-        let last_sample = fields[fields.len() - 1];
 
-        // Extract DS value from sample
-        let sample_values: Vec<&str> = last_sample.split(':').collect();
+        Ok(per_alt)
+    }
+
+    /// Extract R2 (imputation quality) from INFO field, one value per ALT allele
+    ///
+    /// Michigan Imputation Server uses "R2" (not "DR2") for imputation quality;
+    /// Beagle writes "DR2" with one value per ALT allele. A single value is
+    /// applied to every ALT allele.
+    fn extract_dr2(&self, record: &vcf::Record, alt_count: usize) -> Vec<Option<f64>> {
+        let info = record.info();
+
+        // Split INFO field by semicolons
+        for field in info.as_ref().split(';') {
+            let Some(value) = field
+                .strip_prefix("R2=")
+                .or_else(|| field.strip_prefix("DR2="))
+            else {
+                continue;
+            };
 
-        if ds_index >= sample_values.len() {
-            return Err(VCFParseError::MissingField("DS index out of bounds".to_string()));
+            if let Ok(values) = parse_float_list("R2", value) {
+                if values.len() == alt_count {
+                    return values.into_iter().map(Some).collect();
+                } else if values.len() == 1 {
+                    return vec![Some(values[0]); alt_count];
+                }
+            }
         }
 
-        let ds_str = sample_values[ds_index];
+        vec![None; alt_count]
+    }
+}
 
-        // Parse as float
-        ds_str.parse::<f64>()
-            .map_err(|e| VCFParseError::RecordError(format!("Failed to parse DS '{}' as f64: {}", ds_str, e)))
+/// Path of an index file next to a VCF (e.g. "chr1.vcf.gz" -> "chr1.vcf.gz.tbi")
+fn index_path(path: &Path, extension: &str) -> PathBuf {
+    let mut index_path = OsString::from(path);
+    index_path.push(".");
+    index_path.push(extension);
+    PathBuf::from(index_path)
+}
+
+/// Read the tabix (.tbi) or CSI (.csi) index next to a VCF file
+fn read_index(path: &Path) -> Result<Box<dyn BinningIndex>, VCFParseError> {
+    let tbi_path = index_path(path, "tbi");
+    if tbi_path.exists() {
+        let index = tabix::fs::read(&tbi_path)
+            .map_err(|e| VCFParseError::IndexError(format!("{}: {}", tbi_path.display(), e)))?;
+        return Ok(Box::new(index));
     }
 
-    /// Extract R2 (imputation quality) from INFO field
-    ///
-    /// Michigan Imputation Server uses "R2" (not "DR2") for imputation quality
-    fn extract_dr2(&self, record: &vcf::Record, _header: &vcf::Header) -> Option<f64> {
-        let record_str = format!("{:?}", record);
+    let csi_path = index_path(path, "csi");
+    if csi_path.exists() {
+        let index = csi::fs::read(&csi_path)
+            .map_err(|e| VCFParseError::IndexError(format!("{}: {}", csi_path.display(), e)))?;
+        return Ok(Box::new(index));
+    }
 
-        // Find the info field: info: Info("...")
-        let info_prefix = "info: Info(\"";
-        let start_idx = record_str.find(info_prefix)? + info_prefix.len();
+    Err(VCFParseError::IndexError(format!(
+        "No .tbi or .csi index found for {} (build one with VCFParser::build_index)",
+        path.display()
+    )))
+}
 
-        // Find the closing quote
-        let end_idx = record_str[start_idx..].find("\")")? + start_idx;
+/// Detect the genome build of a VCF from its header
+///
+/// Uses the `##contig` lengths first (imputation servers write them for
+/// every chromosome), then the `##reference` line. TOPMed output is GRCh38
+/// with "chr" prefixes; Michigan output is usually GRCh37.
+///
+/// # Returns
+/// * The first build identified, or `GenomeBuild::Unknown`
+pub fn detect_build(header: &vcf::Header) -> GenomeBuild {
+    let from_contigs = header.contigs().iter().find_map(|(name, contig)| {
+        let chromosome = chromosomes::parse_chromosome(name)?;
+        let length = contig.length()? as u64;
+        Some(GenomeBuild::from_contig_length(chromosome, length)).filter(GenomeBuild::is_known)
+    });
+    if let Some(build) = from_contigs {
+        return build;
+    }
 
-        // Extract INFO string
-        let info = &record_str[start_idx..end_idx];
+    match header.get("reference") {
+        Some(vcf::header::record::value::Collection::Unstructured(values)) => values
+            .iter()
+            .map(|value| GenomeBuild::from_reference(value))
+            .find(GenomeBuild::is_known)
+            .unwrap_or_default(),
+        _ => GenomeBuild::Unknown,
+    }
+}
 
-        // Split INFO field by semicolons
-        for field in info.split(';') {
-            if field.starts_with("R2=") {
-                let r2_str = &field[3..];  // Skip "R2="
-                if let Ok(r2) = r2_str.parse::<f64>() {
-                    return Some(r2);
-                }
-            }
+/// Build a query region, matching the chromosome to the index's naming
+///
+/// "1", "chr1", "X", "chrX", "MT" and "chrM" all resolve to whichever name
+/// the index uses for that chromosome.
+fn build_region(
+    index: &dyn BinningIndex,
+    chrom: &str,
+    start: u64,
+    end: u64,
+) -> Result<Region, VCFParseError> {
+    let chromosome = chromosomes::parse_chromosome(chrom).ok_or_else(|| {
+        VCFParseError::InvalidChromosome(format!("{} (must be 1-22, X, Y or MT)", chrom))
+    })?;
+
+    let name = index
+        .header()
+        .and_then(|header| {
+            header
+                .reference_sequence_names()
+                .iter()
+                .map(|name| name.to_string())
+                .find(|name| chromosomes::parse_chromosome(name) == Some(chromosome))
+        })
+        .ok_or_else(|| VCFParseError::IndexError(format!("Chromosome {} not found in index", chrom)))?;
+
+    let position = |value: u64| {
+        usize::try_from(value)
+            .ok()
+            .and_then(|value| Position::try_from(value).ok())
+            .ok_or_else(|| VCFParseError::IndexError(format!("Invalid region position: {}", value)))
+    };
+
+    if start > end {
+        return Err(VCFParseError::IndexError(format!(
+            "Invalid region {}:{}-{} (start after end)",
+            chrom, start, end
+        )));
+    }
+
+    Ok(Region::new(name, position(start)?..=position(end)?))
+}
+
+/// Parse a comma-separated list of floats from a FORMAT/INFO value
+fn parse_float_list(key: &str, value: &str) -> Result<Vec<f64>, VCFParseError> {
+    value
+        .split(',')
+        .map(|v| {
+            v.parse::<f64>().map_err(|e| {
+                VCFParseError::RecordError(format!("Failed to parse {} '{}' as f64: {}", key, value, e))
+            })
+        })
+        .collect()
+}
+
+/// Per-ALT dosages for one sample from DS, HDS or GP (in that order)
+///
+/// Returns `Ok(None)` when the sample has none of the fields, and an error
+/// when DS is present with the wrong number of values and HDS/GP can't be used.
+fn sample_dosages(
+    ds: Option<&[f64]>,
+    hds: Option<&[f64]>,
+    gp: Option<&[f64]>,
+    alt_count: usize,
+) -> Result<Option<Vec<f64>>, VCFParseError> {
+    if let Some(ds) = ds {
+        if ds.len() == alt_count {
+            return Ok(Some(ds.to_vec()));
         }
+    }
+
+    if let Some(dosages) = hds.and_then(|h| dosages_from_hds(h, alt_count)) {
+        return Ok(Some(dosages));
+    }
+
+    if let Some(dosages) = gp.and_then(|g| dosages_from_gp(g, alt_count)) {
+        return Ok(Some(dosages));
+    }
+
+    match ds {
+        Some(ds) => Err(VCFParseError::RecordError(format!(
+            "DS has {} values for {} ALT alleles and no usable HDS/GP",
+            ds.len(),
+            alt_count
+        ))),
+        None => Ok(None),
+    }
+}
 
+/// Haplotype dosages (HDS) of one ALT allele (1-based `allele`)
+///
+/// HDS lists the ALT dosage of each ALT allele per haplotype
+/// (hap1 alt1..altN, hap2 alt1..altN). Haploid samples have one haplotype.
+fn split_hds(hds: &[f64], alt_count: usize, allele: usize) -> Option<Vec<f64>> {
+    let k = allele - 1;
+    if hds.len() == 2 * alt_count {
+        Some(vec![hds[k], hds[alt_count + k]])
+    } else if hds.len() == alt_count {
+        Some(vec![hds[k]])
+    } else {
         None
     }
 }
 
+/// Per-ALT dosages from haplotype dosages (HDS)
+fn dosages_from_hds(hds: &[f64], alt_count: usize) -> Option<Vec<f64>> {
+    (1..=alt_count)
+        .map(|allele| split_hds(hds, alt_count, allele).map(|h| h.iter().sum()))
+        .collect()
+}
+
+/// Genotype probabilities (GP) by copy number of one ALT allele (1-based `allele`)
+///
+/// Diploid GP follows VCF genotype ordering: genotype (a, b) with a <= b is
+/// at index b(b+1)/2 + a. Haploid GP has one probability per allele.
+fn split_gp(gp: &[f64], alt_count: usize, allele: usize) -> Option<Vec<f64>> {
+    let allele_count = alt_count + 1;
+
+    if gp.len() == allele_count * (allele_count + 1) / 2 {
+        let mut by_copies = vec![0.0; 3];
+        let mut idx = 0;
+        for b in 0..allele_count {
+            for a in 0..=b {
+                let copies = (a == allele) as usize + (b == allele) as usize;
+                by_copies[copies] += gp[idx];
+                idx += 1;
+            }
+        }
+        Some(by_copies)
+    } else if gp.len() == allele_count {
+        Some(vec![1.0 - gp[allele], gp[allele]])
+    } else {
+        None
+    }
+}
+
+/// Per-ALT dosages (expected allele counts) from genotype probabilities (GP)
+fn dosages_from_gp(gp: &[f64], alt_count: usize) -> Option<Vec<f64>> {
+    (1..=alt_count)
+        .map(|allele| {
+            split_gp(gp, alt_count, allele)
+                .map(|p| p.iter().enumerate().map(|(copies, p)| copies as f64 * p).sum())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// Create a temporary uncompressed VCF file with the given samples and records
+    fn create_test_vcf(sample_names: &[&str], records: &str) -> NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".vcf").tempfile().unwrap();
+        write!(
+            file,
+            "##fileformat=VCFv4.2\n\
+##contig=<ID=1>\n\
+##INFO=<ID=R2,Number=A,Type=Float,Description=\"Estimated Imputation Accuracy\">\n\
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+##FORMAT=<ID=DS,Number=A,Type=Float,Description=\"Estimated Alternate Allele Dosage\">\n\
+##FORMAT=<ID=HDS,Number=.,Type=Float,Description=\"Estimated Haploid Alternate Allele Dosage\">\n\
+##FORMAT=<ID=GP,Number=G,Type=Float,Description=\"Estimated Posterior Probabilities\">\n\
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\t{}\n{}",
+            sample_names.join("\t"),
+            records
+        )
+        .unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_split_multiallelic_records() {
+        let file = create_test_vcf(
+            &["samp1"],
+            "1\t100\trs1\tA\tG\t.\tPASS\tR2=0.95\tGT:DS\t0|1:1.02\n\
+1\t200\trs2\tA\tC,T\t.\tPASS\tR2=0.9,0.2\tGT:DS\t1|2:0.98,1.01\n\
+1\t300\t.\tC\tG,T\t.\tPASS\tR2=0.8\tGT:HDS\t1|2:0.9,0.1,0.05,0.95\n",
+        );
+        let mut parser = VCFParser::new().with_min_quality(0.3);
+
+        let records = parser.parse(file.path()).unwrap();
+
+        // rs2's T allele is dropped by the quality filter
+        assert_eq!(records.len(), 4);
+        assert_eq!(parser.split_multiallelic_count, 2);
+        assert_eq!(parser.skipped_count, 1);
+
+        assert_eq!(records[1].rsid, "rs2");
+        assert_eq!(records[1].alt_allele, "C");
+        assert_eq!(records[1].dosage, 0.98);
+        assert_eq!(records[1].imputation_quality, Some(0.9));
+
+        // Dosages from HDS, pseudo-IDs per ALT allele
+        assert_eq!(records[2].rsid, "chr1:300:C:G");
+        assert!((records[2].dosage - 0.95).abs() < 1e-9);
+        assert_eq!(records[3].rsid, "chr1:300:C:T");
+        assert!((records[3].dosage - 1.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_multi_sample_format_values() {
+        let file = create_test_vcf(
+            &["mother", "father", "user"],
+            "1\t100\trs1\tA\tG,T\t.\tPASS\tR2=0.9\tGT:HDS:GP\t\
+0|1:0.9,0.0,0.1,0.0:0.0,1.0,0.0,0.0,0.0,0.0\t\
+2|2:0.0,1.0,0.0,1.0:0.0,0.0,0.0,0.0,0.0,1.0\t\
+.|.:.:.\n",
+        );
+        let mut parser = VCFParser::new();
+
+        // The user (last sample) has no dosage, so the record is rejected
+        assert!(parser.parse(file.path()).unwrap().is_empty());
+        assert_eq!(parser.error_count, 1);
+        assert_eq!(parser.sample_names, vec!["mother", "father", "user"]);
+
+        let file = create_test_vcf(
+            &["mother", "user"],
+            "1\t100\trs1\tA\tG,T\t.\tPASS\tR2=0.9\tGT:HDS:GP\t\
+0|1:0.9,0.0,0.1,0.0:0.0,1.0,0.0,0.0,0.0,0.0\t\
+2|2:0.0,1.0,0.0,1.0:0.0,0.0,0.0,0.0,0.0,1.0\n",
+        );
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(records.len(), 2);
+
+        // ALT G: mother carries one copy on haplotype 2 (HDS summed)
+        let mother_g = &records[0].samples[0];
+        assert_eq!(mother_g.genotype, Some("0|1".parse().unwrap()));
+        assert_eq!(mother_g.haplotype_dosages, Some(vec![0.9, 0.1]));
+        assert_eq!(mother_g.genotype_probabilities, Some(vec![0.0, 1.0, 0.0]));
+        assert!((mother_g.dosage.unwrap() - 1.0).abs() < 1e-9);
+
+        // ALT T: user is homozygous, other ALT alleles recoded to 0
+        let user_t = &records[1].samples[1];
+        assert_eq!(user_t.genotype, Some("1|1".parse().unwrap()));
+        assert_eq!(user_t.genotype_probabilities, Some(vec![0.0, 0.0, 1.0]));
+        assert_eq!(records[1].dosage, 2.0);
+        assert_eq!(records[0].samples[1].genotype, Some("0|0".parse().unwrap()));
+
+        // GT wins over the ploidy passed in
+        let sample = records[0].sample_data(0, "mother", 1).unwrap();
+        assert_eq!(sample.genotype.to_string(), "0|1");
+        assert_eq!(sample.source, DataSource::Imputed);
+        assert!(records[0].sample_data(2, "missing", 2).is_none());
+
+        // Without GT the call follows the site ploidy
+        let ds_only = VCFRecord {
+            samples: vec![VCFSampleValues { dosage: Some(0.9), ..Default::default() }],
+            ..records[0].clone()
+        };
+        assert_eq!(ds_only.sample_data(0, "father", 1).unwrap().genotype, Genotype::haploid(Some(1)));
+        assert_eq!(ds_only.sample_data(0, "father", 2).unwrap().genotype, Genotype::from_alt_count(1));
+
+    }
+
+    #[test]
+    fn test_malformed_sample_genotype_counts_as_error() {
+        // A malformed family-member GT skips that record (not the file) and
+        // counts against the error budget, like any other bad record
+        let file = create_test_vcf(
+            &["mother", "user"],
+            "1\t100\trs1\tA\tG\t.\tPASS\tR2=0.9\tGT:DS\tA|G:1.0\t0|1:1.0\n\
+1\t200\trs2\tA\tG\t.\tPASS\tR2=0.9\tGT:DS\t1|1:2.0\t0|1:1.0\n",
+        );
+        let mut parser = VCFParser::new();
+
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rsid, "rs2");
+        assert_eq!(parser.error_count, 1);
+        assert_eq!(parser.report.records_read, 2);
+        assert_eq!(parser.report.records_accepted, 1);
+
+        let mut strict = VCFParser::new().with_max_errors(0);
+        assert!(matches!(strict.parse(file.path()), Err(VCFParseError::RecordError(_))));
+    }
+
+    #[test]
+    fn test_indexed_region_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chr1.dose.vcf.gz");
+
+        let mut writer = noodles_bgzf::io::Writer::new(std::fs::File::create(&path).unwrap());
+        write!(
+            writer,
+            "##fileformat=VCFv4.2\n\
+##contig=<ID=1,length=249250621>\n\
+##FORMAT=<ID=DS,Number=A,Type=Float,Description=\"Dosage\">\n\
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tuser\n\
+1\t100\trs1\tA\tG\t.\tPASS\t.\tDS\t0.1\n\
+1\t200\trs2\tC\tT\t.\tPASS\t.\tDS\t1.0\n\
+1\t300\trs3\tG\tA\t.\tPASS\t.\tDS\t2.0\n"
+        )
+        .unwrap();
+        writer.finish().unwrap();
+
+        let mut parser = VCFParser::new();
+        let index_path = dir.path().join("chr1.dose.vcf.gz.tbi");
+
+        // A full parse leaves the upload unindexed
+        assert_eq!(parser.parse(&path).unwrap().len(), 3);
+        assert!(!index_path.exists());
+
+        // The first query builds the missing index
+        let records = parser.query(&path, "1", 150, 250).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rsid, "rs2");
+        assert!(index_path.exists());
+        assert_eq!(VCFParser::ensure_index(&path).unwrap(), index_path);
+
+        // "chr1" resolves to the file's "1"
+        let records = parser.query(&path, "chr1", 150, 300).unwrap();
+        let rsids: Vec<&str> = records.iter().map(|r| r.rsid.as_str()).collect();
+        assert_eq!(rsids, vec!["rs2", "rs3"]);
+
+        assert!(parser.query(&path, "2", 1, 1000).is_err());
+    }
+
+    #[test]
+    fn test_detect_build_from_header() {
+        // TOPMed: GRCh38 contig lengths with "chr" prefixes
+        let mut file = tempfile::Builder::new().suffix(".vcf").tempfile().unwrap();
+        write!(
+            file,
+            "##fileformat=VCFv4.2\n\
+##contig=<ID=chr22,length=50818468>\n\
+##FORMAT=<ID=DS,Number=A,Type=Float,Description=\"Dosage\">\n\
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tuser\n\
+chr22\t100\trs1\tA\tG\t.\tPASS\t.\tDS\t0.1\n"
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let mut parser = VCFParser::new();
+        let records = parser.parse(file.path()).unwrap();
+        assert_eq!(parser.genome_build, GenomeBuild::GRCh38);
+        assert_eq!(records[0].chromosome, 22);
+        assert_eq!(records[0].genome_build, GenomeBuild::GRCh38);
+
+        // Contigs without lengths fall back to ##reference; neither gives Unknown
+        let mut file = tempfile::Builder::new().suffix(".vcf").tempfile().unwrap();
+        write!(
+            file,
+            "##fileformat=VCFv4.2\n\
+##reference=file:///ref/hs37d5.fa.gz\n\
+##contig=<ID=1>\n\
+##FORMAT=<ID=DS,Number=A,Type=Float,Description=\"Dosage\">\n\
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tuser\n\
+1\t100\trs1\tA\tG\t.\tPASS\t.\tDS\t0.1\n"
+        )
+        .unwrap();
+        file.flush().unwrap();
+        parser.parse(file.path()).unwrap();
+        assert_eq!(parser.genome_build, GenomeBuild::GRCh37);
+
+        let file = create_test_vcf(&["user"], "1\t100\trs1\tA\tG\t.\tPASS\t.\tDS\t0.1\n");
+        parser.parse(file.path()).unwrap();
+        assert_eq!(parser.genome_build, GenomeBuild::Unknown);
+    }
+
+    #[test]
+    fn test_dosages_from_gp() {
+        // Biallelic: P(0/0)=0.1, P(0/1)=0.6, P(1/1)=0.3
+        let dosages = dosages_from_gp(&[0.1, 0.6, 0.3], 1).unwrap();
+        assert!((dosages[0] - 1.2).abs() < 1e-9);
+
+        // Triallelic order: 0/0, 0/1, 1/1, 0/2, 1/2, 2/2
+        let dosages = dosages_from_gp(&[0.0, 0.0, 0.0, 0.0, 1.0, 0.0], 2).unwrap();
+        assert_eq!(dosages, vec![1.0, 1.0]);
+
+        // Haploid
+        assert_eq!(dosages_from_gp(&[0.2, 0.8], 1).unwrap(), vec![0.8]);
+
+        assert!(dosages_from_gp(&[0.5, 0.5, 0.0, 0.0], 1).is_none());
+    }
 
     #[test]
     fn test_chromosome_parsing() {
@@ -396,9 +1101,12 @@ mod tests {
         assert_eq!(parser.parse_chromosome("22").unwrap(), 22);
         assert_eq!(parser.parse_chromosome("chr22").unwrap(), 22);
 
-        assert!(parser.parse_chromosome("X").is_err());
-        assert!(parser.parse_chromosome("23").is_err());
+        assert_eq!(parser.parse_chromosome("chrX").unwrap(), chromosomes::CHR_X);
+        assert_eq!(parser.parse_chromosome("Y").unwrap(), chromosomes::CHR_Y);
+        assert_eq!(parser.parse_chromosome("chrM").unwrap(), chromosomes::CHR_MT);
+
         assert!(parser.parse_chromosome("chr0").is_err());
+        assert!(parser.parse_chromosome("chrUn_gl000220").is_err());
     }
 
     #[test]
@@ -412,8 +1120,10 @@ mod tests {
             position: 100,
             ref_allele: "A".to_string(),
             alt_allele: "G".to_string(),
+            genome_build: GenomeBuild::GRCh37,
             dosage: 0.0,
             imputation_quality: None,
+            samples: Vec::new(),
         }.dosage >= 0.0);
 
         assert!(VCFRecord {
@@ -422,8 +1132,10 @@ mod tests {
             position: 200,
             ref_allele: "A".to_string(),
             alt_allele: "G".to_string(),
+            genome_build: GenomeBuild::GRCh37,
             dosage: 2.0,
             imputation_quality: None,
+            samples: Vec::new(),
         }.dosage <= 2.0);
     }
 }
@@ -0,0 +1,633 @@
+// ==============================================================================
+// pgs_engine.rs - In-House Polygenic Score Calculation
+// ==============================================================================
+// Description: Computes polygenic scores for all 51 samples from PGS Catalog
+//              weights and the merged per-chromosome variant stream
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Algorithm (additive model, per score):
+//   score = Σ weight × effect allele dosage
+//   - Scoring variants are matched on GRCh37 position, falling back to rsID
+//     when the scoring file has no GRCh37 coordinates
+//   - Alleles are matched directly, with REF/ALT swapped, or on the opposite
+//     strand; non-matching alleles are not scored
+//   - Missing sample genotypes are mean-imputed from the other samples
+//   - Scoring variants absent from the merged data contribute
+//     2 × effect allele frequency × weight when the frequency is known
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::genome_build::REFERENCE_PANEL_BUILD;
+use crate::models::{MultiSampleVariant, SampleData};
+use crate::parsers::pgs::{PgsDataset, PgsNormalization, PgsParser, PgsRecord};
+use crate::parsers::pgs_catalog::ScoringFile;
+use crate::parsers::report::ParseReport;
+
+/// How a scoring variant's alleles matched a merged variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlleleMatch {
+    /// Effect allele is ALT
+    Direct,
+    /// Effect allele is REF
+    Swapped,
+    /// Effect allele is ALT on the opposite strand
+    Flipped,
+    /// Effect allele is REF on the opposite strand
+    FlippedSwapped,
+}
+
+impl AlleleMatch {
+    /// Whether the effect allele is the REF allele (dosage counts the other allele)
+    pub fn is_swapped(&self) -> bool {
+        matches!(self, AlleleMatch::Swapped | AlleleMatch::FlippedSwapped)
+    }
+
+    /// Whether the alleles matched on the opposite strand
+    pub fn is_flipped(&self) -> bool {
+        matches!(self, AlleleMatch::Flipped | AlleleMatch::FlippedSwapped)
+    }
+}
+
+/// Per-score coverage report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PgsCoverage {
+    /// Score label (PGS ID)
+    pub label: String,
+
+    /// Trait as reported in the scoring file
+    pub trait_reported: Option<String>,
+
+    /// Variants in the scoring file
+    pub variants_total: usize,
+
+    /// Variants found in the merged data with matching alleles
+    pub variants_matched: usize,
+
+    /// Matched variants whose effect allele is the REF allele
+    pub variants_swapped: usize,
+
+    /// Matched variants whose alleles are on the opposite strand
+    pub variants_flipped: usize,
+
+    /// Variants found at the right locus but with different alleles
+    pub variants_allele_mismatch: usize,
+
+    /// Variants not matched (including allele mismatches)
+    pub variants_missing: usize,
+
+    /// Missing variants scored from the effect allele frequency
+    pub variants_imputed_by_frequency: usize,
+
+    /// Sample genotypes mean-imputed at matched variants
+    pub genotypes_imputed: usize,
+}
+
+impl PgsCoverage {
+    /// Fraction of scoring variants matched (0.0-1.0)
+    pub fn coverage(&self) -> f64 {
+        if self.variants_total == 0 {
+            0.0
+        } else {
+            self.variants_matched as f64 / self.variants_total as f64
+        }
+    }
+}
+
+/// Scores and coverage produced by `PgsEngine::finish`
+#[derive(Debug, Clone)]
+pub struct PgsEngineResult {
+    /// Unscaled and scaled scores (None if no score matched any variant)
+    pub dataset: Option<PgsDataset>,
+
+    /// Coverage report for every scoring file
+    pub coverage: Vec<PgsCoverage>,
+}
+
+/// Running state for one scoring file
+struct ScoreState {
+    scoring: ScoringFile,
+    /// Scoring variant was matched to a merged variant
+    matched: Vec<bool>,
+    /// Scoring variant's locus was seen with different alleles
+    seen: Vec<bool>,
+    /// Per-sample score sums (same order as `PgsEngine::sample_ids`)
+    sums: Vec<f64>,
+    coverage: PgsCoverage,
+}
+
+/// Streaming polygenic score calculator
+///
+/// Feed each chromosome's merged variants to `add_chromosome` as they are
+/// produced, then call `finish` once all chromosomes are processed.
+pub struct PgsEngine {
+    scores: Vec<ScoreState>,
+    /// (chromosome, GRCh37 position) -> (score index, variant index)
+    by_locus: HashMap<(u8, u64), Vec<(usize, usize)>>,
+    /// rsID -> (score index, variant index), for variants without GRCh37 coordinates
+    by_rsid: HashMap<String, Vec<(usize, usize)>>,
+    /// Sample IDs taken from the first merged variant
+    sample_ids: Vec<String>,
+}
+
+impl PgsEngine {
+    /// Create an engine for a set of scoring files
+    pub fn new(scoring_files: Vec<ScoringFile>) -> Self {
+        let mut by_locus: HashMap<(u8, u64), Vec<(usize, usize)>> = HashMap::new();
+        let mut by_rsid: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        let mut scores = Vec::with_capacity(scoring_files.len());
+
+        for (score_idx, scoring) in scoring_files.into_iter().enumerate() {
+            for (variant_idx, variant) in scoring.variants.iter().enumerate() {
+                if let Some(locus) = scoring.locus(variant, REFERENCE_PANEL_BUILD) {
+                    by_locus.entry(locus).or_default().push((score_idx, variant_idx));
+                } else if let Some(rsid) = &variant.rsid {
+                    by_rsid.entry(rsid.clone()).or_default().push((score_idx, variant_idx));
+                }
+            }
+
+            let variant_count = scoring.variants.len();
+            scores.push(ScoreState {
+                coverage: PgsCoverage {
+                    label: scoring.label(),
+                    trait_reported: scoring.metadata.trait_reported.clone(),
+                    variants_total: variant_count,
+                    ..Default::default()
+                },
+                scoring,
+                matched: vec![false; variant_count],
+                seen: vec![false; variant_count],
+                sums: Vec::new(),
+            });
+        }
+
+        Self {
+            scores,
+            by_locus,
+            by_rsid,
+            sample_ids: Vec::new(),
+        }
+    }
+
+    /// Number of scoring files
+    pub fn score_count(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Add one chromosome's merged variants to the running scores
+    pub fn add_chromosome(&mut self, variants: &[MultiSampleVariant]) {
+        for variant in variants {
+            if self.sample_ids.is_empty() {
+                self.sample_ids = variant.samples.iter().map(|s| s.sample_id.clone()).collect();
+                for score in &mut self.scores {
+                    score.sums = vec![0.0; self.sample_ids.len()];
+                }
+            }
+            if variant.samples.len() != self.sample_ids.len() {
+                continue;
+            }
+
+            let candidates = self
+                .by_locus
+                .get(&(variant.chromosome, variant.position))
+                .into_iter()
+                .chain(self.by_rsid.get(&variant.rsid))
+                .flatten();
+
+            for &(score_idx, variant_idx) in candidates {
+                let score = &mut self.scores[score_idx];
+                if score.matched[variant_idx] {
+                    continue;
+                }
+
+                let weight = &score.scoring.variants[variant_idx];
+                let Some(allele_match) = match_alleles(
+                    &weight.effect_allele,
+                    weight.other_allele.as_deref(),
+                    &variant.ref_allele,
+                    &variant.alt_allele,
+                ) else {
+                    // Split multi-allelic sites can still match a later record
+                    score.seen[variant_idx] = true;
+                    continue;
+                };
+
+                score.matched[variant_idx] = true;
+                score.coverage.variants_matched += 1;
+                if allele_match.is_swapped() {
+                    score.coverage.variants_swapped += 1;
+                }
+                if allele_match.is_flipped() {
+                    score.coverage.variants_flipped += 1;
+                }
+
+                let effect_weight = weight.effect_weight;
+                score.coverage.genotypes_imputed +=
+                    add_weighted_dosages(&mut score.sums, &variant.samples, allele_match, effect_weight);
+            }
+        }
+    }
+
+    /// Finish scoring: impute missing variants and build the PGS dataset
+    pub fn finish(self) -> PgsEngineResult {
+        let mut unscaled = Vec::new();
+        let mut coverage = Vec::with_capacity(self.scores.len());
+        let mut report = ParseReport::new("pgs_engine");
+
+        for mut score in self.scores {
+            for (idx, variant) in score.scoring.variants.iter().enumerate() {
+                if score.matched[idx] {
+                    continue;
+                }
+
+                score.coverage.variants_missing += 1;
+                if score.seen[idx] {
+                    score.coverage.variants_allele_mismatch += 1;
+                }
+                if let Some(frequency) = variant.allele_frequency {
+                    score.coverage.variants_imputed_by_frequency += 1;
+                    let expected = 2.0 * frequency * variant.effect_weight;
+                    score.sums.iter_mut().for_each(|sum| *sum += expected);
+                }
+            }
+
+            report.records_read += score.coverage.variants_total;
+            report.records_accepted += score.coverage.variants_matched;
+
+            if score.coverage.variants_matched == 0 {
+                warn!("PGS {}: no scoring variants found in merged data, skipping", score.coverage.label);
+            } else {
+                unscaled.extend(self.sample_ids.iter().zip(&score.sums).map(|(sample_id, value)| {
+                    PgsRecord {
+                        sample_id: sample_id.clone(),
+                        label: score.coverage.label.clone(),
+                        value: *value,
+                    }
+                }));
+            }
+
+            coverage.push(score.coverage);
+        }
+
+        let dataset = if unscaled.is_empty() {
+            None
+        } else {
+            Some(PgsDataset {
+                scaled: PgsParser::scale_pgs(&unscaled),
+                unscaled,
+                normalization: PgsNormalization::ZScore,
+                report,
+            })
+        };
+
+        PgsEngineResult { dataset, coverage }
+    }
+}
+
+/// Add weight × effect allele dosage for every sample, mean-imputing missing genotypes
+///
+/// # Returns
+/// * Number of sample genotypes that were imputed
+fn add_weighted_dosages(
+    sums: &mut [f64],
+    samples: &[SampleData],
+    allele_match: AlleleMatch,
+    effect_weight: f64,
+) -> usize {
+    let dosages: Vec<Option<f64>> = samples
+        .iter()
+        .map(|sample| effect_allele_dosage(sample, allele_match))
+        .collect();
+
+    let called: Vec<f64> = dosages.iter().flatten().copied().collect();
+    let mean = if called.is_empty() {
+        0.0
+    } else {
+        called.iter().sum::<f64>() / called.len() as f64
+    };
+
+    for (sum, dosage) in sums.iter_mut().zip(&dosages) {
+        *sum += effect_weight * dosage.unwrap_or(mean);
+    }
+
+    dosages.len() - called.len()
+}
+
+/// Effect allele dosage for a sample (None if the genotype is missing)
+fn effect_allele_dosage(sample: &SampleData, allele_match: AlleleMatch) -> Option<f64> {
+    if sample.genotype.is_missing() {
+        return None;
+    }
+
+    if allele_match.is_swapped() {
+        // Allele count is 2 for diploid genotypes ("0|1") and 1 for haploid ("1");
+        // dosages are still allele counts here, the haploid convention is applied at output
+        let allele_count = f64::from(sample.genotype.ploidy());
+        Some(allele_count - sample.dosage)
+    } else {
+        Some(sample.dosage)
+    }
+}
+
+/// Match a scoring variant's alleles against a merged variant's REF/ALT
+///
+/// A direct match is preferred over a strand flip, so strand-ambiguous
+/// (A/T, C/G) variants are assumed to be on the same strand.
+///
+/// # Returns
+/// * `Some(AlleleMatch)` - How the alleles matched
+/// * `None` - Alleles do not match
+pub fn match_alleles(
+    effect_allele: &str,
+    other_allele: Option<&str>,
+    ref_allele: &str,
+    alt_allele: &str,
+) -> Option<AlleleMatch> {
+    let matches = |effect: &str, other: Option<&str>| {
+        let other_ok = |expected: &str| other.map_or(true, |o| o.eq_ignore_ascii_case(expected));
+        if effect.eq_ignore_ascii_case(alt_allele) && other_ok(ref_allele) {
+            Some(false)
+        } else if effect.eq_ignore_ascii_case(ref_allele) && other_ok(alt_allele) {
+            Some(true)
+        } else {
+            None
+        }
+    };
+
+    if let Some(swapped) = matches(effect_allele, other_allele) {
+        return Some(if swapped { AlleleMatch::Swapped } else { AlleleMatch::Direct });
+    }
+
+    let effect_flipped = reverse_complement(effect_allele)?;
+    let other_flipped = match other_allele {
+        Some(other) => Some(reverse_complement(other)?),
+        None => None,
+    };
+    matches(&effect_flipped, other_flipped.as_deref()).map(|swapped| {
+        if swapped {
+            AlleleMatch::FlippedSwapped
+        } else {
+            AlleleMatch::Flipped
+        }
+    })
+}
+
+/// Reverse complement of a nucleotide allele (None for non-ACGT alleles)
+fn reverse_complement(allele: &str) -> Option<String> {
+    allele
+        .chars()
+        .rev()
+        .map(|base| match base.to_ascii_uppercase() {
+            'A' => Some('T'),
+            'T' => Some('A'),
+            'C' => Some('G'),
+            'G' => Some('C'),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome_build::GenomeBuild;
+    use crate::models::DataSource;
+    use crate::parsers::pgs_catalog::{ScoringMetadata, ScoringVariant};
+
+    fn sample(id: &str, genotype: &str, dosage: f64) -> SampleData {
+        SampleData {
+            sample_id: id.to_string(),
+            genotype: genotype.parse().unwrap(),
+            dosage,
+            source: DataSource::Genotyped,
+            imputation_quality: None,
+        }
+    }
+
+    fn variant(position: u64, ref_allele: &str, alt_allele: &str, samples: Vec<SampleData>) -> MultiSampleVariant {
+        MultiSampleVariant {
+            rsid: format!("rs{}", position),
+            chromosome: 1,
+            position,
+            ref_allele: ref_allele.to_string(),
+            alt_allele: alt_allele.to_string(),
+            allele_freq: None,
+            minor_allele_freq: None,
+            is_typed: true,
+            samples,
+        }
+    }
+
+    fn weight(position: u64, effect: &str, other: &str, effect_weight: f64) -> ScoringVariant {
+        ScoringVariant {
+            rsid: Some(format!("rs{}", position)),
+            chromosome: Some(1),
+            position: Some(position),
+            harmonized_chromosome: None,
+            harmonized_position: None,
+            effect_allele: effect.to_string(),
+            other_allele: Some(other.to_string()),
+            effect_weight,
+            allele_frequency: None,
+        }
+    }
+
+    #[test]
+    fn test_match_alleles() {
+        assert_eq!(match_alleles("G", Some("A"), "A", "G"), Some(AlleleMatch::Direct));
+        assert_eq!(match_alleles("A", Some("G"), "A", "G"), Some(AlleleMatch::Swapped));
+        assert_eq!(match_alleles("C", Some("T"), "A", "G"), Some(AlleleMatch::Flipped));
+        assert_eq!(match_alleles("T", None, "A", "G"), Some(AlleleMatch::FlippedSwapped));
+        // Strand-ambiguous SNPs are taken as-is
+        assert_eq!(match_alleles("T", Some("A"), "A", "T"), Some(AlleleMatch::Direct));
+        assert_eq!(match_alleles("C", Some("A"), "A", "G"), None);
+    }
+
+    #[test]
+    fn test_engine_scores_and_coverage() {
+        let mut missing = weight(500, "T", "C", 1.0);
+        missing.allele_frequency = Some(0.25);
+
+        let scoring = ScoringFile {
+            metadata: ScoringMetadata {
+                pgs_id: Some("PGS000001".to_string()),
+                genome_build: GenomeBuild::GRCh37,
+                ..Default::default()
+            },
+            variants: vec![
+                weight(100, "G", "A", 0.5),  // direct
+                weight(200, "A", "C", 2.0),  // swapped (REF=A, ALT=C)
+                weight(300, "G", "A", -1.0), // allele mismatch
+                missing,
+            ],
+            report: ParseReport::default(),
+        };
+
+        let mut engine = PgsEngine::new(vec![scoring]);
+        engine.add_chromosome(&[
+            variant(100, "A", "G", vec![sample("samp1", "0|1", 1.0), sample("user", "1|1", 2.0)]),
+            variant(200, "A", "C", vec![sample("samp1", "0|0", 0.0), sample("user", "./.", 0.0)]),
+            variant(300, "A", "C", vec![sample("samp1", "0|1", 1.0), sample("user", "0|1", 1.0)]),
+        ]);
+
+        let result = engine.finish();
+        let coverage = &result.coverage[0];
+        assert_eq!(coverage.variants_total, 4);
+        assert_eq!(coverage.variants_matched, 2);
+        assert_eq!(coverage.variants_swapped, 1);
+        assert_eq!(coverage.variants_allele_mismatch, 1);
+        assert_eq!(coverage.variants_missing, 2);
+        assert_eq!(coverage.variants_imputed_by_frequency, 1);
+        assert_eq!(coverage.genotypes_imputed, 1);
+        assert_eq!(coverage.coverage(), 0.5);
+
+        // samp1: 0.5×1 + 2.0×(2-0) + 2×0.25×1.0 = 5.0
+        // user:  0.5×2 + 2.0×2 (mean-imputed) + 0.5 = 5.5
+        let dataset = result.dataset.unwrap();
+        assert_eq!(dataset.unscaled.len(), 2);
+        assert_eq!(dataset.unscaled[0].sample_id, "samp1");
+        assert!((dataset.unscaled[0].value - 5.0).abs() < 1e-9);
+        assert!((dataset.unscaled[1].value - 5.5).abs() < 1e-9);
+        assert_eq!(dataset.scaled.len(), 2);
+    }
+
+    fn scoring_file(variants: Vec<ScoringVariant>) -> ScoringFile {
+        ScoringFile {
+            metadata: ScoringMetadata {
+                pgs_id: Some("PGS000001".to_string()),
+                genome_build: GenomeBuild::GRCh37,
+                ..Default::default()
+            },
+            variants,
+            report: ParseReport::default(),
+        }
+    }
+
+    /// Unscaled score per sample, in sample order
+    fn score_values(result: &PgsEngineResult) -> Vec<f64> {
+        result.dataset.as_ref().unwrap().unscaled.iter().map(|r| r.value).collect()
+    }
+
+    #[test]
+    fn test_effect_allele_flips_against_ref_alt() {
+        // One scoring variant per match type, each against REF=A, ALT=G
+        let weights = [
+            ("G", "A", AlleleMatch::Direct),
+            ("A", "G", AlleleMatch::Swapped),
+            ("C", "T", AlleleMatch::Flipped),
+            ("T", "C", AlleleMatch::FlippedSwapped),
+        ];
+
+        for (effect, other, expected) in weights {
+            assert_eq!(match_alleles(effect, Some(other), "A", "G"), Some(expected));
+
+            let mut engine = PgsEngine::new(vec![scoring_file(vec![weight(100, effect, other, 1.0)])]);
+            engine.add_chromosome(&[variant(
+                100,
+                "A",
+                "G",
+                vec![
+                    sample("samp1", "0|0", 0.0),
+                    sample("samp2", "0|1", 0.8),
+                    sample("samp3", "1", 1.0),
+                ],
+            )]);
+            let result = engine.finish();
+
+            // A REF effect allele counts the other copies, including at haploid sites
+            let values = score_values(&result);
+            if expected.is_swapped() {
+                assert_eq!(values, vec![2.0, 1.2, 0.0], "{:?}", expected);
+            } else {
+                assert_eq!(values, vec![0.0, 0.8, 1.0], "{:?}", expected);
+            }
+            assert_eq!(result.coverage[0].variants_swapped, usize::from(expected.is_swapped()));
+            assert_eq!(result.coverage[0].variants_flipped, usize::from(expected.is_flipped()));
+        }
+    }
+
+    #[test]
+    fn test_haploid_convention_is_applied_after_scoring() {
+        use crate::genotype_converter::HaploidConvention;
+
+        // Haploid chrX calls scored against a REF effect allele under the 0/2 output coding
+        let mut variants = vec![variant(
+            100,
+            "A",
+            "G",
+            vec![sample("samp1", "1", 1.0), sample("samp2", "0", 0.0), sample("user", "1", 1.0)],
+        )];
+        variants[0].chromosome = 23;
+
+        let mut effect = weight(100, "A", "G", 1.0);
+        effect.chromosome = Some(23);
+
+        let mut engine = PgsEngine::new(vec![scoring_file(vec![effect])]);
+        engine.add_chromosome(&variants);
+        HaploidConvention::Homozygous.apply(&mut variants);
+
+        // Scores use allele counts, so no sample goes below zero copies of the effect allele
+        assert_eq!(score_values(&engine.finish()), vec![0.0, 1.0, 0.0]);
+        let dosages: Vec<f64> = variants[0].samples.iter().map(|s| s.dosage).collect();
+        assert_eq!(dosages, vec![2.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_missing_genotypes_are_mean_imputed() {
+        let mut engine = PgsEngine::new(vec![scoring_file(vec![weight(100, "G", "A", 2.0)])]);
+        engine.add_chromosome(&[variant(
+            100,
+            "A",
+            "G",
+            vec![
+                sample("samp1", "0|1", 1.0),
+                sample("samp2", "1|1", 2.0),
+                sample("user", "./.", 0.0),
+            ],
+        )]);
+        let result = engine.finish();
+
+        // The user's missing call scores the called samples' mean dosage (1.5)
+        assert_eq!(score_values(&result), vec![2.0, 4.0, 3.0]);
+        assert_eq!(result.coverage[0].genotypes_imputed, 1);
+
+        // With no called samples the mean is 0
+        let mut engine = PgsEngine::new(vec![scoring_file(vec![weight(100, "G", "A", 2.0)])]);
+        engine.add_chromosome(&[variant(100, "A", "G", vec![sample("samp1", "./.", 0.0)])]);
+        assert_eq!(score_values(&engine.finish()), vec![0.0]);
+    }
+
+    #[test]
+    fn test_missing_variants_imputed_by_frequency_or_skipped() {
+        let mut with_frequency = weight(200, "T", "C", 1.0);
+        with_frequency.allele_frequency = Some(0.1);
+        let without_frequency = weight(300, "T", "C", 5.0);
+
+        let mut engine = PgsEngine::new(vec![scoring_file(vec![
+            weight(100, "G", "A", 1.0),
+            with_frequency,
+            without_frequency,
+        ])]);
+        engine.add_chromosome(&[variant(100, "A", "G", vec![sample("samp1", "0|1", 1.0)])]);
+        let result = engine.finish();
+
+        // 1.0 + 2 × 0.1 × 1.0; the variant without a frequency adds nothing
+        let values = score_values(&result);
+        assert!((values[0] - 1.2).abs() < 1e-9);
+        let coverage = &result.coverage[0];
+        assert_eq!(coverage.variants_missing, 2);
+        assert_eq!(coverage.variants_imputed_by_frequency, 1);
+
+        // A score with no matched variants is skipped rather than imputed
+        let mut engine = PgsEngine::new(vec![scoring_file(vec![weight(900, "G", "A", 1.0)])]);
+        engine.add_chromosome(&[variant(100, "A", "G", vec![sample("samp1", "0|1", 1.0)])]);
+        let result = engine.finish();
+        assert!(result.dataset.is_none());
+        assert_eq!(result.coverage[0].coverage(), 0.0);
+    }
+}
@@ -0,0 +1,446 @@
+// ==============================================================================
+// pgs_interpretation.rs - PGS Interpretation Relative to the Reference Panel
+// ==============================================================================
+// Description: User percentile among the 50 reference samples and predicted
+//              trait shift (r × z) with prediction intervals
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// Method (see docs/PGS_CALCULATION_REFERENCE.md):
+//   z            = (user score - reference mean) / reference SD
+//   r            = √R²
+//   predicted z  = r × z
+//   predicted    = predicted z × trait SD + trait mean
+//   interval     = predicted z ± z_crit × √(1 - R²)   (residual trait variance)
+// ==============================================================================
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use tracing::warn;
+
+use crate::models::USER_SAMPLE_ID;
+use crate::parsers::pgs::PgsDataset;
+
+/// Predictive performance and scale of a trait's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitModel {
+    /// Variance in the trait explained by the score (0.0-1.0)
+    pub r2: f64,
+
+    /// Trait standard deviation in the target population
+    #[serde(default)]
+    pub trait_sd: Option<f64>,
+
+    /// Trait mean in the target population (predictions are shifts when absent)
+    #[serde(default)]
+    pub trait_mean: Option<f64>,
+
+    /// Trait units (e.g., "years", "cm")
+    #[serde(default)]
+    pub units: Option<String>,
+}
+
+fn default_confidence_level() -> f64 {
+    0.95
+}
+
+/// Interpretation settings, keyed by PGS label
+///
+/// Loaded from JSON, e.g.
+/// `{"confidence_level": 0.95, "traits": {"PGS002012": {"r2": 0.0946, "trait_sd": 3.0, "trait_mean": 14.0, "units": "years"}}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterpretationConfig {
+    /// Coverage of the prediction interval (e.g., 0.95)
+    #[serde(default = "default_confidence_level")]
+    pub confidence_level: f64,
+
+    /// Trait models by PGS label; labels without a model get a percentile only
+    #[serde(default)]
+    pub traits: HashMap<String, TraitModel>,
+}
+
+impl Default for InterpretationConfig {
+    fn default() -> Self {
+        Self {
+            confidence_level: default_confidence_level(),
+            traits: HashMap::new(),
+        }
+    }
+}
+
+impl InterpretationConfig {
+    /// Load a configuration from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::open(path.as_ref())
+            .with_context(|| format!("Failed to open PGS trait config {:?}", path.as_ref()))?;
+        let config: Self = serde_json::from_reader(std::io::BufReader::new(file))
+            .context("Failed to parse PGS trait config")?;
+
+        if !(config.confidence_level > 0.0 && config.confidence_level < 1.0) {
+            anyhow::bail!("confidence_level must be between 0 and 1, got {}", config.confidence_level);
+        }
+        if let Some((label, _)) = config.traits.iter().find(|(_, m)| !(0.0..=1.0).contains(&m.r2)) {
+            anyhow::bail!("R² for {} must be between 0 and 1", label);
+        }
+
+        Ok(config)
+    }
+}
+
+/// Interpretation of the user's score for one trait
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PgsInterpretation {
+    pub trait_label: String,
+    pub sample_id: String,
+
+    /// User's unscaled score
+    pub raw_score: f64,
+
+    /// Reference samples the user is compared against
+    pub reference_count: usize,
+    pub reference_mean: f64,
+    pub reference_sd: f64,
+
+    /// User's score in reference standard deviations
+    pub z_score: f64,
+
+    /// Percentage of reference samples scoring below the user (ties count half)
+    pub percentile: f64,
+
+    /// Percentile of `z_score` under a standard normal distribution
+    pub normal_percentile: f64,
+
+    /// Variance explained, if the trait is configured
+    pub r2: Option<f64>,
+
+    /// Predicted trait z-score (r × z)
+    pub predicted_z: Option<f64>,
+    pub predicted_z_lower: Option<f64>,
+    pub predicted_z_upper: Option<f64>,
+
+    /// Predicted trait value (trait mean + predicted z × trait SD), or the
+    /// predicted shift from the mean when no trait mean is configured
+    pub predicted_value: Option<f64>,
+    pub predicted_value_lower: Option<f64>,
+    pub predicted_value_upper: Option<f64>,
+
+    /// Coverage of the prediction interval
+    pub confidence_level: f64,
+
+    pub units: Option<String>,
+}
+
+/// Interpret the user's scores relative to the reference samples
+///
+/// Uses unscaled scores, so the result does not depend on how the dataset
+/// was normalized. Traits without a user score or with constant reference
+/// scores are skipped.
+///
+/// # Arguments
+/// * `dataset` - Scores for the reference samples, the user and any extra samples
+/// * `reference_ids` - Sample IDs forming the reference (see `models::reference_sample_ids`)
+/// * `config` - Per-trait R², trait SD and interval coverage
+pub fn interpret(
+    dataset: &PgsDataset,
+    reference_ids: &[String],
+    config: &InterpretationConfig,
+) -> Vec<PgsInterpretation> {
+    interpret_sample(dataset, USER_SAMPLE_ID, reference_ids, config)
+}
+
+/// Interpret one sample's scores relative to the reference samples
+///
+/// Samples outside `reference_ids` (such as the other samples of a family
+/// VCF) are ignored, so they cannot shift the reference distribution.
+pub fn interpret_sample(
+    dataset: &PgsDataset,
+    sample_id: &str,
+    reference_ids: &[String],
+    config: &InterpretationConfig,
+) -> Vec<PgsInterpretation> {
+    let reference_ids: HashSet<&str> = reference_ids.iter().map(String::as_str).collect();
+
+    let mut by_label: BTreeMap<&str, (Option<f64>, Vec<f64>)> = BTreeMap::new();
+    for record in &dataset.unscaled {
+        let entry = by_label.entry(record.label.as_str()).or_default();
+        if record.sample_id == sample_id {
+            entry.0 = Some(record.value);
+        } else if reference_ids.contains(record.sample_id.as_str()) {
+            entry.1.push(record.value);
+        }
+    }
+
+    let z_crit = normal_quantile(0.5 + config.confidence_level / 2.0);
+    let mut interpretations = Vec::new();
+
+    for (label, (user_score, reference)) in by_label {
+        let Some(raw_score) = user_score else {
+            warn!("PGS {}: no score for sample {}, skipping interpretation", label, sample_id);
+            continue;
+        };
+
+        // Compare values directly: rounding in the mean leaves a tiny non-zero
+        // SD for constant scores, which would blow the z-score up
+        if reference.windows(2).all(|w| w[0] == w[1]) {
+            warn!("PGS {}: reference scores have no spread, skipping interpretation", label);
+            continue;
+        }
+
+        let n = reference.len() as f64;
+        let reference_mean = reference.iter().sum::<f64>() / n;
+        let reference_sd = (reference.iter().map(|v| (v - reference_mean).powi(2)).sum::<f64>() / n).sqrt();
+        if !(reference_sd > 0.0 && reference_sd.is_finite()) {
+            warn!("PGS {}: reference SD is {}, skipping interpretation", label, reference_sd);
+            continue;
+        }
+
+        let z_score = (raw_score - reference_mean) / reference_sd;
+        let below = reference.iter().filter(|v| **v < raw_score).count() as f64;
+        let ties = reference.iter().filter(|v| **v == raw_score).count() as f64;
+
+        let model = config.traits.get(label);
+        let prediction = model.map(|m| {
+            let predicted_z = m.r2.sqrt() * z_score;
+            let half_width = z_crit * (1.0 - m.r2).sqrt();
+            (predicted_z, predicted_z - half_width, predicted_z + half_width)
+        });
+        let to_value = |z: f64| {
+            model.and_then(|m| m.trait_sd.map(|sd| m.trait_mean.unwrap_or(0.0) + z * sd))
+        };
+
+        interpretations.push(PgsInterpretation {
+            trait_label: label.to_string(),
+            sample_id: sample_id.to_string(),
+            raw_score,
+            reference_count: reference.len(),
+            reference_mean,
+            reference_sd,
+            z_score,
+            percentile: (below + ties / 2.0) / n * 100.0,
+            normal_percentile: normal_cdf(z_score) * 100.0,
+            r2: model.map(|m| m.r2),
+            predicted_z: prediction.map(|p| p.0),
+            predicted_z_lower: prediction.map(|p| p.1),
+            predicted_z_upper: prediction.map(|p| p.2),
+            predicted_value: prediction.and_then(|p| to_value(p.0)),
+            predicted_value_lower: prediction.and_then(|p| to_value(p.1)),
+            predicted_value_upper: prediction.and_then(|p| to_value(p.2)),
+            confidence_level: config.confidence_level,
+            units: model.and_then(|m| m.units.clone()),
+        });
+    }
+
+    interpretations
+}
+
+/// Standard normal cumulative distribution function
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Numerical Recipes erfcc, relative error < 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+        .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Inverse of the standard normal CDF (Acklam's algorithm, relative error < 1.2e-9)
+///
+/// Returns ±infinity for p = 0 or 1 and NaN outside [0, 1].
+#[allow(clippy::excessive_precision)] // published coefficients, kept verbatim
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+        1.383577518672690e+02, -3.066479806614716e+01, 2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+        6.680131188771972e+01, -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+        -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if !(0.0..=1.0).contains(&p) {
+        return f64::NAN;
+    }
+    if p == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p == 1.0 {
+        return f64::INFINITY;
+    }
+
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        -(((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reference_sample_ids;
+    use crate::parsers::pgs::{PgsNormalization, PgsRecord};
+    use crate::parsers::report::ParseReport;
+
+    fn record(sample_id: &str, label: &str, value: f64) -> PgsRecord {
+        PgsRecord {
+            sample_id: sample_id.to_string(),
+            label: label.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_normal_functions() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(3.5) - 0.999767).abs() < 1e-6);
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+        assert!((normal_quantile(0.01) + 2.326348).abs() < 1e-6);
+        assert!(normal_quantile(1.5).is_nan());
+    }
+
+    #[test]
+    fn test_interpretation_matches_reference_example() {
+        // 50 reference samples with mean 0 and SD 1; user at z = 3.5
+        let mut unscaled: Vec<PgsRecord> = (1..=50)
+            .map(|i| record(&format!("samp{}", i), "PGS002012", if i % 2 == 0 { 1.0 } else { -1.0 }))
+            .collect();
+        unscaled.push(record(USER_SAMPLE_ID, "PGS002012", 3.5));
+        unscaled.push(record(USER_SAMPLE_ID, "PGS000001", 0.0));
+        let dataset = PgsDataset {
+            scaled: Vec::new(),
+            unscaled,
+            normalization: PgsNormalization::None,
+            report: ParseReport::default(),
+        };
+
+        let mut config = InterpretationConfig::default();
+        config.traits.insert(
+            "PGS002012".to_string(),
+            TraitModel {
+                r2: 0.0946,
+                trait_sd: Some(3.0),
+                trait_mean: Some(14.0),
+                units: Some("years".to_string()),
+            },
+        );
+
+        let results = interpret(&dataset, &reference_sample_ids(), &config);
+        // PGS000001 has no reference scores
+        assert_eq!(results.len(), 1);
+
+        let result = &results[0];
+        assert_eq!(result.reference_count, 50);
+        assert!((result.z_score - 3.5).abs() < 1e-9);
+        assert_eq!(result.percentile, 100.0);
+        assert!((result.normal_percentile - 99.98).abs() < 0.01);
+        assert!((result.predicted_z.unwrap() - 1.0765).abs() < 1e-3);
+        assert!((result.predicted_value.unwrap() - 17.23).abs() < 0.01);
+
+        let half_width = 1.959964 * (1.0f64 - 0.0946).sqrt() * 3.0;
+        assert!((result.predicted_value_upper.unwrap() - result.predicted_value.unwrap() - half_width).abs() < 1e-3);
+        assert_eq!(result.units.as_deref(), Some("years"));
+    }
+
+    fn dataset(unscaled: Vec<PgsRecord>) -> PgsDataset {
+        PgsDataset {
+            scaled: Vec::new(),
+            unscaled,
+            normalization: PgsNormalization::None,
+            report: ParseReport::default(),
+        }
+    }
+
+    #[test]
+    fn test_constant_reference_scores_are_skipped() {
+        // Summing fifty 0.1s gives a mean just below 0.1 and an SD of ~4e-17
+        let mut unscaled: Vec<PgsRecord> = (1..=50).map(|i| record(&format!("samp{}", i), "PGS000001", 0.1)).collect();
+        unscaled.push(record(USER_SAMPLE_ID, "PGS000001", 0.2));
+
+        let results = interpret(&dataset(unscaled), &reference_sample_ids(), &InterpretationConfig::default());
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_single_reference_score_is_skipped() {
+        let unscaled = vec![record("samp1", "PGS000001", 1.0), record(USER_SAMPLE_ID, "PGS000001", 2.0)];
+        assert!(interpret(&dataset(unscaled), &reference_sample_ids(), &InterpretationConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_every_reported_score_is_finite() {
+        // A constant trait next to a normal one: only the normal one is reported,
+        // and without a trait model it carries a percentile but no prediction
+        let mut unscaled = Vec::new();
+        for i in 1..=50 {
+            unscaled.push(record(&format!("samp{}", i), "PGS000001", 0.1));
+            unscaled.push(record(&format!("samp{}", i), "PGS000002", i as f64));
+        }
+        unscaled.push(record(USER_SAMPLE_ID, "PGS000001", 0.1));
+        unscaled.push(record(USER_SAMPLE_ID, "PGS000002", 25.0));
+
+        let results = interpret(&dataset(unscaled), &reference_sample_ids(), &InterpretationConfig::default());
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.trait_label, "PGS000002");
+        assert!(result.z_score.is_finite());
+        assert!(result.normal_percentile.is_finite());
+        // 24 below, 1 tie
+        assert_eq!(result.percentile, 49.0);
+        assert!(result.predicted_z.is_none());
+        assert!(result.predicted_value.is_none());
+    }
+
+    #[test]
+    fn test_extra_samples_are_not_reference() {
+        // A family member from a multi-sample VCF with an extreme score
+        let mut unscaled: Vec<PgsRecord> = (1..=50)
+            .map(|i| record(&format!("samp{}", i), "PGS000001", if i % 2 == 0 { 1.0 } else { -1.0 }))
+            .collect();
+        unscaled.push(record(USER_SAMPLE_ID, "PGS000001", 2.0));
+        unscaled.push(record("mother", "PGS000001", 100.0));
+
+        let results = interpret(&dataset(unscaled), &reference_sample_ids(), &InterpretationConfig::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].reference_count, 50);
+        assert_eq!(results[0].reference_mean, 0.0);
+        assert!((results[0].z_score - 2.0).abs() < 1e-9);
+        assert_eq!(results[0].percentile, 100.0);
+    }
+}
@@ -17,9 +17,11 @@ use uuid::Uuid;
 
 use crate::secure_delete;
 use crate::audit;
-use crate::parsers::{Genome23Parser, Genome23Record, PgsParser, PgsDataset, VCFParser};
-use crate::genotype_converter::genotype_to_dosage;
-use crate::models::{MultiSampleVariant, SampleData, QualityThreshold};
+use crate::chromosomes::{self, chromosome_name, is_autosome, Sex, ALL_CHROMOSOMES};
+use crate::parsers::{DetectedProvider, GenomeByChromosome, Genome23Record, PgsParser, PgsDataset, VCFParser};
+use crate::parsers::raw_genotype::open_raw_genotype_source;
+use crate::genotype_converter::{expand_haploid_call, genotype_to_dosage, HaploidConvention};
+use crate::models::{Genotype, MultiSampleVariant, SampleData, QualityThreshold};
 use crate::reference_panel::ReferencePanelReader;
 
 // Re-export for backward compatibility with worker
@@ -32,6 +34,7 @@ pub struct GeneticsProcessor {
     reference_path: PathBuf,
     db_pool: PgPool,
     quality_threshold: QualityThreshold,
+    haploid_convention: HaploidConvention,
 }
 
 impl GeneticsProcessor {
@@ -50,9 +53,16 @@ impl GeneticsProcessor {
             reference_path,
             db_pool,
             quality_threshold,
+            haploid_convention: HaploidConvention::default(),
         }
     }
 
+    /// Set the dosage coding of haploid genotypes in the output (0/1 or 0/2)
+    pub fn with_haploid_convention(mut self, convention: HaploidConvention) -> Self {
+        self.haploid_convention = convention;
+        self
+    }
+
     /// Main processing pipeline
     pub async fn process(&self) -> Result<PathBuf> {
         info!("Starting 51-sample genetic data processing for job {}", self.job_id);
@@ -75,14 +85,23 @@ impl GeneticsProcessor {
 
         // 4. Parse 23andMe data
         info!("Parsing 23andMe data");
-        let _user_genome = self.parse_23andme(&files.genome_file).await?;
+        let user_genome = self.parse_23andme(&files.genome_file).await?;
+        info!("User genome file: {}", user_genome.provider.description());
+        info!("Inferred user sex from chrX heterozygosity: {}", user_genome.sex.as_str());
 
         // 5. Process each chromosome (50 reference + 1 user = 51 samples)
-        info!("Processing 22 chromosomes with 51-sample merge");
+        info!("Processing autosomes, X, Y and MT with 51-sample merge");
         let mut merged_chromosomes: HashMap<u8, Vec<MultiSampleVariant>> = HashMap::new();
 
-        for chr in 1..=22 {
-            let merged = self.process_chromosome(chr, &files, &reference_panel).await?;
+        for chr in ALL_CHROMOSOMES {
+            // Sex chromosomes and MT are optional in the reference panel
+            if !is_autosome(chr) && reference_panel.get_chromosome_variant_count(chr)? == 0 {
+                info!("Reference panel has no chr{} variants, skipping", chromosome_name(chr));
+                continue;
+            }
+
+            let mut merged = self.process_chromosome(chr, &files, &reference_panel, &user_genome).await?;
+            self.haploid_convention.apply(&mut merged);
             merged_chromosomes.insert(chr, merged);
         }
 
@@ -146,7 +165,9 @@ impl GeneticsProcessor {
             let path = entry.path();
             let file_name = path.file_name().unwrap().to_string_lossy();
 
-            if file_name.starts_with("genome_") && file_name.ends_with(".txt") {
+            if file_name.starts_with("genome_")
+                && (file_name.ends_with(".txt") || file_name.ends_with(".csv"))
+            {
                 genome_file = Some(path.clone());
             } else if file_name.starts_with("chr") && file_name.ends_with(".dose.vcf.gz") {
                 vcf_files.push(path.clone());
@@ -163,15 +184,15 @@ impl GeneticsProcessor {
     }
 
     fn validate_file_set(&self, files: &InputFiles) -> Result<()> {
-        // Must have exactly 22 VCF files (one per chromosome)
-        if files.vcf_files.len() != 22 {
+        // Must have at least 22 VCF files (one per autosome; X/Y/MT are optional)
+        if files.vcf_files.len() < 22 {
             anyhow::bail!(
-                "Expected 22 VCF files, found {}",
+                "Expected at least 22 VCF files, found {}",
                 files.vcf_files.len()
             );
         }
 
-        // Check each chromosome VCF exists
+        // Check each autosome VCF exists
         for chr in 1..=22 {
             let expected = format!("chr{}.dose.vcf.gz", chr);
             let found = files.vcf_files.iter().any(|p| {
@@ -193,16 +214,23 @@ impl GeneticsProcessor {
     async fn parse_23andme(&self, path: &Path) -> Result<UserGenomeData> {
         info!("Parsing 23andMe genome file: {:?}", path);
 
-        // Create parser that only includes autosomal chromosomes (1-22)
-        let parser = Genome23Parser::autosomal_only();
+        // Detect the provider and create a parser for all chromosomes
+        let (mut provider, parser) = open_raw_genotype_source(path, Vec::new())
+            .context("Failed to detect raw genotype file provider")?;
 
-        // Parse the file
-        let records = parser.parse(path)
+        // Parse the file once, grouped by chromosome for per-chromosome merging
+        let (by_chromosome, report) = parser.parse_grouped_with_report(path)
             .context("Failed to parse 23andMe genome file")?;
+        provider.record_parse(&report);
+        info!("Detected {} raw genotype file", provider.description());
+
+        let snp_count: usize = by_chromosome.values().map(|m| m.len()).sum();
+        info!("Parsed {} SNPs from 23andMe file", snp_count);
+        info!("Genome parse report: {}", report.summary());
 
-        info!("Parsed {} SNPs from 23andMe file", records.len());
+        let sex = chromosomes::infer_sex_from_genome(&by_chromosome);
 
-        Ok(UserGenomeData { records })
+        Ok(UserGenomeData { provider, sex, by_chromosome })
     }
 
     async fn process_chromosome(
@@ -210,6 +238,7 @@ impl GeneticsProcessor {
         chr: u8,
         files: &InputFiles,
         reference_panel: &ReferencePanelReader,
+        user_genome: &UserGenomeData,
     ) -> Result<Vec<MultiSampleVariant>> {
         info!("Processing chromosome {} with 51-sample merge", chr);
 
@@ -220,6 +249,7 @@ impl GeneticsProcessor {
         info!("Loaded {} reference panel variants for chr{}", ref_variants.len(), chr);
 
         // 2. Parse user's VCF file (imputed data)
+        let chr_name = chromosome_name(chr);
         let vcf_path = files
             .vcf_files
             .iter()
@@ -227,22 +257,38 @@ impl GeneticsProcessor {
                 p.file_name()
                     .unwrap()
                     .to_string_lossy()
-                    .contains(&format!("chr{}.dose.vcf.gz", chr))
-            })
-            .ok_or_else(|| anyhow::anyhow!("VCF file for chr{} not found", chr))?;
+                    .contains(&format!("chr{}.dose.vcf.gz", chr_name))
+            });
 
-        debug!("Parsing user VCF file: {:?}", vcf_path);
-        let mut vcf_parser = VCFParser::new();
-        let user_vcf_records = vcf_parser
-            .parse(vcf_path)
-            .context(format!("Failed to parse VCF for chromosome {}", chr))?;
+        let user_vcf_records = match vcf_path {
+            Some(vcf_path) => {
+                debug!("Parsing user VCF file: {:?}", vcf_path);
+                let mut vcf_parser = VCFParser::new();
+                let records = vcf_parser
+                    .parse(vcf_path)
+                    .context(format!("Failed to parse VCF for chromosome {}", chr_name))?;
+                debug!(
+                    "Split {} multi-allelic records for chr{}",
+                    vcf_parser.split_multiallelic_count, chr_name
+                );
+                info!("VCF parse report: {}", vcf_parser.report.summary());
+                records
+            }
+            // Imputation of X/Y/MT is optional; merge genotyped data only
+            None if !is_autosome(chr) => Vec::new(),
+            None => anyhow::bail!("VCF file for chr{} not found", chr_name),
+        };
 
-        info!("Parsed {} user imputed variants for chr{}", user_vcf_records.len(), chr);
+        info!("Parsed {} user imputed variants for chr{}", user_vcf_records.len(), chr_name);
 
-        // 3. Parse user's 23andMe data (genotyped data)
-        let user_genome_records = self.load_23andme_for_chr(chr, &files.genome_file).await?;
+        // 3. Look up user's 23andMe data (genotyped data, parsed once up front)
+        let empty_chromosome = HashMap::new();
+        let user_genome_lookup: &HashMap<u64, Genome23Record> = user_genome
+            .by_chromosome
+            .get(&chr_name)
+            .unwrap_or(&empty_chromosome);
 
-        info!("Loaded {} user genotyped variants for chr{}", user_genome_records.len(), chr);
+        info!("Loaded {} user genotyped variants for chr{}", user_genome_lookup.len(), chr);
 
         // 4. Build lookups for user data
         // Key: (position, ref_allele, alt_allele)
@@ -256,12 +302,6 @@ impl GeneticsProcessor {
             user_vcf_lookup.insert(key, record);
         }
 
-        // Build lookup for 23andMe data (only by position, since we'll check alleles during matching)
-        let mut user_genome_lookup: HashMap<u64, &Genome23Record> = HashMap::new();
-        for record in &user_genome_records {
-            user_genome_lookup.insert(record.position, record);
-        }
-
         // 5. Merge all variants
         let mut merged = Vec::new();
         let mut user_genotyped_count = 0;
@@ -282,22 +322,46 @@ impl GeneticsProcessor {
                 continue;
             }
 
-            let user_sample = if let Some(user_vcf) = user_vcf_lookup.get(&key) {
+            // Haploid on male non-PAR X, Y and MT; absent on Y for females
+            let ploidy = chromosomes::ploidy(chr, ref_variant.position, user_genome.sex);
+
+            let user_sample = if ploidy == 0 {
+                // Chromosome absent (chrY in females): no call, whatever the inputs report
+                SampleData {
+                    sample_id: "samp51".to_string(),
+                    genotype: Genotype::missing(0),
+                    dosage: 0.0,
+                    source: DataSource::ImputedLowQual,
+                    imputation_quality: None,
+                }
+            } else if let Some(user_vcf) = user_vcf_lookup.get(&key) {
                 // User has imputed VCF data for this variant
                 // Check if we also have genotyped data
                 if let Some(user_genome) = user_genome_lookup.get(&ref_variant.position) {
-                    // Try to use genotyped data
-                    match genotype_to_dosage(
-                        &user_genome.genotype,
+                    // Try to use genotyped data (hemizygous calls are reported
+                    // as "A" or "AA", so convert to one allele at haploid sites)
+                    let genotype = if ploidy == 1 {
+                        expand_haploid_call(&user_genome.genotype)
+                    } else {
+                        user_genome.genotype.as_str().into()
+                    };
+                    let genotyped_dosage = genotype_to_dosage(
+                        &genotype,
                         &ref_variant.ref_allele,
                         &ref_variant.alt_allele,
-                    ) {
+                    )
+                    .map(|dosage| match (dosage, ploidy) {
+                        (Some(d), 1) => chromosomes::haploid_dosage(d),
+                        (dosage, _) => dosage,
+                    });
+
+                    match genotyped_dosage {
                         Ok(Some(dosage)) => {
                             // Successfully converted genotype
                             user_genotyped_count += 1;
                             SampleData {
                                 sample_id: "samp51".to_string(),
-                                genotype: user_genome.genotype.clone(),
+                                genotype: Genotype::from_dosage(dosage, ploidy),
                                 dosage,
                                 source: DataSource::Genotyped,
                                 imputation_quality: user_vcf.imputation_quality,
@@ -318,7 +382,7 @@ impl GeneticsProcessor {
 
                             SampleData {
                                 sample_id: "samp51".to_string(),
-                                genotype: format_dosage_as_genotype(user_vcf.dosage),
+                                genotype: Genotype::from_dosage(user_vcf.dosage, ploidy),
                                 dosage: user_vcf.dosage,
                                 source,
                                 imputation_quality: user_vcf.imputation_quality,
@@ -340,7 +404,7 @@ impl GeneticsProcessor {
 
                     SampleData {
                         sample_id: "samp51".to_string(),
-                        genotype: format_dosage_as_genotype(user_vcf.dosage),
+                        genotype: Genotype::from_dosage(user_vcf.dosage, ploidy),
                         dosage: user_vcf.dosage,
                         source,
                         imputation_quality: user_vcf.imputation_quality,
@@ -348,10 +412,10 @@ impl GeneticsProcessor {
                 }
             } else {
                 // User has no VCF data for this variant
-                // Mark as missing data (dosage 0.0, genotype "./.")
+                // Mark as missing data (dosage 0.0, genotype "./." or ".")
                 SampleData {
                     sample_id: "samp51".to_string(),
-                    genotype: "./.".to_string(),
+                    genotype: Genotype::missing(ploidy),
                     dosage: 0.0,
                     source: DataSource::ImputedLowQual,
                     imputation_quality: None,
@@ -364,11 +428,12 @@ impl GeneticsProcessor {
             // Add 50 reference samples
             for (idx, genotype) in ref_variant.sample_genotypes.iter().enumerate() {
                 let sample_id = format!("samp{}", idx + 1);
-                let dosage = calculate_dosage_from_genotype(genotype);
+                // Missing reference calls count as REF
+                let dosage = genotype.alt_dosage().unwrap_or(0.0);
 
                 samples.push(SampleData {
                     sample_id,
-                    genotype: genotype.clone(),
+                    genotype: *genotype,
                     dosage,
                     source: if ref_variant.is_typed {
                         DataSource::Genotyped
@@ -384,7 +449,7 @@ impl GeneticsProcessor {
 
             // Create multi-sample variant
             merged.push(MultiSampleVariant {
-                rsid: ref_variant.rsid.unwrap_or_else(|| format!("chr{}:{}", chr, ref_variant.position)),
+                rsid: ref_variant.rsid.unwrap_or_else(|| format!("chr{}:{}", chr_name, ref_variant.position)),
                 chromosome: chr,
                 position: ref_variant.position,
                 ref_allele: ref_variant.ref_allele.clone(),
@@ -398,7 +463,7 @@ impl GeneticsProcessor {
 
         info!(
             "Merged chr{}: {} variants ({} user genotyped, {} user imputed, {} filtered by quality)",
-            chr,
+            chr_name,
             merged.len(),
             user_genotyped_count,
             user_imputed_count,
@@ -408,31 +473,6 @@ impl GeneticsProcessor {
         Ok(merged)
     }
 
-    async fn load_23andme_for_chr(&self, chr: u8, genome_file: &Path) -> Result<Vec<Genome23Record>> {
-        debug!("Loading 23andMe data for chromosome {}", chr);
-
-        // Parse 23andMe file (parser caches internally for efficiency)
-        let parser = Genome23Parser::autosomal_only();
-        let all_records = parser
-            .parse(genome_file)
-            .context("Failed to parse 23andMe genome file")?;
-
-        // Filter for this chromosome
-        let chr_str = chr.to_string();
-        let chr_records: Vec<Genome23Record> = all_records
-            .into_iter()
-            .filter(|r| r.chromosome == chr_str)
-            .collect();
-
-        debug!(
-            "Filtered {} records for chromosome {}",
-            chr_records.len(),
-            chr
-        );
-
-        Ok(chr_records)
-    }
-
     async fn process_pgs_scores(&self, path: &Path) -> Result<PgsDataset> {
         info!("Parsing PGS scores from {:?}", path);
 
@@ -489,7 +529,7 @@ impl GeneticsProcessor {
         );
 
         // Log summary statistics
-        for chr in 1..=22 {
+        for chr in ALL_CHROMOSOMES {
             if let Some(variants) = merged_chromosomes.get(&chr) {
                 let user_genotyped = variants
                     .iter()
@@ -577,42 +617,10 @@ struct InputFiles {
 }
 
 struct UserGenomeData {
-    /// All parsed 23andMe records
-    records: Vec<Genome23Record>,
-}
-
-// Helper functions
-
-/// Convert dosage (0.0-2.0) to genotype string for display
-fn format_dosage_as_genotype(dosage: f64) -> String {
-    // Round to nearest integer for simple display
-    // 0.0 -> "0|0", 1.0 -> "0|1", 2.0 -> "1|1"
-    if dosage < 0.5 {
-        "0|0".to_string()
-    } else if dosage < 1.5 {
-        "0|1".to_string()
-    } else {
-        "1|1".to_string()
-    }
-}
-
-/// Calculate dosage from phased genotype string
-fn calculate_dosage_from_genotype(genotype: &str) -> f64 {
-    // Parse genotypes like "0|0", "0|1", "1|0", "1|1", "./."
-    let parts: Vec<&str> = if genotype.contains('|') {
-        genotype.split('|').collect()
-    } else if genotype.contains('/') {
-        genotype.split('/').collect()
-    } else {
-        return 0.0; // Invalid format
-    };
-
-    if parts.len() != 2 {
-        return 0.0;
-    }
-
-    let allele1 = parts[0].parse::<i32>().unwrap_or(0);
-    let allele2 = parts[1].parse::<i32>().unwrap_or(0);
-
-    (allele1 + allele2) as f64
+    /// Detected raw genotype provider and chip version
+    provider: DetectedProvider,
+    /// Genetic sex inferred from chrX heterozygosity (determines X/Y ploidy)
+    sex: Sex,
+    /// Parsed 23andMe records: chromosome -> (position -> record)
+    by_chromosome: GenomeByChromosome,
 }
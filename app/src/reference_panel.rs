@@ -14,7 +14,7 @@ use serde_json;
 use std::path::Path;
 use tracing::info;
 
-use crate::models::ReferencePanelVariant;
+use crate::models::{Genotype, ReferencePanelVariant};
 
 /// Reference panel database reader
 pub struct ReferencePanelReader {
@@ -51,8 +51,9 @@ impl ReferencePanelReader {
         let variant_iter = stmt.query_map(params![chromosome], |row| {
             let sample_genotypes_json: String = row.get(10)?;
 
-            // Deserialize as a map with sample IDs as keys (e.g., {"samp1": "0|0", "samp2": "0|1", ...})
-            let sample_map: std::collections::HashMap<String, String> = serde_json::from_str(&sample_genotypes_json)
+            // Deserialize as a map with sample IDs as keys (e.g., {"samp1": "0|0", "samp2": "0|1", ...});
+            // genotypes are parsed strictly, so a malformed call fails the query
+            let sample_map: std::collections::HashMap<String, Genotype> = serde_json::from_str(&sample_genotypes_json)
                 .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
                     10,
                     rusqlite::types::Type::Text,
@@ -71,9 +72,8 @@ impl ReferencePanelReader {
                             std::io::ErrorKind::InvalidData,
                             format!("Missing genotype for {}", sample_id)
                         ))
-                    ))?
-                    .clone();
-                sample_genotypes.push(genotype);
+                    ))?;
+                sample_genotypes.push(*genotype);
             }
 
             Ok(ReferencePanelVariant {
@@ -155,9 +155,46 @@ mod tests {
             minor_allele_freq: Some(0.5),
             imputation_quality: Some(0.95),
             is_typed: true,
-            sample_genotypes: vec!["0|0".to_string(); 50],
+            sample_genotypes: vec![Genotype::diploid(Some(0), Some(0), true); 50],
         };
     }
 
+    #[test]
+    fn test_sample_genotypes_parsed_strictly() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("reference_panel.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute(
+            "CREATE TABLE reference_variants (
+                chromosome INTEGER, position INTEGER, rsid TEXT, ref_allele TEXT,
+                alt_allele TEXT, phased INTEGER, allele_freq REAL, minor_allele_freq REAL,
+                imputation_quality REAL, is_typed INTEGER, sample_genotypes TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        let insert = |chromosome: u8, last_genotype: &str| {
+            let mut genotypes: serde_json::Map<String, serde_json::Value> = (1..50)
+                .map(|i| (format!("samp{}", i), "0|1".into()))
+                .collect();
+            genotypes.insert("samp50".to_string(), last_genotype.into());
+            conn.execute(
+                "INSERT INTO reference_variants VALUES (?1, 100, 'rs1', 'A', 'G', 1, 0.5, 0.5, 0.9, 1, ?2)",
+                params![chromosome, serde_json::Value::Object(genotypes).to_string()],
+            )
+            .unwrap();
+        };
+        insert(1, "./.");
+        insert(2, "A|G");
+
+        let reader = ReferencePanelReader::open(&db_path).unwrap();
+        let variants = reader.get_chromosome_variants(1).unwrap();
+        assert_eq!(variants[0].sample_genotypes[0].alt_dosage(), Some(1.0));
+        assert!(variants[0].sample_genotypes[49].is_missing());
+
+        assert!(reader.get_chromosome_variants(2).is_err());
+    }
+
     // Additional tests will be added once reference_panel.db is available
 }
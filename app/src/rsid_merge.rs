@@ -0,0 +1,313 @@
+// ==============================================================================
+// rsid_merge.rs - dbSNP rsID Merge History
+// ==============================================================================
+// Description: Resolves retired (merged) rsIDs to their current rsID using a
+//              local SQLite table loaded from dbSNP's RsMergeArch dump
+// Author: Matt Barham
+// Created: 2026-10-16
+// Modified: 2026-10-16
+// Version: 1.0.0
+// ==============================================================================
+// RsMergeArch.bcp.gz columns (tab-separated, no header):
+//   rsHigh  rsLow  build_id  orien  create_time  last_updated_time
+//   rsCurrent  orien2Current  comment
+// rsHigh was merged into rsLow; rsCurrent is the rsID rsHigh resolves to
+// today (empty in old rows, where rsLow is used instead).
+//
+// Table: rs_merge (rs_high INTEGER PRIMARY KEY, rs_current INTEGER NOT NULL)
+// ==============================================================================
+
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::models::ReferencePanelVariant;
+use crate::parsers::GenomeByChromosome;
+
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Rows inserted per transaction while importing a dump
+const IMPORT_BATCH_SIZE: usize = 100_000;
+
+/// Numeric part of an "rs" ID (e.g., "rs123" -> 123)
+///
+/// 23andMe internal IDs ("i3000001") and other IDs return `None`.
+pub fn rs_number(rsid: &str) -> Option<i64> {
+    rsid.trim()
+        .strip_prefix("rs")
+        .or_else(|| rsid.trim().strip_prefix("RS"))
+        .and_then(|n| n.parse().ok())
+}
+
+/// rsID merge table reader
+pub struct RsidMergeTable {
+    conn: Connection,
+}
+
+impl RsidMergeTable {
+    /// Open an rsID merge database (read-only)
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .context("Failed to open rsID merge database")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Load a dbSNP RsMergeArch dump into a new or existing merge database
+    ///
+    /// # Arguments
+    /// * `dump_path` - RsMergeArch.bcp or RsMergeArch.bcp.gz
+    /// * `db_path` - SQLite database to create or update
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of merge rows imported
+    pub fn import_dump(dump_path: impl AsRef<Path>, db_path: impl AsRef<Path>) -> Result<usize> {
+        let mut reader = BufReader::new(
+            File::open(dump_path.as_ref()).context("Failed to open RsMergeArch dump")?,
+        );
+        let reader: Box<dyn BufRead> = if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+
+        let mut conn = Connection::open(db_path.as_ref())
+            .context("Failed to create rsID merge database")?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS rs_merge (
+                rs_high INTEGER PRIMARY KEY,
+                rs_current INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        let mut rows: Vec<(i64, i64)> = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        let mut imported = 0;
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let fields: Vec<&str> = line.split('\t').collect();
+            let field = |i: usize| fields.get(i).and_then(|v| v.trim().parse::<i64>().ok());
+
+            let (Some(rs_high), Some(rs_low)) = (field(0), field(1)) else {
+                anyhow::bail!("Invalid RsMergeArch row at line {}", idx + 1);
+            };
+            rows.push((rs_high, field(6).unwrap_or(rs_low)));
+
+            if rows.len() == IMPORT_BATCH_SIZE {
+                imported += Self::insert_rows(&mut conn, &rows)?;
+                rows.clear();
+            }
+        }
+        imported += Self::insert_rows(&mut conn, &rows)?;
+
+        info!("Imported {} rsID merge rows into {:?}", imported, db_path.as_ref());
+        Ok(imported)
+    }
+
+    fn insert_rows(conn: &mut Connection, rows: &[(i64, i64)]) -> Result<usize> {
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO rs_merge (rs_high, rs_current) VALUES (?1, ?2)",
+            )?;
+            for (rs_high, rs_current) in rows {
+                stmt.execute(params![rs_high, rs_current])?;
+            }
+        }
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Current rsID for a retired rsID
+    ///
+    /// Merges are followed until an rsID that was not merged, since old rows
+    /// point at rsLow, which may itself have been merged later. A merge loop
+    /// cannot name a current rsID, so its IDs are left unresolved.
+    ///
+    /// # Returns
+    /// * `Ok(Some(rsid))` - `rsid` was merged into a different, current rsID
+    /// * `Ok(None)` - Not merged (already current), in a merge loop, or not an "rs" ID
+    pub fn current_rsid(&self, rsid: &str) -> Result<Option<String>> {
+        let Some(number) = rs_number(rsid) else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT rs_current FROM rs_merge WHERE rs_high = ?1")?;
+        let mut visited = vec![number];
+        let mut current = number;
+        while let Some(next) = stmt.query_row(params![current], |row| row.get::<_, i64>(0)).optional()? {
+            if next == current {
+                break;
+            }
+            if visited.contains(&next) {
+                warn!("rsID merge loop through rs{}, leaving {} unresolved", next, rsid);
+                return Ok(None);
+            }
+            visited.push(next);
+            current = next;
+        }
+
+        Ok((current != number).then(|| format!("rs{}", current)))
+    }
+
+    /// Replace retired rsIDs in raw genotype records with their current rsIDs
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of records renamed
+    pub fn canonicalize_genome(&self, genome: &mut GenomeByChromosome) -> Result<usize> {
+        let mut renamed = 0;
+        for record in genome.values_mut().flat_map(|records| records.values_mut()) {
+            if let Some(current) = self.current_rsid(&record.rsid)? {
+                record.rsid = current;
+                renamed += 1;
+            }
+        }
+        Ok(renamed)
+    }
+
+    /// Replace retired rsIDs in reference panel variants with their current rsIDs
+    ///
+    /// # Returns
+    /// * `Ok(count)` - Number of variants renamed
+    pub fn canonicalize_panel(&self, variants: &mut [ReferencePanelVariant]) -> Result<usize> {
+        let mut renamed = 0;
+        for variant in variants {
+            let Some(rsid) = variant.rsid.as_deref() else {
+                continue;
+            };
+            if let Some(current) = self.current_rsid(rsid)? {
+                variant.rsid = Some(current);
+                renamed += 1;
+            }
+        }
+        Ok(renamed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::Genome23Record;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    #[test]
+    fn test_import_and_canonicalize() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump_path = dir.path().join("RsMergeArch.bcp");
+        let db_path = dir.path().join("rsid_merge.db");
+
+        // rs100 -> rs50 (rsCurrent set), rs200 -> rs150 (old row, rsLow used)
+        let mut dump = File::create(&dump_path).unwrap();
+        writeln!(dump, "100\t60\t130\t0\t2009-01-01\t2009-01-01\t50\t0\t").unwrap();
+        writeln!(dump, "200\t150\t100\t0\t2002-01-01\t2002-01-01\t\t\t").unwrap();
+        drop(dump);
+
+        assert_eq!(RsidMergeTable::import_dump(&dump_path, &db_path).unwrap(), 2);
+        let table = RsidMergeTable::open(&db_path).unwrap();
+
+        assert_eq!(table.current_rsid("rs100").unwrap().as_deref(), Some("rs50"));
+        assert_eq!(table.current_rsid("rs200").unwrap().as_deref(), Some("rs150"));
+        assert_eq!(table.current_rsid("rs50").unwrap(), None);
+        assert_eq!(table.current_rsid("i3000001").unwrap(), None);
+
+        let record = |rsid: &str, position: u64| Genome23Record {
+            rsid: rsid.to_string(),
+            chromosome: "1".to_string(),
+            position,
+            genotype: "AG".to_string(),
+        };
+        let mut genome: GenomeByChromosome = HashMap::new();
+        let chr1 = genome.entry("1".to_string()).or_default();
+        chr1.insert(10, record("rs100", 10));
+        chr1.insert(20, record("i3000001", 20));
+
+        assert_eq!(table.canonicalize_genome(&mut genome).unwrap(), 1);
+        assert_eq!(genome["1"][&10].rsid, "rs50");
+        assert_eq!(genome["1"][&20].rsid, "i3000001");
+    }
+
+    /// Build a merge database from (rsHigh, rsLow, rsCurrent) rows
+    fn merge_table(dir: &tempfile::TempDir, rows: &[(i64, i64, Option<i64>)]) -> RsidMergeTable {
+        let dump_path = dir.path().join("RsMergeArch.bcp");
+        let db_path = dir.path().join("rsid_merge.db");
+        let mut dump = File::create(&dump_path).unwrap();
+        for (rs_high, rs_low, rs_current) in rows {
+            let current = rs_current.map(|c| c.to_string()).unwrap_or_default();
+            writeln!(dump, "{}\t{}\t130\t0\t2009-01-01\t2009-01-01\t{}\t0\t", rs_high, rs_low, current).unwrap();
+        }
+        drop(dump);
+
+        RsidMergeTable::import_dump(&dump_path, &db_path).unwrap();
+        RsidMergeTable::open(&db_path).unwrap()
+    }
+
+    #[test]
+    fn test_chained_merges_resolve_to_current() {
+        let dir = tempfile::tempdir().unwrap();
+        // Old rows only name rsLow: rs1 -> rs2 -> rs3, and rs10 -> rs11 -> rs12 -> rs13
+        let table = merge_table(
+            &dir,
+            &[(1, 2, None), (2, 3, None), (10, 11, None), (11, 12, Some(12)), (12, 13, None)],
+        );
+
+        assert_eq!(table.current_rsid("rs1").unwrap().as_deref(), Some("rs3"));
+        assert_eq!(table.current_rsid("rs2").unwrap().as_deref(), Some("rs3"));
+        assert_eq!(table.current_rsid("rs3").unwrap(), None);
+        assert_eq!(table.current_rsid("rs10").unwrap().as_deref(), Some("rs13"));
+
+        let mut variants = vec![ReferencePanelVariant {
+            chromosome: 1,
+            position: 100,
+            rsid: Some("rs1".to_string()),
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            phased: true,
+            allele_freq: None,
+            minor_allele_freq: None,
+            imputation_quality: None,
+            is_typed: false,
+            sample_genotypes: Vec::new(),
+        }];
+        assert_eq!(table.canonicalize_panel(&mut variants).unwrap(), 1);
+        assert_eq!(variants[0].rsid.as_deref(), Some("rs3"));
+    }
+
+    #[test]
+    fn test_merge_loops_are_left_unresolved() {
+        let dir = tempfile::tempdir().unwrap();
+        // rs1 <-> rs2, rs5 -> rs6 -> rs7 -> rs6, and a self-merge of rs9
+        let table = merge_table(
+            &dir,
+            &[(1, 2, None), (2, 1, None), (5, 6, None), (6, 7, None), (7, 6, None), (9, 9, None)],
+        );
+
+        assert_eq!(table.current_rsid("rs1").unwrap(), None);
+        assert_eq!(table.current_rsid("rs2").unwrap(), None);
+        assert_eq!(table.current_rsid("rs5").unwrap(), None);
+        assert_eq!(table.current_rsid("rs9").unwrap(), None);
+    }
+
+    #[test]
+    fn test_ids_without_rs_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = merge_table(&dir, &[(100, 50, Some(50))]);
+
+        assert_eq!(rs_number("rs100"), Some(100));
+        assert_eq!(rs_number(" RS100 "), Some(100));
+        assert_eq!(table.current_rsid("RS100").unwrap().as_deref(), Some("rs50"));
+
+        // Bare numbers, 23andMe internal IDs and positional IDs are never looked up
+        for rsid in ["100", "i100", "1:100", "rs", "rsX100", ""] {
+            assert_eq!(rs_number(rsid), None, "{}", rsid);
+            assert_eq!(table.current_rsid(rsid).unwrap(), None, "{}", rsid);
+        }
+    }
+}
@@ -17,6 +17,8 @@ use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 use tracing::{debug, info};
 
+use crate::parsers::is_genotype_csv_header;
+
 const MAX_FILE_SIZE: usize = 500 * 1024 * 1024; // 500 MB
 
 #[derive(Debug)]
@@ -38,9 +40,12 @@ impl FileValidator {
     pub fn new() -> Self {
         let mut allowed_types = HashMap::new();
 
-        // 23andMe raw text file (plain text, no specific magic number)
+        // 23andMe / AncestryDNA raw text file (plain text, no specific magic number)
         allowed_types.insert("txt".to_string(), vec![]);
 
+        // MyHeritage / FamilyTreeDNA raw CSV file (plain text)
+        allowed_types.insert("csv".to_string(), vec![]);
+
         // Gzip compressed files (VCF.gz)
         allowed_types.insert("vcf.gz".to_string(), vec![0x1f, 0x8b, 0x08]);
 
@@ -173,23 +178,32 @@ impl FileValidator {
 
     async fn validate_content(&self, path: &Path, ext: &str) -> Result<()> {
         match ext {
-            "txt" => self.validate_23andme_format(path),
+            "txt" | "csv" => self.validate_23andme_format(path),
             "vcf.gz" => self.validate_vcf_format(path),
             "vcf.gz.tbi" => Ok(()), // Tabix index, no content validation needed
             _ => Ok(()),
         }
     }
 
+    /// Validate a consumer raw genotype file
+    ///
+    /// Accepts 23andMe (4 tab-delimited columns), AncestryDNA (5 columns with
+    /// allele1/allele2) and MyHeritage/FTDNA quoted CSV with a
+    /// `RSID,CHROMOSOME,POSITION,RESULT` header.
     fn validate_23andme_format(&self, path: &Path) -> Result<()> {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
         let mut lines = reader.lines();
 
-        // Check for 23andMe header
+        // Check for 23andMe header (or the FTDNA CSV header, which has no comments)
         let first_line = lines
             .next()
             .ok_or_else(|| anyhow::anyhow!("File is empty"))??;
 
+        if is_genotype_csv_header(&first_line) {
+            return Ok(());
+        }
+
         if !first_line.contains("23andMe") && !first_line.starts_with('#') {
             anyhow::bail!("Not a valid 23andMe format file");
         }
@@ -198,11 +212,17 @@ impl FileValidator {
         for line in lines {
             let line = line?;
             if !line.starts_with('#') && !line.trim().is_empty() {
+                // MyHeritage: comment block followed by the CSV header
+                if is_genotype_csv_header(&line) {
+                    break;
+                }
+
                 // First data line should have 4 columns: rsid, chromosome, position, genotype
+                // (AncestryDNA: 5 columns with separate allele1/allele2)
                 let columns: Vec<&str> = line.split_whitespace().collect();
-                if columns.len() != 4 {
+                if columns.len() != 4 && columns.len() != 5 {
                     anyhow::bail!(
-                        "Invalid 23andMe format: expected 4 columns, found {}",
+                        "Invalid 23andMe format: expected 4 or 5 columns, found {}",
                         columns.len()
                     );
                 }
@@ -314,4 +334,30 @@ mod tests {
 
         assert!(validator.validate_23andme_format(temp_file.path()).is_ok());
     }
+
+    #[tokio::test]
+    async fn test_validate_genotype_csv_format() {
+        let validator = FileValidator::new();
+
+        // MyHeritage: comment block then CSV header
+        let mut myheritage = NamedTempFile::new().unwrap();
+        writeln!(myheritage, "# MyHeritage DNA raw data.").unwrap();
+        writeln!(myheritage, "RSID,CHROMOSOME,POSITION,RESULT").unwrap();
+        writeln!(myheritage, "\"rs12345\",\"1\",\"12345\",\"AA\"").unwrap();
+        myheritage.flush().unwrap();
+        assert!(validator.validate_23andme_format(myheritage.path()).is_ok());
+
+        // FTDNA: quoted CSV header on the first line
+        let mut ftdna = NamedTempFile::new().unwrap();
+        writeln!(ftdna, "\"RSID\",\"CHROMOSOME\",\"POSITION\",\"RESULT\"").unwrap();
+        writeln!(ftdna, "\"rs12345\",\"1\",\"12345\",\"AA\"").unwrap();
+        ftdna.flush().unwrap();
+        assert!(validator.validate_23andme_format(ftdna.path()).is_ok());
+
+        // Unrelated CSV is still rejected
+        let mut other = NamedTempFile::new().unwrap();
+        writeln!(other, "name,value").unwrap();
+        other.flush().unwrap();
+        assert!(validator.validate_23andme_format(other.path()).is_err());
+    }
 }
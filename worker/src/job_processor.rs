@@ -12,24 +12,39 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use redis::aio::ConnectionManager;
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use tracing::{info, warn};
 use uuid::Uuid;
 
 // Import from genetics-processor library
-use genetics_processor::genotype_converter::genotype_to_dosage;
+use genetics_processor::chromosomes::{self, chromosome_name, is_autosome, Sex, ALL_CHROMOSOMES};
+use genetics_processor::genome_build::{GenomeBuild, REFERENCE_PANEL_BUILD};
+use genetics_processor::concordance::ConcordanceAnalysis;
+use genetics_processor::genotype_converter::{
+    expand_haploid_call, genotype_to_dosage, genotype_to_dosage_with_strand, has_strand_mismatch,
+    HaploidConvention as ProcessorHaploidConvention, StrandStats,
+};
+use genetics_processor::liftover::{LiftOver, LiftOverStats};
 use genetics_processor::output::{OutputFormat as ProcessorOutputFormat, OutputGenerator};
 use genetics_processor::parsers::{
-    genome23andme::{Genome23Parser, Genome23Record},
-    pgs::PgsParser,
+    genome23andme::{GenomeByChromosome, Genome23Record},
+    imputation_info::{ImputationInfoByChromosome, ImputationInfoParser, ImputationInfoRecord},
+    pgs::{PgsDataset, PgsNormalization as ProcessorPgsNormalization, PgsParser},
+    pgs_catalog::PgsCatalogParser,
+    plink::PlinkFileset,
+    raw_genotype::{open_raw_genotype_source, DetectedProvider},
+    report::ParseReport,
     vcf::{VCFParser, VCFRecord},
 };
 use genetics_processor::processor::{DataSource, MergedVariant};
-use genetics_processor::models::{MultiSampleVariant, SampleData, QualityThreshold as ModelQualityThreshold};
+use genetics_processor::models::{Genotype, MultiSampleVariant, SampleData, QualityThreshold as ModelQualityThreshold, reference_sample_ids, USER_SAMPLE_ID};
+use genetics_processor::pgs_engine::PgsEngine;
+use genetics_processor::pgs_interpretation::{self, InterpretationConfig};
 use genetics_processor::reference_panel::ReferencePanelReader;
+use genetics_processor::rsid_merge::RsidMergeTable;
 
-use crate::queue::{JobQueue, OutputFormat, QualityThreshold};
+use crate::queue::{HaploidConvention, JobQueue, OutputFormat, PgsNormalization, QualityThreshold};
 
 /// Job processor that executes genetics data merging
 pub struct JobProcessor {
@@ -38,6 +53,12 @@ pub struct JobProcessor {
     upload_dir: PathBuf,
     output_dir: PathBuf,
     reference_panel_path: PathBuf,
+    pgs_scoring_dir: Option<PathBuf>,
+    liftover_chain_dir: Option<PathBuf>,
+    rsid_merge_db: Option<PathBuf>,
+    interpretation_config: InterpretationConfig,
+    pgs_normalization: PgsNormalization,
+    haploid_convention: HaploidConvention,
     db_pool: PgPool,
     redis_conn: ConnectionManager,
 }
@@ -58,11 +79,64 @@ impl JobProcessor {
             upload_dir,
             output_dir,
             reference_panel_path,
+            pgs_scoring_dir: None,
+            liftover_chain_dir: None,
+            rsid_merge_db: None,
+            interpretation_config: InterpretationConfig::default(),
+            pgs_normalization: PgsNormalization::default(),
+            haploid_convention: HaploidConvention::default(),
             db_pool,
             redis_conn,
         }
     }
 
+    /// Set the directory of PGS Catalog scoring files used to compute scores
+    /// when no scores file is uploaded
+    pub fn with_pgs_scoring_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.pgs_scoring_dir = dir;
+        self
+    }
+
+    /// Set the directory of UCSC chain files used to lift imputed VCFs
+    /// from another build onto the reference panel build
+    pub fn with_liftover_chain_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.liftover_chain_dir = dir;
+        self
+    }
+
+    /// Set the dbSNP rsID merge database used to replace retired rsIDs
+    /// in the genome file and reference panel with current rsIDs
+    pub fn with_rsid_merge_db(mut self, path: Option<PathBuf>) -> Self {
+        self.rsid_merge_db = path;
+        self
+    }
+
+    /// Set the per-trait R² and trait SD used to interpret the user's scores
+    pub fn with_interpretation_config(mut self, config: InterpretationConfig) -> Self {
+        self.interpretation_config = config;
+        self
+    }
+
+    /// Set the normalization applied to scaled polygenic scores
+    pub fn with_pgs_normalization(mut self, normalization: PgsNormalization) -> Self {
+        self.pgs_normalization = normalization;
+        self
+    }
+
+    /// Set the dosage coding of haploid genotypes in the output (0/1 or 0/2)
+    pub fn with_haploid_convention(mut self, convention: HaploidConvention) -> Self {
+        self.haploid_convention = convention;
+        self
+    }
+
+    /// Haploid dosage convention in the processor's terms
+    fn processor_haploid_convention(&self) -> ProcessorHaploidConvention {
+        match self.haploid_convention {
+            HaploidConvention::AlleleCount => ProcessorHaploidConvention::AlleleCount,
+            HaploidConvention::Homozygous => ProcessorHaploidConvention::Homozygous,
+        }
+    }
+
     /// Get VCF format preference from job metadata
     async fn get_vcf_format_preference(&self) -> Result<genetics_processor::output::VcfFormat> {
         use genetics_processor::output::VcfFormat;
@@ -93,6 +167,22 @@ impl JobProcessor {
         Ok(VcfFormat::Merged)
     }
 
+    /// Merge keys into the job's metadata JSON
+    ///
+    /// Existing keys not present in `values` are preserved.
+    async fn merge_job_metadata(&self, values: serde_json::Value) -> Result<()> {
+        sqlx::query(
+            "UPDATE genetics_jobs SET metadata = COALESCE(metadata, '{}'::jsonb) || $1 WHERE id = $2"
+        )
+        .bind(values)
+        .bind(self.job_id)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to update job metadata")?;
+
+        Ok(())
+    }
+
     /// Main processing function
     pub async fn process(&self, output_formats: &[OutputFormat], quality_threshold: QualityThreshold) -> Result<()> {
         info!("Starting multi-sample genetics processing (51 samples) for job {} with quality threshold: {:?}",
@@ -119,17 +209,45 @@ impl JobProcessor {
 
         // Step 3: Parse 23andMe genome file
         self.publish_progress(20.0, "Parsing 23andMe genome data").await?;
-        let genome_data = self.parse_genome_file(&files.genome_file).await?;
-        info!("Parsed {} genome records", genome_data.len());
+        let (genome_provider, genome_data, genome_report) = self.parse_genome_file(&files.genome_file).await?;
+        let genome_data = self.canonicalize_genome_rsids(genome_data).await?;
+        let genome_record_count: usize = genome_data.values().map(|m| m.len()).sum();
+        info!("Parsed {} genome records ({})", genome_record_count, genome_provider.description());
+        self.merge_job_metadata(serde_json::json!({ "genome_provider": genome_provider.to_json() })).await?;
         self.publish_progress(
             25.0,
-            &format!("Loaded {} genotyped variants from 23andMe", genome_data.len())
+            &format!("Loaded {} genotyped variants from 23andMe", genome_record_count)
         ).await?;
 
         // Step 4: Parse VCF files
         self.publish_progress(30.0, &format!("Parsing {} VCF file(s)...", files.vcf_files.len())).await?;
-        let vcf_data = self.parse_vcf_files(&files.vcf_files).await?;
+        let (vcf_data, vcf_samples, vcf_builds, vcf_reports) = self.parse_vcf_files(&files.vcf_files).await?;
         info!("Parsed VCF data for {} chromosomes", vcf_data.len());
+        if !vcf_samples.is_empty() {
+            info!("Merging {} extra imputed sample(s): {}", vcf_samples.len(), vcf_samples.join(", "));
+        }
+        let vcf_build = check_vcf_build(&vcf_builds)?;
+        self.merge_job_metadata(serde_json::json!({ "genome_build": vcf_build.as_str() })).await?;
+
+        // Imputation server .info files (optional) annotate typed/imputed status
+        let (imputation_info, info_reports) = self.parse_info_files(&files.info_files).await;
+        if !imputation_info.is_empty() {
+            let info_variants: usize = imputation_info.values().map(|m| m.len()).sum();
+            info!("Loaded imputation info for {} variants", info_variants);
+        }
+
+        // TOPMed (GRCh38) results are lifted onto the reference panel build,
+        // together with their .info annotation so the two still line up
+        let (vcf_data, imputation_info, liftover_stats) = if vcf_build == REFERENCE_PANEL_BUILD {
+            (vcf_data, imputation_info, None)
+        } else {
+            self.publish_progress(38.0, &format!("Lifting imputed variants from {} to {}",
+                vcf_build.as_str(), REFERENCE_PANEL_BUILD.as_str())).await?;
+            let liftover = self.load_liftover(vcf_build, REFERENCE_PANEL_BUILD).await?;
+            let (lifted, stats) = lift_vcf_data(&liftover, vcf_data);
+            info!("VCF liftover: {}", stats.summary());
+            (lifted, lift_info_data(&liftover, imputation_info), Some(stats))
+        };
         let total_vcf_variants: usize = vcf_data.values().map(|v| v.len()).sum();
         self.publish_progress(
             40.0,
@@ -151,17 +269,48 @@ impl JobProcessor {
                 &format!("Loaded {} polygenic scores for {} traits",
                     data.unscaled.len(), trait_count)
             ).await?;
+        }
+
+        // Without uploaded scores, compute them from PGS Catalog scoring files
+        let pgs_engine = if pgs_data.is_none() {
+            self.load_pgs_engine().await
         } else {
+            None
+        };
+        if let Some(ref engine) = pgs_engine {
+            self.publish_progress(
+                50.0,
+                &format!("Loaded {} PGS Catalog scoring file(s) - scores will be computed", engine.score_count())
+            ).await?;
+        } else if pgs_data.is_none() {
             info!("No PGS data available, continuing without polygenic scores");
             self.publish_progress(50.0, "No PGS data - continuing without polygenic scores").await?;
         }
 
+        // Record parse diagnostics so users can see why variants were dropped
+        let mut parse_reports = vec![genome_report];
+        parse_reports.extend(vcf_reports);
+        parse_reports.extend(info_reports);
+        if let Some(ref data) = pgs_data {
+            parse_reports.push(data.report.clone());
+        }
+        self.merge_job_metadata(serde_json::json!({ "parse_reports": parse_reports })).await?;
+
         // Step 6 & 7: Merge and stream output chromosome-by-chromosome (memory-efficient)
-        self.publish_progress(55.0, "Starting streaming multi-sample processing (51 samples × 22 autosomes)").await?;
+        let sex = chromosomes::infer_sex_from_genome(&genome_data);
+        info!("Inferred sex from chrX heterozygosity: {}", sex.as_str());
+
+        self.publish_progress(55.0, "Starting streaming multi-sample processing (51 samples × autosomes, X, Y, MT)").await?;
         let output_paths = self.merge_and_stream_chromosomes(
+            &genome_provider,
             &genome_data,
+            sex,
             &vcf_data,
+            &vcf_samples,
+            liftover_stats,
+            &imputation_info,
             pgs_data.as_ref(),
+            pgs_engine,
             quality_threshold,
             output_formats
         ).await?;
@@ -181,6 +330,8 @@ impl JobProcessor {
         let mut genome_file: Option<PathBuf> = None;
         let mut vcf_files: Vec<PathBuf> = Vec::new();
         let mut pgs_file: Option<PathBuf> = None;
+        let mut plink_files: Vec<PathBuf> = Vec::new();
+        let mut info_files: Vec<PathBuf> = Vec::new();
 
         let mut entries = tokio::fs::read_dir(&self.upload_dir)
             .await
@@ -191,33 +342,78 @@ impl JobProcessor {
             let filename = entry.file_name();
             let filename_str = filename.to_string_lossy();
 
-            if filename_str.ends_with(".txt") && !filename_str.contains("scores") {
+            if (filename_str.ends_with(".txt") || filename_str.ends_with(".csv"))
+                && !filename_str.contains("scores")
+            {
+                // 23andMe/AncestryDNA (.txt) or MyHeritage/FTDNA (.csv) raw data
                 genome_file = Some(path);
             } else if filename_str.ends_with(".vcf.gz") || filename_str.ends_with(".vcf") {
                 vcf_files.push(path);
-            } else if filename_str.contains("scores") && filename_str.ends_with(".txt") {
+            } else if filename_str.ends_with(".info.gz") || filename_str.ends_with(".info") {
+                // Per-variant quality from the imputation server (chrN.info.gz)
+                info_files.push(path);
+            } else if filename_str.ends_with(".pgs")
+                || (filename_str.contains("scores")
+                    && [".txt", ".txt.gz", ".csv"].iter().any(|ext| filename_str.ends_with(ext)))
+            {
+                // PGS scores (comma, tab or semicolon separated, optionally gzipped)
                 pgs_file = Some(path);
+            } else if [".bed", ".bim", ".fam"].iter().any(|ext| filename_str.ends_with(ext)) {
+                // PLINK fileset from a research array (grouped below)
+                plink_files.push(path);
             }
         }
 
+        // A complete PLINK fileset stands in for the raw genome file
+        if genome_file.is_none() && !plink_files.is_empty() {
+            let mut filesets: Vec<PlinkFileset> = plink_files.iter().map(PlinkFileset::from_path).collect();
+            filesets.sort_by(|a, b| a.bed.cmp(&b.bed));
+            filesets.dedup();
+            if filesets.len() > 1 {
+                anyhow::bail!("Found {} PLINK filesets, expected one", filesets.len());
+            }
+            let fileset = filesets.remove(0);
+            if !fileset.exists() {
+                anyhow::bail!("PLINK fileset {:?} is incomplete (needs .bed, .bim and .fam)", fileset.bed);
+            }
+            info!("Using PLINK fileset {:?} as genome data", fileset.bed);
+            genome_file = Some(fileset.bed);
+        }
+
         Ok(UploadedFiles {
             genome_file: genome_file.context("No genome file found")?,
             vcf_files,
+            info_files,
             pgs_file,
         })
     }
 
-    /// Parse 23andMe genome file
-    async fn parse_genome_file(&self, path: &PathBuf) -> Result<Vec<Genome23Record>> {
-        let parser = Genome23Parser::new();
-        let records = parser.parse(path)
+    /// Parse raw genome file (provider detected from the file header)
+    ///
+    /// The file is read once and grouped by chromosome so the per-chromosome
+    /// merge can look records up by position without re-filtering.
+    async fn parse_genome_file(&self, path: &PathBuf) -> Result<(DetectedProvider, GenomeByChromosome, ParseReport)> {
+        let (mut provider, parser) = open_raw_genotype_source(path, Vec::new())
+            .context("Failed to detect raw genotype file provider")?;
+        let (records, report) = parser.parse_grouped_with_report(path)
             .context("Failed to parse 23andMe genome file")?;
-        Ok(records)
+        provider.record_parse(&report);
+        info!("Detected genome file provider: {}", provider.description());
+        info!("Genome parse report: {}", report.summary());
+        Ok((provider, records, report))
     }
 
-    /// Parse VCF files
-    async fn parse_vcf_files(&self, paths: &[PathBuf]) -> Result<HashMap<u8, Vec<VCFRecord>>> {
+    /// Parse VCF files, returning records by chromosome, the extra sample
+    /// names, and the detected genome build and parse report of each file
+    ///
+    /// The user is the last sample of each VCF; any samples before it (e.g.
+    /// family members imputed in the same batch) are returned by header name
+    /// and must be the same in every file.
+    async fn parse_vcf_files(&self, paths: &[PathBuf]) -> Result<(HashMap<u8, Vec<VCFRecord>>, Vec<String>, Vec<GenomeBuild>, Vec<ParseReport>)> {
         let mut all_records = HashMap::new();
+        let mut extra_samples: Option<Vec<String>> = None;
+        let mut builds = Vec::with_capacity(paths.len());
+        let mut reports = Vec::with_capacity(paths.len());
         let total_files = paths.len();
 
         for (idx, path) in paths.iter().enumerate() {
@@ -236,6 +432,16 @@ impl JobProcessor {
 
             let record_count = records.len();
 
+            let file_samples = parser.sample_names[..parser.sample_names.len().saturating_sub(1)].to_vec();
+            match &extra_samples {
+                Some(samples) if *samples != file_samples => anyhow::bail!(
+                    "VCF file {} lists samples {:?} before the user, but earlier files list {:?}",
+                    filename, file_samples, samples
+                ),
+                Some(_) => {}
+                None => extra_samples = Some(file_samples),
+            }
+
             // Group by chromosome
             for record in records {
                 all_records
@@ -244,10 +450,70 @@ impl JobProcessor {
                     .push(record);
             }
 
-            info!("Parsed {} variants from {}", record_count, filename);
+            info!(
+                "Parsed {} variants from {} ({} multi-allelic records split)",
+                record_count, filename, parser.split_multiallelic_count
+            );
+            info!("VCF parse report: {}", parser.report.summary());
+            builds.push(parser.genome_build);
+            reports.push(parser.report);
         }
 
-        Ok(all_records)
+        Ok((all_records, extra_samples.unwrap_or_default(), builds, reports))
+    }
+
+    /// Parse imputation server .info files
+    ///
+    /// Files that fail to parse are skipped with a warning; the merge then
+    /// falls back to the VCF INFO R2 for their variants.
+    async fn parse_info_files(&self, paths: &[PathBuf]) -> (ImputationInfoByChromosome, Vec<ParseReport>) {
+        let mut all_records: ImputationInfoByChromosome = HashMap::new();
+        let mut reports = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            match ImputationInfoParser::parse(path) {
+                Ok((records, report)) => {
+                    info!("Imputation info parse report: {}", report.summary());
+                    for (chr, chr_records) in records {
+                        all_records.entry(chr).or_default().extend(chr_records);
+                    }
+                    reports.push(report);
+                }
+                Err(e) => warn!("Skipping imputation info file {:?}: {}", path, e),
+            }
+        }
+
+        (all_records, reports)
+    }
+
+    /// Load the chain file converting `from` coordinates to `to`
+    ///
+    /// Chain files are matched by their UCSC name (e.g. "hg38ToHg19.over.chain.gz").
+    async fn load_liftover(&self, from: GenomeBuild, to: GenomeBuild) -> Result<LiftOver> {
+        let no_chain = || anyhow::anyhow!(
+            "Imputed VCFs are {} but the reference panel is {}, and no {} to {} chain file is available; \
+             re-run imputation on {}",
+            from.as_str(), to.as_str(), from.as_str(), to.as_str(), to.as_str()
+        );
+        let dir = self.liftover_chain_dir.as_ref().ok_or_else(no_chain)?;
+
+        let mut entries = tokio::fs::read_dir(dir)
+            .await
+            .context("Failed to read liftover chain directory")?;
+        while let Some(entry) = entries.next_entry().await? {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if LiftOver::builds_from_file_name(&filename) != Some((from, to)) {
+                continue;
+            }
+
+            info!("Loading liftover chain file {}", filename);
+            let path = entry.path();
+            return tokio::task::spawn_blocking(move || LiftOver::load(&path, from, to))
+                .await?
+                .with_context(|| format!("Failed to load chain file {}", filename));
+        }
+
+        Err(no_chain())
     }
 
     /// Parse PGS scores file
@@ -272,15 +538,89 @@ impl JobProcessor {
         }
     }
 
+    /// Replace retired rsIDs in the genome data with their current rsIDs
+    ///
+    /// Does nothing without an rsID merge database. 23andMe internal IDs
+    /// ("i"-prefixed) have no dbSNP entry and are left unchanged.
+    async fn canonicalize_genome_rsids(&self, mut genome_data: GenomeByChromosome) -> Result<GenomeByChromosome> {
+        let Some(path) = self.rsid_merge_db.clone() else {
+            return Ok(genome_data);
+        };
+
+        let (genome_data, renamed) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let merge_table = RsidMergeTable::open(&path)?;
+            let renamed = merge_table.canonicalize_genome(&mut genome_data)?;
+            Ok((genome_data, renamed))
+        }).await??;
+
+        info!("Replaced {} retired rsIDs in genome data", renamed);
+        self.merge_job_metadata(serde_json::json!({ "rsid_merge": { "genome_renamed": renamed } })).await?;
+        Ok(genome_data)
+    }
+
+    /// Load PGS Catalog scoring files from the scoring directory
+    ///
+    /// Files that fail to parse are skipped with a warning.
+    ///
+    /// # Returns
+    /// * `Some(PgsEngine)` - At least one scoring file was loaded
+    /// * `None` - No scoring directory or no usable scoring files
+    async fn load_pgs_engine(&self) -> Option<PgsEngine> {
+        let dir = self.pgs_scoring_dir.as_ref()?;
+
+        let mut entries = match tokio::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Failed to read PGS scoring directory {:?}: {}", dir, e);
+                return None;
+            }
+        };
+
+        let mut scoring_files = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if !(filename.ends_with(".txt") || filename.ends_with(".txt.gz")) {
+                continue;
+            }
+
+            match PgsCatalogParser::parse(&path) {
+                Ok(scoring) => {
+                    info!("Loaded scoring file {} ({} variants)", scoring.label(), scoring.variants.len());
+                    scoring_files.push(scoring);
+                }
+                Err(e) => warn!("Skipping PGS scoring file {}: {}", filename, e),
+            }
+        }
+
+        if scoring_files.is_empty() {
+            None
+        } else {
+            Some(PgsEngine::new(scoring_files))
+        }
+    }
+
     /// Merge and stream output chromosome-by-chromosome (memory-efficient)
     ///
     /// This method processes chromosomes one at a time, writing output immediately
-    /// to avoid accumulating all 22 chromosomes in memory (~31GB).
+    /// to avoid accumulating all chromosomes in memory (~31GB). X, Y and MT are
+    /// skipped when the reference panel has no variants for them. When a PGS
+    /// engine is given, each merged chromosome is scored before it is dropped
+    /// and the computed scores replace `pgs_data`. Liftover stats, when the
+    /// VCFs were lifted, gain REF mismatches against the panel and are
+    /// recorded in job metadata.
+    #[allow(clippy::too_many_arguments)]
     async fn merge_and_stream_chromosomes(
         &self,
-        genome_data: &[Genome23Record],
+        genome_provider: &DetectedProvider,
+        genome_data: &GenomeByChromosome,
+        sex: Sex,
         vcf_data: &HashMap<u8, Vec<VCFRecord>>,
-        pgs_data: Option<&genetics_processor::parsers::pgs::PgsDataset>,
+        vcf_samples: &[String],
+        mut liftover_stats: Option<LiftOverStats>,
+        imputation_info: &ImputationInfoByChromosome,
+        pgs_data: Option<&PgsDataset>,
+        mut pgs_engine: Option<PgsEngine>,
         quality_threshold: QualityThreshold,
         output_formats: &[OutputFormat],
     ) -> Result<HashMap<String, PathBuf>> {
@@ -294,6 +634,11 @@ impl JobProcessor {
         info!("════════════════════════════════════════════════════════════════");
 
         let mut total_variants = 0usize;
+        let mut strand_stats: BTreeMap<String, StrandStats> = BTreeMap::new();
+        let mut rsid_joined: BTreeMap<String, usize> = BTreeMap::new();
+        let mut concordance = ConcordanceAnalysis::new();
+        let no_genome_records: HashMap<u64, Genome23Record> = HashMap::new();
+        let no_info_records = HashMap::new();
 
         // Convert QualityThreshold
         let model_threshold = match quality_threshold {
@@ -316,7 +661,9 @@ impl JobProcessor {
             self.job_id.to_string(),
             self.user_id.clone(),
             self.output_dir.clone(),
-        );
+        )
+        .with_genome_file(genome_provider.description())
+        .with_extra_samples(vcf_samples.to_vec());
 
         // Get VCF format preference from job metadata
         use genetics_processor::output::VcfFormat;
@@ -327,45 +674,63 @@ impl JobProcessor {
         info!("✓ Streaming output initialized (files created, headers written)");
 
         // Process each chromosome and stream output immediately
-        for chr in 1..=22u8 {
+        let total_chromosomes = ALL_CHROMOSOMES.len();
+        for (chr_idx, chr) in ALL_CHROMOSOMES.into_iter().enumerate() {
+            let chr_name = chromosome_name(chr);
             info!("════════════════════════════════════════════════════════════════");
-            info!("▶ CHROMOSOME {} / 22", chr);
+            info!("▶ CHROMOSOME {} ({}/{})", chr_name, chr_idx + 1, total_chromosomes);
             info!("════════════════════════════════════════════════════════════════");
 
             // Load reference panel for this chromosome
-            info!("  [1/4] Loading reference panel for chromosome {}...", chr);
+            info!("  [1/4] Loading reference panel for chromosome {}...", chr_name);
             let ref_variants = tokio::task::spawn_blocking({
                 let path = self.reference_panel_path.clone();
+                let rsid_merge_db = self.rsid_merge_db.clone();
                 let chr_num = chr;
                 move || -> Result<Vec<genetics_processor::models::ReferencePanelVariant>> {
                     let reference_panel = ReferencePanelReader::open(&path)?;
-                    let variants = reference_panel.get_chromosome_variants(chr_num)?;
+                    let mut variants = reference_panel.get_chromosome_variants(chr_num)?;
+                    if let Some(db) = rsid_merge_db {
+                        RsidMergeTable::open(&db)?.canonicalize_panel(&mut variants)?;
+                    }
                     Ok(variants)
                 }
             }).await??;
 
+            // Sex chromosomes and MT are optional in the reference panel
+            if ref_variants.is_empty() && !is_autosome(chr) {
+                info!("  Reference panel has no chr{} variants, skipping", chr_name);
+                continue;
+            }
+
             let ref_panel_size_mb = (ref_variants.len() * 50 * 50) / 1_048_576; // Conservative estimate
             info!("  ✓ Loaded {} reference variants (~{} MB estimated)", ref_variants.len(), ref_panel_size_mb);
 
             // Get user data for this chromosome
-            info!("  [2/4] Extracting user data for chromosome {}...", chr);
-            let chr_str = chr.to_string();
-            let chr_genome: Vec<_> = genome_data.iter()
-                .filter(|r| r.chromosome == chr_str)
-                .cloned()
-                .collect();
+            info!("  [2/4] Extracting user data for chromosome {}...", chr_name);
+            let chr_genome = genome_data.get(&chr_name).unwrap_or(&no_genome_records);
             let chr_vcf = vcf_data.get(&chr).map(|v| v.as_slice()).unwrap_or(&[]);
+            let chr_info = imputation_info.get(&chr).unwrap_or(&no_info_records);
             info!("  ✓ User data: {} genome records, {} VCF variants", chr_genome.len(), chr_vcf.len());
+            if let Some(stats) = liftover_stats.as_mut() {
+                stats.count_ref_mismatches(chr_vcf, &ref_variants);
+            }
 
             // Merge this chromosome
-            info!("  [3/4] Merging chromosome {} (50 reference + 1 user = 51 samples)...", chr);
-            let merged = self.merge_single_chromosome_multi_sample(
+            info!("  [3/4] Merging chromosome {} (50 reference + 1 user = 51 samples)...", chr_name);
+            let (mut merged, chr_stats) = Self::merge_single_chromosome_multi_sample(
                 chr,
                 &ref_variants,
-                &chr_genome,
+                chr_genome,
                 chr_vcf,
-                model_threshold
+                vcf_samples,
+                chr_info,
+                sex,
+                model_threshold,
+                &mut concordance
             )?;
+            strand_stats.insert(chr_name.clone(), chr_stats.strand);
+            rsid_joined.insert(chr_name.clone(), chr_stats.rsid_joined);
 
             let variant_count = merged.len();
             total_variants += variant_count;
@@ -376,30 +741,94 @@ impl JobProcessor {
             drop(ref_variants);
             info!("  ✓ Reference panel memory freed");
 
+            // Scores use allele counts; the haploid convention only codes the output
+            if let Some(engine) = pgs_engine.as_mut() {
+                engine.add_chromosome(&merged);
+            }
+            self.processor_haploid_convention().apply(&mut merged);
+
             // IMMEDIATELY write to output files - do NOT accumulate in memory
-            info!("  [4/4] Writing chromosome {} to output files...", chr);
+            info!("  [4/4] Writing chromosome {} to output files...", chr_name);
             output_gen.append_chromosome(chr, &merged).await?;
-            info!("  ✓ Chromosome {} written to all output formats", chr);
+            info!("  ✓ Chromosome {} written to all output formats", chr_name);
 
             // Drop merged data - no longer needed!
             drop(merged);
-            info!("  ✓ Chromosome {} memory freed (peak memory released)", chr);
+            info!("  ✓ Chromosome {} memory freed (peak memory released)", chr_name);
 
             // Publish progress
-            let progress = 60.0 + (chr as f32 / 22.0) * 25.0; // 60-85% range
+            let progress = 60.0 + ((chr_idx + 1) as f32 / total_chromosomes as f32) * 25.0; // 60-85% range
             self.publish_progress(
                 progress,
-                &format!("Processed and wrote chromosome {}/22 ({} total variants)", chr, total_variants)
+                &format!("Processed and wrote chromosome {} ({}/{}, {} total variants)", chr_name, chr_idx + 1, total_chromosomes, total_variants)
             ).await?;
 
-            info!("✓ Chromosome {}/22 complete ({} accumulated variants)", chr, total_variants);
+            info!("✓ Chromosome {} complete ({} accumulated variants)", chr_name, total_variants);
         }
 
         info!("════════════════════════════════════════════════════════════════");
-        info!("All 22 chromosomes processed successfully!");
+        info!("All chromosomes processed successfully!");
         info!("Total: {} variants × 51 samples", total_variants);
         info!("Peak memory: ~2-3GB (one chromosome at a time)");
         info!("════════════════════════════════════════════════════════════════");
+        self.merge_job_metadata(serde_json::json!({
+            "merge_stats": { "strand": strand_stats, "rsid_joined": rsid_joined }
+        })).await?;
+        if let Some(stats) = liftover_stats {
+            if stats.ref_mismatch > 0 {
+                warn!("VCF liftover: {} lifted variants disagree with the panel REF", stats.ref_mismatch);
+            }
+            self.merge_job_metadata(serde_json::json!({ "liftover": stats })).await?;
+        }
+
+        // Genotyped vs imputed agreement catches mismatched uploads
+        let concordance = concordance.report();
+        if concordance.flags.is_empty() {
+            info!("Genotyped vs imputed concordance: {}", concordance.summary());
+        } else {
+            warn!("Genotyped vs imputed concordance: {}", concordance.summary());
+        }
+        self.merge_job_metadata(serde_json::json!({ "concordance": concordance })).await?;
+        output_gen.set_concordance(&concordance)?;
+
+        // Write polygenic scores (computed in-house or uploaded)
+        let computed_pgs = match pgs_engine {
+            Some(engine) => {
+                let result = engine.finish();
+                for coverage in &result.coverage {
+                    info!(
+                        "PGS {}: {}/{} variants matched ({:.1}% coverage, {} swapped, {} strand-flipped)",
+                        coverage.label, coverage.variants_matched, coverage.variants_total,
+                        coverage.coverage() * 100.0, coverage.variants_swapped, coverage.variants_flipped
+                    );
+                }
+                self.merge_job_metadata(serde_json::json!({ "pgs_coverage": result.coverage })).await?;
+                result.dataset
+            }
+            None => None,
+        };
+        if let Some(mut scores) = computed_pgs.or_else(|| pgs_data.cloned()) {
+            let normalization = match self.pgs_normalization {
+                PgsNormalization::ReferenceZscore => ProcessorPgsNormalization::ReferenceZScore,
+                PgsNormalization::Zscore => ProcessorPgsNormalization::ZScore,
+                PgsNormalization::RankInverseNormal => ProcessorPgsNormalization::RankInverseNormal,
+                PgsNormalization::MinMax => ProcessorPgsNormalization::MinMax,
+                PgsNormalization::None => ProcessorPgsNormalization::None,
+            };
+            scores.rescale(normalization, &reference_sample_ids());
+            info!("PGS normalization: {}", normalization.as_str());
+
+            output_gen.append_pgs_scores(&scores).await?;
+
+            let interpretations = pgs_interpretation::interpret(&scores, &reference_sample_ids(), &self.interpretation_config);
+            for i in &interpretations {
+                info!(
+                    "PGS {}: z = {:.2}, {:.1}th percentile of {} reference samples",
+                    i.trait_label, i.z_score, i.percentile, i.reference_count
+                );
+            }
+            output_gen.append_pgs_interpretation(&interpretations).await?;
+        }
 
         // Finalize streaming output (close files, write metadata, create indexes)
         self.publish_progress(90.0, "Finalizing output files (metadata, indexes)...").await?;
@@ -429,11 +858,12 @@ impl JobProcessor {
     #[allow(dead_code)]
     async fn merge_chromosomes_multi_sample(
         &self,
-        genome_data: &[Genome23Record],
+        genome_data: &GenomeByChromosome,
         vcf_data: &HashMap<u8, Vec<VCFRecord>>,
         quality_threshold: QualityThreshold,
     ) -> Result<HashMap<u8, Vec<MultiSampleVariant>>> {
         let mut merged_chromosomes = HashMap::new();
+        let no_genome_records: HashMap<u64, Genome23Record> = HashMap::new();
 
         // Convert QualityThreshold to ModelQualityThreshold
         let model_threshold = match quality_threshold {
@@ -456,25 +886,25 @@ impl JobProcessor {
 
             info!("Loaded {} reference panel variants for chromosome {}", ref_variants.len(), chr);
 
-            // Filter genome data for this chromosome (23andMe uses String chromosomes)
-            let chr_str = chr.to_string();
-            let chr_genome: Vec<_> = genome_data
-                .iter()
-                .filter(|r| r.chromosome == chr_str)
-                .cloned()
-                .collect();
+            // Genome data for this chromosome (23andMe uses String chromosomes)
+            let chr_genome = genome_data.get(&chr.to_string()).unwrap_or(&no_genome_records);
 
             // Get VCF data for this chromosome
             let chr_vcf = vcf_data.get(&chr).map(|v| v.as_slice()).unwrap_or(&[]);
 
-            // Merge multi-sample chromosome data
-            let merged = self.merge_single_chromosome_multi_sample(
+            // Merge multi-sample chromosome data (autosomes only, always diploid)
+            let (mut merged, _) = Self::merge_single_chromosome_multi_sample(
                 chr,
                 &ref_variants,
-                &chr_genome,
+                chr_genome,
                 chr_vcf,
-                model_threshold
+                &[],
+                &HashMap::new(),
+                Sex::Unknown,
+                model_threshold,
+                &mut ConcordanceAnalysis::new()
             )?;
+            self.processor_haploid_convention().apply(&mut merged);
 
             let variant_count = merged.len();
             info!("Merged chromosome {}: {} variants × 51 samples", chr, variant_count);
@@ -660,30 +1090,61 @@ impl JobProcessor {
     }
 
     /// Merge a single chromosome's multi-sample data (50 reference + 1 user = 51 samples)
+    ///
+    /// The user sample is written with the ploidy implied by `sex` (haploid
+    /// on male non-PAR X, Y and MT). Imputed user genotypes are classified
+    /// from the imputation server's .info annotation when `info_records`
+    /// lists the variant, otherwise from the VCF INFO R2. Genotyped records
+    /// are joined by position, falling back to rsID when no genotyped record
+    /// sits at the panel position. Genotyped strand resolution and rsID joins
+    /// are counted in the returned stats. Homozygous A/T and C/G calls are
+    /// dropped when the chromosome's unambiguous calls show a strand
+    /// mismatch, and fall back to the imputed VCF like any other
+    /// unconvertible genotype. Converted
+    /// genotyped calls are compared with the imputed dosage in `concordance`.
+    /// Extra imputed samples (`vcf_samples`, the VCF samples before the user)
+    /// follow the user under their header names, with a missing call where
+    /// the VCF has no dosage for them.
+    #[allow(clippy::too_many_arguments)]
     fn merge_single_chromosome_multi_sample(
-        &self,
         chr: u8,
         ref_variants: &[genetics_processor::models::ReferencePanelVariant],
-        genome_records: &[Genome23Record],
+        genome_records: &HashMap<u64, Genome23Record>,
         vcf_records: &[VCFRecord],
+        vcf_samples: &[String],
+        info_records: &HashMap<(u64, String, String), ImputationInfoRecord>,
+        sex: Sex,
         quality_threshold: ModelQualityThreshold,
-    ) -> Result<Vec<MultiSampleVariant>> {
-        // Build lookups for user data by (position, ref_allele, alt_allele)
-        let mut user_genotyped_lookup: HashMap<(u64, String, String), &Genome23Record> = HashMap::new();
-        for record in genome_records {
-            let key = (record.position, record.genotype.chars().next().unwrap_or('-').to_string(),
-                       record.genotype.chars().nth(1).unwrap_or('-').to_string());
-            user_genotyped_lookup.insert(key, record);
-        }
-
+        concordance: &mut ConcordanceAnalysis,
+    ) -> Result<(Vec<MultiSampleVariant>, ChromosomeMergeStats)> {
+        // Genotyped data is matched by position only; alleles and strand are
+        // checked by genotype_to_dosage_with_strand against the panel REF/ALT
         let mut user_vcf_lookup: HashMap<(u64, String, String), &VCFRecord> = HashMap::new();
         for record in vcf_records {
             let key = (record.position, record.ref_allele.clone(), record.alt_allele.clone());
             user_vcf_lookup.insert(key, record);
         }
 
+        // Only "rs" IDs are joined on; 23andMe internal IDs are array-specific
+        let genome_by_rsid: HashMap<&str, &Genome23Record> = genome_records
+            .values()
+            .filter(|record| record.rsid.starts_with("rs"))
+            .map(|record| (record.rsid.as_str(), record))
+            .collect();
+
+        // Homozygous A/T and C/G calls are only dropped when the unambiguous
+        // SNPs show the genotype file is not on the forward strand
+        let strand_mismatch = has_strand_mismatch(ref_variants.iter().filter_map(|variant| {
+            let record = genome_records.get(&variant.position)?;
+            Some((record.genotype.as_str(), variant.ref_allele.as_str(), variant.alt_allele.as_str()))
+        }));
+        if strand_mismatch {
+            warn!("Chromosome {}: genotype calls are not on the forward strand, dropping homozygous A/T and C/G calls", chr);
+        }
+
         let mut merged = Vec::new();
         let mut filtered_count = 0;
+        let mut stats = ChromosomeMergeStats::default();
 
         for ref_variant in ref_variants {
             // Apply quality threshold filtering
@@ -699,66 +1160,108 @@ impl JobProcessor {
                 ref_variant.alt_allele.clone(),
             );
 
+            let ploidy = chromosomes::ploidy(chr, ref_variant.position, sex);
+
+            // The chromosome is absent (chrY in females): no call, whatever the
+            // genotype file or VCF report, and nothing to compare for concordance
+            let genotyped = if ploidy == 0 {
+                None
+            } else {
+                genome_records.get(&ref_variant.position).or_else(|| {
+                    let record = genome_by_rsid.get(ref_variant.rsid.as_deref()?).copied();
+                    stats.rsid_joined += usize::from(record.is_some());
+                    record
+                })
+            };
+
             // Try genotyped data first, then VCF
-            let user_sample = if let Some(genotyped) = user_genotyped_lookup.get(&key) {
-                // User has genotyped data for this variant
-                match genotype_to_dosage(&genotyped.genotype, &ref_variant.ref_allele, &ref_variant.alt_allele) {
-                    Ok(Some(dosage)) => SampleData {
-                        sample_id: "samp51".to_string(),
-                        genotype: format_dosage_as_genotype(dosage),
-                        dosage,
-                        source: DataSource::Genotyped,
-                        imputation_quality: None,
-                    },
+            let user_sample = if ploidy == 0 {
+                SampleData {
+                    sample_id: "samp51".to_string(),
+                    genotype: Genotype::missing(0),
+                    dosage: 0.0,
+                    source: DataSource::ImputedLowQual,
+                    imputation_quality: None,
+                }
+            } else if let Some(genotyped) = genotyped {
+                // User has genotyped data for this variant (hemizygous calls are
+                // reported as "A" or "AA", so convert to one allele at haploid sites)
+                let genotype = if ploidy == 1 {
+                    expand_haploid_call(&genotyped.genotype)
+                } else {
+                    genotyped.genotype.as_str().into()
+                };
+                let genotyped_dosage = genotype_to_dosage_with_strand(
+                    &genotype,
+                    &ref_variant.ref_allele,
+                    &ref_variant.alt_allele,
+                    strand_mismatch,
+                )
+                .map(|(dosage, resolution)| {
+                    stats.strand.record(resolution);
+                    match (dosage, ploidy) {
+                        (Some(d), 1) => chromosomes::haploid_dosage(d),
+                        (dosage, _) => dosage,
+                    }
+                });
+                match genotyped_dosage {
+                    Ok(Some(dosage)) => {
+                        if !vcf_records.is_empty() {
+                            let maf = ref_variant.minor_allele_freq
+                                .or_else(|| ref_variant.allele_freq.map(|af| af.min(1.0 - af)));
+                            let imputed = user_vcf_lookup.get(&key).map(|vcf| vcf.dosage);
+                            concordance.record(chr, maf, dosage, imputed);
+                        }
+                        SampleData {
+                            sample_id: "samp51".to_string(),
+                            genotype: format_dosage_as_genotype(dosage, ploidy),
+                            dosage,
+                            source: DataSource::Genotyped,
+                            imputation_quality: None,
+                        }
+                    }
                     _ => {
                         // Genotype conversion failed, try VCF
                         if let Some(vcf) = user_vcf_lookup.get(&key) {
-                            let source = if vcf.imputation_quality.unwrap_or(1.0) < 0.3 {
-                                DataSource::ImputedLowQual
-                            } else {
-                                DataSource::Imputed
-                            };
+                            let (source, imputation_quality) = imputed_source(vcf, info_records.get(&key));
                             SampleData {
                                 sample_id: "samp51".to_string(),
-                                genotype: format_dosage_as_genotype(vcf.dosage),
+                                genotype: format_dosage_as_genotype(vcf.dosage, ploidy),
                                 dosage: vcf.dosage,
                                 source,
-                                imputation_quality: vcf.imputation_quality,
+                                imputation_quality,
                             }
                         } else {
-                            // User has no data, use reference/reference (0|0)
+                            // User has no data: a missing call, so scoring mean-imputes it
                             SampleData {
                                 sample_id: "samp51".to_string(),
-                                genotype: "0|0".to_string(),
+                                genotype: Genotype::missing(ploidy),
                                 dosage: 0.0,
-                                source: DataSource::Imputed,
-                                imputation_quality: ref_variant.imputation_quality,
+                                source: DataSource::ImputedLowQual,
+                                imputation_quality: None,
                             }
                         }
                     }
                 }
             } else if let Some(vcf) = user_vcf_lookup.get(&key) {
-                // User has VCF data but not genotyped
-                let source = if vcf.imputation_quality.unwrap_or(1.0) < 0.3 {
-                    DataSource::ImputedLowQual
-                } else {
-                    DataSource::Imputed
-                };
+                // User has VCF data but not genotyped (the server may still
+                // have had the variant on the uploaded array)
+                let (source, imputation_quality) = imputed_source(vcf, info_records.get(&key));
                 SampleData {
                     sample_id: "samp51".to_string(),
-                    genotype: format_dosage_as_genotype(vcf.dosage),
+                    genotype: format_dosage_as_genotype(vcf.dosage, ploidy),
                     dosage: vcf.dosage,
                     source,
-                    imputation_quality: vcf.imputation_quality,
+                    imputation_quality,
                 }
             } else {
-                // User has no data for this variant, use reference/reference (0|0)
+                // User has no data for this variant: a missing call ("./." or ".")
                 SampleData {
                     sample_id: "samp51".to_string(),
-                    genotype: "0|0".to_string(),
+                    genotype: Genotype::missing(ploidy),
                     dosage: 0.0,
-                    source: DataSource::Imputed,
-                    imputation_quality: ref_variant.imputation_quality,
+                    source: DataSource::ImputedLowQual,
+                    imputation_quality: None,
                 }
             };
 
@@ -767,16 +1270,30 @@ impl JobProcessor {
             for (idx, genotype) in ref_variant.sample_genotypes.iter().enumerate() {
                 samples.push(SampleData {
                     sample_id: format!("samp{}", idx + 1),
-                    genotype: genotype.clone(),
-                    dosage: calculate_dosage_from_genotype(genotype),
+                    genotype: *genotype,
+                    // Missing reference calls count as REF
+                    dosage: genotype.alt_dosage().unwrap_or(0.0),
                     source: if ref_variant.is_typed { DataSource::Genotyped } else { DataSource::Imputed },
                     imputation_quality: ref_variant.imputation_quality,
                 });
             }
             samples.push(user_sample);
 
+            // Extra samples' sex is not inferred: haploid on Y and MT only
+            let vcf = user_vcf_lookup.get(&key);
+            let extra_ploidy = chromosomes::ploidy(chr, ref_variant.position, Sex::Unknown);
+            for (idx, name) in vcf_samples.iter().enumerate() {
+                samples.push(vcf.and_then(|vcf| vcf.sample_data(idx, name, extra_ploidy)).unwrap_or_else(|| SampleData {
+                    sample_id: name.clone(),
+                    genotype: Genotype::missing(extra_ploidy),
+                    dosage: 0.0,
+                    source: DataSource::ImputedLowQual,
+                    imputation_quality: None,
+                }));
+            }
+
             merged.push(MultiSampleVariant {
-                rsid: ref_variant.rsid.clone().unwrap_or_else(|| format!("{}:{}", chr, ref_variant.position)),
+                rsid: ref_variant.rsid.clone().unwrap_or_else(|| format!("{}:{}", chromosome_name(chr), ref_variant.position)),
                 chromosome: chr,
                 position: ref_variant.position,
                 ref_allele: ref_variant.ref_allele.clone(),
@@ -789,13 +1306,16 @@ impl JobProcessor {
         }
 
         info!(
-            "Chromosome {} multi-sample merge: {} variants × 51 samples ({} filtered by quality)",
+            "Chromosome {} multi-sample merge: {} variants × 51 samples ({} filtered by quality, {} genotypes joined by rsID, {} strand-flipped, {} ambiguous dropped)",
             chr,
             merged.len(),
-            filtered_count
+            filtered_count,
+            stats.rsid_joined,
+            stats.strand.flipped,
+            stats.strand.dropped
         );
 
-        Ok(merged)
+        Ok((merged, stats))
     }
 
     /// Generate output files in requested formats (OLD single-sample - deprecated)
@@ -977,39 +1497,526 @@ impl JobProcessor {
     }
 }
 
-/// Convert dosage value (0.0-2.0) to phased genotype string
-fn format_dosage_as_genotype(dosage: f64) -> String {
+/// Convert dosage value (0.0-2.0) to a phased genotype
+///
+/// Haploid (male non-PAR X, Y, MT) and absent sites are formatted by
+/// `Genotype::from_dosage` as "0"/"1" or ".".
+fn format_dosage_as_genotype(dosage: f64, ploidy: u8) -> Genotype {
+    if ploidy < 2 {
+        return Genotype::from_dosage(dosage, ploidy);
+    }
+
     // Round to nearest 0.5 for determining allele counts
     let rounded = (dosage * 2.0).round() / 2.0;
 
     match rounded {
-        d if d <= 0.25 => "0|0".to_string(),      // Reference/Reference
-        d if d <= 0.75 => "0|1".to_string(),      // Reference/Alt (assume phased)
-        d if d <= 1.25 => "0|1".to_string(),      // Het (dosage ~1.0)
-        d if d <= 1.75 => "1|1".to_string(),      // Alt/Alt (dosage ~1.5-1.75)
-        _ => "1|1".to_string(),                   // Alt/Alt
+        d if d <= 0.25 => Genotype::from_alt_count(0),      // Reference/Reference
+        d if d <= 0.75 => Genotype::from_alt_count(1),      // Reference/Alt (assume phased)
+        d if d <= 1.25 => Genotype::from_alt_count(1),      // Het (dosage ~1.0)
+        d if d <= 1.75 => Genotype::from_alt_count(2),      // Alt/Alt (dosage ~1.5-1.75)
+        _ => Genotype::from_alt_count(2),                   // Alt/Alt
+    }
+}
+
+/// Detect the genome build shared by the imputed VCFs
+///
+/// TOPMed returns GRCh38 coordinates while the reference panel is GRCh37;
+/// merging them unconverted would silently match almost nothing, so the
+/// caller lifts other builds over (or fails) and mixed builds fail here.
+/// VCFs whose build cannot be detected are assumed to match the panel.
+fn check_vcf_build(file_builds: &[GenomeBuild]) -> Result<GenomeBuild> {
+    let mut builds: Vec<GenomeBuild> = file_builds
+        .iter()
+        .copied()
+        .filter(GenomeBuild::is_known)
+        .collect();
+    builds.sort_by_key(|build| build.as_str());
+    builds.dedup();
+
+    match builds.as_slice() {
+        [] => {
+            warn!("VCF genome build not detected, assuming {}", REFERENCE_PANEL_BUILD.as_str());
+            Ok(REFERENCE_PANEL_BUILD)
+        }
+        [build] => Ok(*build),
+        _ => anyhow::bail!(
+            "Imputed VCFs mix genome builds ({})",
+            builds.iter().map(|b| b.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+/// Lift imputed VCF records onto the liftover's destination build
+///
+/// Records are regrouped by their lifted chromosome; unmappable records
+/// are dropped and counted in the returned stats.
+fn lift_vcf_data(
+    liftover: &LiftOver,
+    vcf_data: HashMap<u8, Vec<VCFRecord>>,
+) -> (HashMap<u8, Vec<VCFRecord>>, LiftOverStats) {
+    let mut lifted: HashMap<u8, Vec<VCFRecord>> = HashMap::new();
+    let mut stats = liftover.stats();
+
+    for record in vcf_data.into_values().flatten() {
+        let result = liftover.lift_vcf_record(&record);
+        stats.record(&result);
+        if let Ok((record, _)) = result {
+            lifted.entry(record.chromosome).or_default().push(record);
+        }
+    }
+
+    for records in lifted.values_mut() {
+        records.sort_by_key(|r| r.position);
     }
+
+    (lifted, stats)
 }
 
-/// Calculate dosage from phased genotype string
-fn calculate_dosage_from_genotype(genotype: &str) -> f64 {
-    // Handle both phased (|) and unphased (/) separators
-    let alleles: Vec<&str> = genotype.split(|c| c == '|' || c == '/').collect();
+/// Lift imputation server .info records onto the liftover's destination build
+///
+/// Records are re-keyed by their lifted (position, REF, ALT) so they match
+/// the lifted VCF records. Unmapped records are dropped; their VCF records
+/// were dropped (and counted) by `lift_vcf_data`.
+fn lift_info_data(liftover: &LiftOver, info: ImputationInfoByChromosome) -> ImputationInfoByChromosome {
+    let mut lifted: ImputationInfoByChromosome = HashMap::new();
 
-    if alleles.len() != 2 {
-        return 0.0; // Default for invalid genotype
+    for record in info.into_values().flat_map(HashMap::into_values) {
+        if let Ok((record, _)) = liftover.lift_info_record(&record) {
+            let key = (record.position, record.ref_allele.clone(), record.alt_allele.clone());
+            lifted.entry(record.chromosome).or_default().insert(key, record);
+        }
     }
 
-    let a1 = alleles[0].parse::<u8>().unwrap_or(0);
-    let a2 = alleles[1].parse::<u8>().unwrap_or(0);
+    lifted
+}
+
+/// Imputation R2 below which a user genotype is flagged as low quality
+const LOW_QUALITY_R2: f64 = 0.3;
 
-    (a1 + a2) as f64
+/// Data source and quality for an imputed user genotype
+///
+/// Prefers the imputation server's .info annotation (typed/imputed flag and
+/// EmpRsq) over the VCF INFO R2 when the variant is listed there.
+fn imputed_source(vcf: &VCFRecord, info: Option<&ImputationInfoRecord>) -> (DataSource, Option<f64>) {
+    if let Some(info) = info {
+        return (info.data_source(LOW_QUALITY_R2), info.quality().or(vcf.imputation_quality));
+    }
+
+    let source = if vcf.imputation_quality.unwrap_or(1.0) < LOW_QUALITY_R2 {
+        DataSource::ImputedLowQual
+    } else {
+        DataSource::Imputed
+    };
+    (source, vcf.imputation_quality)
+}
+
+/// Per-chromosome counts from merging the user into the reference panel
+#[derive(Debug, Default)]
+struct ChromosomeMergeStats {
+    /// Strand resolution of genotyped calls
+    strand: StrandStats,
+    /// Genotyped calls joined by rsID because none sat at the panel position
+    rsid_joined: usize,
 }
 
 /// Uploaded files structure
 struct UploadedFiles {
+    /// Raw genotype file, or the .bed file of a PLINK fileset
     genome_file: PathBuf,
     vcf_files: Vec<PathBuf>,
+    /// Imputation server .info files (chrN.info.gz)
+    info_files: Vec<PathBuf>,
     pgs_file: Option<PathBuf>,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use genetics_processor::chromosomes::CHR_Y;
+    use genetics_processor::models::ReferencePanelVariant;
+    use genetics_processor::parsers::vcf::VCFSampleValues;
+    use genetics_processor::parsers::InfoVariantStatus;
+
+    fn panel_variant(chromosome: u8, position: u64, rsid: &str) -> ReferencePanelVariant {
+        ReferencePanelVariant {
+            chromosome,
+            position,
+            rsid: Some(rsid.to_string()),
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            phased: true,
+            allele_freq: Some(0.3),
+            minor_allele_freq: Some(0.3),
+            imputation_quality: Some(0.95),
+            is_typed: true,
+            sample_genotypes: vec![Genotype::haploid(Some(0)); 50],
+        }
+    }
+
+    fn genome_record(chromosome: &str, position: u64, rsid: &str, genotype: &str) -> Genome23Record {
+        Genome23Record {
+            rsid: rsid.to_string(),
+            chromosome: chromosome.to_string(),
+            position,
+            genotype: genotype.to_string(),
+        }
+    }
+
+    fn vcf_record(chromosome: u8, position: u64, dosage: f64) -> VCFRecord {
+        VCFRecord {
+            rsid: format!("{}:{}", chromosome, position),
+            chromosome,
+            position,
+            genome_build: Default::default(),
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            dosage,
+            imputation_quality: Some(0.9),
+            samples: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_female_chr_y_call_is_missing() {
+        let panel = vec![panel_variant(CHR_Y, 10_000_000, "rs1")];
+        let genome: HashMap<u64, Genome23Record> =
+            HashMap::from([(10_000_000, genome_record("Y", 10_000_000, "rs1", "G"))]);
+        let vcf = vec![vcf_record(CHR_Y, 10_000_000, 1.0)];
+        let mut concordance = ConcordanceAnalysis::new();
+
+        let (merged, _) = JobProcessor::merge_single_chromosome_multi_sample(
+            CHR_Y,
+            &panel,
+            &genome,
+            &vcf,
+            &[],
+            &HashMap::new(),
+            Sex::Female,
+            ModelQualityThreshold::NoFilter,
+            &mut concordance,
+        )
+        .unwrap();
+
+        let user = &merged[0].samples[50];
+        assert_eq!(user.genotype, Genotype::missing(0));
+        assert_eq!(user.dosage, 0.0);
+        assert_ne!(user.source, DataSource::Genotyped);
+        assert_eq!(concordance.report().overall.genotyped, 0);
+
+        // The same call in a male sample is a haploid genotyped call
+        let (merged, _) = JobProcessor::merge_single_chromosome_multi_sample(
+            CHR_Y,
+            &panel,
+            &genome,
+            &vcf,
+            &[],
+            &HashMap::new(),
+            Sex::Male,
+            ModelQualityThreshold::NoFilter,
+            &mut concordance,
+        )
+        .unwrap();
+
+        let user = &merged[0].samples[50];
+        assert_eq!(user.genotype, Genotype::haploid(Some(1)));
+        assert_eq!(user.dosage, 1.0);
+        assert_eq!(user.source, DataSource::Genotyped);
+        assert_eq!(concordance.report().overall.genotyped, 1);
+    }
+
+    #[test]
+    fn test_extra_vcf_samples_follow_user() {
+        let panel = vec![panel_variant(1, 100, "rs1"), panel_variant(1, 200, "rs2")];
+        let mut vcf = vcf_record(1, 100, 1.0);
+        vcf.samples = vec![
+            VCFSampleValues {
+                genotype: Some("1|1".parse().unwrap()),
+                dosage: Some(1.9),
+                ..Default::default()
+            },
+            VCFSampleValues {
+                genotype: Some("0|1".parse().unwrap()),
+                dosage: Some(1.0),
+                ..Default::default()
+            },
+        ];
+        let mother = vec!["mother".to_string()];
+
+        let (merged, _) = JobProcessor::merge_single_chromosome_multi_sample(
+            1,
+            &panel,
+            &HashMap::new(),
+            &[vcf],
+            &mother,
+            &HashMap::new(),
+            Sex::Female,
+            ModelQualityThreshold::NoFilter,
+            &mut ConcordanceAnalysis::new(),
+        )
+        .unwrap();
+
+        // 50 reference samples, the user, then the VCF's other samples by header name
+        let samples = &merged[0].samples;
+        assert_eq!(samples.len(), 52);
+        assert_eq!(samples[50].sample_id, USER_SAMPLE_ID);
+        assert_eq!(samples[50].dosage, 1.0);
+        assert_eq!(samples[51].sample_id, "mother");
+        assert_eq!(samples[51].genotype.to_string(), "1|1");
+        assert_eq!(samples[51].dosage, 1.9);
+        assert_eq!(samples[51].source, DataSource::Imputed);
+
+        // No imputed record at rs2: the extra sample is a missing call
+        let mother_rs2 = &merged[1].samples[51];
+        assert_eq!(mother_rs2.sample_id, "mother");
+        assert!(mother_rs2.genotype.is_missing());
+        assert_eq!(mother_rs2.dosage, 0.0);
+    }
+
+    #[test]
+    fn test_extra_samples_without_gt_use_site_ploidy() {
+        use genetics_processor::chromosomes::CHR_MT;
+
+        let panel = vec![panel_variant(CHR_MT, 100, "rs1"), panel_variant(CHR_MT, 200, "rs2")];
+        let mut vcf = vcf_record(CHR_MT, 100, 1.0);
+        vcf.samples = vec![
+            VCFSampleValues { dosage: Some(0.9), ..Default::default() },
+            VCFSampleValues { dosage: Some(1.0), ..Default::default() },
+        ];
+
+        let (merged, _) = JobProcessor::merge_single_chromosome_multi_sample(
+            CHR_MT,
+            &panel,
+            &HashMap::new(),
+            &[vcf],
+            &["father".to_string()],
+            &HashMap::new(),
+            Sex::Female,
+            ModelQualityThreshold::NoFilter,
+            &mut ConcordanceAnalysis::new(),
+        )
+        .unwrap();
+
+        // DS-only MT calls are haploid, not a diploid 0/1
+        assert_eq!(merged[0].samples[51].genotype, Genotype::haploid(Some(1)));
+        assert_eq!(merged[1].samples[51].genotype, Genotype::missing(1));
+    }
+
+    #[test]
+    fn test_rsid_join_only_when_position_misses() {
+        let panel = vec![
+            panel_variant(1, 100, "rs1"),
+            panel_variant(1, 200, "rs2"),
+            panel_variant(1, 300, "rs3"),
+            panel_variant(1, 400, "rs4"),
+            panel_variant(1, 500, "i5"),
+        ];
+        let genome: HashMap<u64, Genome23Record> = [
+            genome_record("1", 100, "rs1", "AG"),
+            // rs2 is reported at a different position
+            genome_record("1", 250, "rs2", "GG"),
+            // The record at 300 wins over rs3 elsewhere
+            genome_record("1", 300, "rs99", "AA"),
+            genome_record("1", 350, "rs3", "GG"),
+            // Internal IDs are never joined on
+            genome_record("1", 550, "i5", "GG"),
+        ]
+        .into_iter()
+        .map(|r| (r.position, r))
+        .collect();
+        let mut concordance = ConcordanceAnalysis::new();
+
+        let (merged, stats) = JobProcessor::merge_single_chromosome_multi_sample(
+            1,
+            &panel,
+            &genome,
+            &[],
+            &[],
+            &HashMap::new(),
+            Sex::Female,
+            ModelQualityThreshold::NoFilter,
+            &mut concordance,
+        )
+        .unwrap();
+
+        assert_eq!(stats.rsid_joined, 1);
+        let user: Vec<(DataSource, f64)> = merged.iter().map(|v| (v.samples[50].source.clone(), v.samples[50].dosage)).collect();
+        assert_eq!(
+            user,
+            vec![
+                (DataSource::Genotyped, 1.0),
+                (DataSource::Genotyped, 2.0),
+                (DataSource::Genotyped, 0.0),
+                (DataSource::ImputedLowQual, 0.0),
+                (DataSource::ImputedLowQual, 0.0),
+            ]
+        );
+        assert!(merged[3].samples[50].genotype.is_missing());
+        assert!(merged[4].samples[50].genotype.is_missing());
+    }
+
+    #[test]
+    fn test_ambiguous_calls_follow_strand_evidence() {
+        // 60 A/G sites plus an A/T site where A is the minor allele
+        let mut panel: Vec<ReferencePanelVariant> =
+            (1..=60).map(|i| panel_variant(1, i * 100, &format!("rs{}", i))).collect();
+        panel.push(ReferencePanelVariant {
+            alt_allele: "T".to_string(),
+            allele_freq: Some(0.8),
+            minor_allele_freq: Some(0.2),
+            ..panel_variant(1, 10_000, "rs100")
+        });
+
+        // The imputed VCF agrees with the genotyped call: the user is AA
+        let vcf = vec![VCFRecord { alt_allele: "T".to_string(), ..vcf_record(1, 10_000, 0.0) }];
+
+        // The first `flipped_sites` A/G sites are reported on the other strand
+        let merge = |flipped_sites: u64| {
+            let mut genome: HashMap<u64, Genome23Record> = (1..=60)
+                .map(|i| {
+                    let call = if i <= flipped_sites { "TC" } else { "AG" };
+                    (i * 100, genome_record("1", i * 100, &format!("rs{}", i), call))
+                })
+                .collect();
+            genome.insert(10_000, genome_record("1", 10_000, "rs100", "AA"));
+            let (merged, stats) = JobProcessor::merge_single_chromosome_multi_sample(
+                1,
+                &panel,
+                &genome,
+                &vcf,
+                &[],
+                &HashMap::new(),
+                Sex::Female,
+                ModelQualityThreshold::NoFilter,
+                    &mut ConcordanceAnalysis::new(),
+            )
+            .unwrap();
+            let user = &merged[60].samples[50];
+            (user.source.clone(), user.dosage, stats.strand)
+        };
+
+        // Forward-strand file: the homozygous-minor call is kept
+        let (source, dosage, strand) = merge(0);
+        assert_eq!((source, dosage), (DataSource::Genotyped, 0.0));
+        assert_eq!(strand, StrandStats::default());
+
+        // Mostly forward file with some other-strand calls: AA is not
+        // rewritten to the major TT but dropped, so the imputed minor
+        // homozygote survives
+        let (source, dosage, strand) = merge(6);
+        assert_eq!((source, dosage), (DataSource::Imputed, 0.0));
+        assert_eq!(strand, StrandStats { flipped: 6, dropped: 1 });
+
+        // Other-strand file: likewise dropped, not complemented from frequency
+        let (source, dosage, strand) = merge(60);
+        assert_eq!((source, dosage), (DataSource::Imputed, 0.0));
+        assert_eq!(strand, StrandStats { flipped: 60, dropped: 1 });
+    }
+
+    #[test]
+    fn test_lift_vcf_data() {
+        // GRCh38 chr1 0-1000 sits 500 bases later on GRCh37; chr2 0-100 is on GRCh37 chr3
+        let chain = "\
+chain 1000 chr1 248956422 + 0 1000 chr1 249250621 + 500 1500 1
+1000
+
+chain 100 chr2 242193529 + 0 100 chr3 198022430 + 0 100 2
+100
+";
+        let liftover = LiftOver::parse_reader(
+            std::io::BufReader::new(chain.as_bytes()),
+            GenomeBuild::GRCh38,
+            GenomeBuild::GRCh37,
+        )
+        .unwrap();
+
+        let vcf_data = HashMap::from([
+            (1, vec![vcf_record(1, 300, 1.0), vcf_record(1, 100, 0.0), vcf_record(1, 2000, 2.0)]),
+            (2, vec![vcf_record(2, 10, 1.0)]),
+        ]);
+        let (lifted, mut stats) = lift_vcf_data(&liftover, vcf_data);
+
+        // Regrouped by destination chromosome and sorted by lifted position
+        let positions: Vec<u64> = lifted[&1].iter().map(|r| r.position).collect();
+        assert_eq!(positions, vec![600, 800]);
+        assert_eq!(lifted[&3][0].position, 10);
+        assert!(!lifted.contains_key(&2));
+        assert!(lifted.values().flatten().all(|r| r.genome_build == GenomeBuild::GRCh37));
+        assert_eq!(stats.lifted, 3);
+        assert_eq!(stats.unmapped_count(), 1);
+
+        // The panel has C at the second lifted position
+        let panel = vec![
+            panel_variant(1, 600, "rs1"),
+            ReferencePanelVariant { ref_allele: "C".to_string(), ..panel_variant(1, 800, "rs2") },
+        ];
+        stats.count_ref_mismatches(&lifted[&1], &panel);
+        assert_eq!(stats.ref_mismatch, 1);
+    }
+
+    #[test]
+    fn test_info_records_follow_liftover() {
+        // GRCh38 chr1 0-1000 sits 500 bases later on GRCh37; chr2 0-100 maps
+        // to the reverse strand
+        let chain = "\
+chain 1000 chr1 248956422 + 0 1000 chr1 249250621 + 500 1500 1
+1000
+
+chain 100 chr2 242193529 + 0 100 chr2 243199373 - 0 100 2
+100
+";
+        let liftover = LiftOver::parse_reader(
+            std::io::BufReader::new(chain.as_bytes()),
+            GenomeBuild::GRCh38,
+            GenomeBuild::GRCh37,
+        )
+        .unwrap();
+
+        let info_record = |chromosome: u8, position: u64| ImputationInfoRecord {
+            chromosome,
+            position,
+            ref_allele: "A".to_string(),
+            alt_allele: "G".to_string(),
+            alt_frequency: Some(0.3),
+            maf: Some(0.3),
+            rsq: Some(0.95),
+            emp_rsq: Some(0.98),
+            status: InfoVariantStatus::Genotyped,
+        };
+        let info_entry = |chromosome: u8, position: u64| {
+            (chromosome, HashMap::from([((position, "A".to_string(), "G".to_string()), info_record(chromosome, position))]))
+        };
+
+        let vcf_data = HashMap::from([(1, vec![vcf_record(1, 100, 1.0)]), (2, vec![vcf_record(2, 10, 1.0)])]);
+        let info = HashMap::from([info_entry(1, 100), info_entry(2, 10)]);
+        let (vcf, _) = lift_vcf_data(&liftover, vcf_data);
+        let info = lift_info_data(&liftover, info);
+
+        // Both sides carry the same lifted key, complemented on the reverse strand
+        for (chr, records) in &vcf {
+            let record = &records[0];
+            let key = (record.position, record.ref_allele.clone(), record.alt_allele.clone());
+            assert!(info[chr].contains_key(&key), "chr{} key {:?} missing from lifted info", chr, key);
+        }
+        assert_eq!(vcf[&2][0].ref_allele, "T");
+        assert_eq!(vcf[&1][0].position, 600);
+
+        // The lifted annotation reaches the merge: typed, with EmpRsq as quality
+        let panel = vec![panel_variant(1, 600, "rs1")];
+        let (merged, _) = JobProcessor::merge_single_chromosome_multi_sample(
+            1,
+            &panel,
+            &HashMap::new(),
+            &vcf[&1],
+            &[],
+            &info[&1],
+            Sex::Female,
+            ModelQualityThreshold::NoFilter,
+            &mut ConcordanceAnalysis::new(),
+        )
+        .unwrap();
+        let user = &merged[0].samples[50];
+        assert_eq!(user.source, DataSource::Genotyped);
+        assert_eq!(user.imputation_quality, Some(0.98));
+    }
+}
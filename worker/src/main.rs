@@ -21,6 +21,7 @@ use std::time::Duration;
 use tracing::{error, info, warn, Level};
 use uuid::Uuid;
 use zip::{ZipWriter, write::SimpleFileOptions};
+use genetics_processor::pgs_interpretation::InterpretationConfig;
 
 mod email;
 mod job_processor;
@@ -95,8 +96,71 @@ async fn main() -> Result<()> {
 
     info!("Reference panel database accessible at {:?}", reference_panel_path);
 
+    // Get PGS Catalog scoring files directory (optional, for in-house scores)
+    let pgs_scoring_dir = std::env::var("PGS_SCORING_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| encrypted_volume_path.join("pgs_scoring"));
+
+    let pgs_scoring_dir = if pgs_scoring_dir.is_dir() {
+        info!("PGS scoring files directory accessible at {:?}", pgs_scoring_dir);
+        Some(pgs_scoring_dir)
+    } else {
+        warn!("No PGS scoring files directory at {:?}, scores require an uploaded scores file", pgs_scoring_dir);
+        None
+    };
+
+    // Get UCSC liftover chain files directory (optional, for GRCh38 imputation results)
+    let liftover_chain_dir = std::env::var("LIFTOVER_CHAIN_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| encrypted_volume_path.join("liftover"));
+
+    let liftover_chain_dir = if liftover_chain_dir.is_dir() {
+        info!("Liftover chain files directory accessible at {:?}", liftover_chain_dir);
+        Some(liftover_chain_dir)
+    } else {
+        warn!("No liftover chain files directory at {:?}, only GRCh37 imputation results can be merged", liftover_chain_dir);
+        None
+    };
+
+    // Get dbSNP rsID merge database (optional, replaces retired rsIDs)
+    let rsid_merge_db = std::env::var("RSID_MERGE_DB")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| encrypted_volume_path.join("rsid_merge.db"));
+
+    let rsid_merge_db = if rsid_merge_db.exists() {
+        info!("rsID merge database accessible at {:?}", rsid_merge_db);
+        Some(rsid_merge_db)
+    } else {
+        warn!("No rsID merge database at {:?}, retired rsIDs will not be updated", rsid_merge_db);
+        None
+    };
+
+    // Get per-trait PGS interpretation settings (optional, percentiles only without it)
+    let pgs_trait_config_path = std::env::var("PGS_TRAIT_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| encrypted_volume_path.join("pgs_traits.json"));
+
+    let interpretation_config = if pgs_trait_config_path.exists() {
+        match InterpretationConfig::load(&pgs_trait_config_path) {
+            Ok(config) => {
+                info!("Loaded PGS trait config for {} traits from {:?}", config.traits.len(), pgs_trait_config_path);
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring PGS trait config {:?}: {:#}", pgs_trait_config_path, e);
+                InterpretationConfig::default()
+            }
+        }
+    } else {
+        info!("No PGS trait config at {:?}, predicted trait values disabled", pgs_trait_config_path);
+        InterpretationConfig::default()
+    };
+
     // Create worker instance
-    let worker = Worker::new(db_pool, redis_conn, encrypted_volume_path, reference_panel_path);
+    let worker = Worker::new(db_pool, redis_conn, encrypted_volume_path, reference_panel_path, pgs_scoring_dir)
+        .with_interpretation_config(interpretation_config)
+        .with_liftover_chain_dir(liftover_chain_dir)
+        .with_rsid_merge_db(rsid_merge_db);
 
     // Recover stuck jobs from previous worker instance
     info!("Checking for stuck jobs from previous worker instance...");
@@ -122,18 +186,47 @@ struct Worker {
     redis_conn: ConnectionManager,
     encrypted_volume_path: PathBuf,
     reference_panel_path: PathBuf,
+    pgs_scoring_dir: Option<PathBuf>,
+    liftover_chain_dir: Option<PathBuf>,
+    rsid_merge_db: Option<PathBuf>,
+    interpretation_config: InterpretationConfig,
 }
 
 impl Worker {
-    fn new(db_pool: PgPool, redis_conn: ConnectionManager, encrypted_volume_path: PathBuf, reference_panel_path: PathBuf) -> Self {
+    fn new(
+        db_pool: PgPool,
+        redis_conn: ConnectionManager,
+        encrypted_volume_path: PathBuf,
+        reference_panel_path: PathBuf,
+        pgs_scoring_dir: Option<PathBuf>,
+    ) -> Self {
         Self {
             db_pool,
             redis_conn,
             encrypted_volume_path,
             reference_panel_path,
+            pgs_scoring_dir,
+            liftover_chain_dir: None,
+            rsid_merge_db: None,
+            interpretation_config: InterpretationConfig::default(),
         }
     }
 
+    fn with_interpretation_config(mut self, config: InterpretationConfig) -> Self {
+        self.interpretation_config = config;
+        self
+    }
+
+    fn with_liftover_chain_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.liftover_chain_dir = dir;
+        self
+    }
+
+    fn with_rsid_merge_db(mut self, path: Option<PathBuf>) -> Self {
+        self.rsid_merge_db = path;
+        self
+    }
+
     /// Main processing loop - polls Redis queue for jobs
     async fn run(&self) -> Result<()> {
         let mut job_queue = JobQueue::new(self.redis_conn.clone());
@@ -199,7 +292,13 @@ impl Worker {
             self.reference_panel_path.clone(),
             self.db_pool.clone(),
             self.redis_conn.clone(),
-        );
+        )
+        .with_pgs_scoring_dir(self.pgs_scoring_dir.clone())
+        .with_liftover_chain_dir(self.liftover_chain_dir.clone())
+        .with_rsid_merge_db(self.rsid_merge_db.clone())
+        .with_interpretation_config(self.interpretation_config.clone())
+        .with_pgs_normalization(payload.pgs_normalization)
+        .with_haploid_convention(payload.haploid_convention);
 
         // Execute processing
         match processor.process(&payload.output_formats, payload.quality_threshold).await {
@@ -61,6 +61,27 @@ impl Default for QualityThreshold {
     }
 }
 
+/// Normalization applied to scaled polygenic scores (must match API gateway)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PgsNormalization {
+    #[default]
+    ReferenceZscore,    // z-score against the 50 reference samples (default)
+    Zscore,             // z-score against all 51 samples, including the user
+    RankInverseNormal,  // Rank-based inverse normal transform
+    MinMax,             // Rescaled to 0-1
+    None,               // Raw scores
+}
+
+/// Dosage coding of haploid genotypes in the output (must match API gateway)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HaploidConvention {
+    #[default]
+    AlleleCount,        // 0/1 ALT allele count (default)
+    Homozygous,         // 0/2, hemizygous calls coded as homozygotes (PLINK-style)
+}
+
 /// Job payload from Redis queue (must match API gateway)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobPayload {
@@ -80,6 +101,12 @@ pub struct JobPayload {
     /// VCF format preference: "merged" or "per_chromosome" (defaults to "merged")
     #[serde(default = "default_vcf_format")]
     pub vcf_format: String,
+    /// Normalization for scaled polygenic scores (defaults to reference_zscore)
+    #[serde(default)]
+    pub pgs_normalization: PgsNormalization,
+    /// Haploid genotype dosage coding (defaults to allele_count, 0/1)
+    #[serde(default)]
+    pub haploid_convention: HaploidConvention,
 }
 
 fn default_vcf_format() -> String {